@@ -56,5 +56,93 @@ pub enum Action {
     Import {
         #[arg(value_name = "FILE")]
         file: String,
+        /// Only report which feeds would be added, are duplicates or fail.
+        #[arg(long)]
+        dry_run: bool,
+        /// Print the summary as json.
+        #[arg(long)]
+        json: bool,
     },
+    #[command(flatten)]
+    Remote(Remote),
+    /// Log in to last.fm once and store the session key used for scrobbling.
+    LastfmLogin,
+    /// Run the playback daemon in the foreground. The tui starts one when none is running.
+    Daemon {
+        /// Ask the running daemon to quit.
+        #[arg(long)]
+        stop: bool,
+    },
+}
+
+/// Commands for a running termusic, sent over its control socket.
+#[derive(Subcommand, Debug)]
+pub enum Remote {
+    /// Start or resume playing.
+    Play,
+    /// Pause playing.
+    Pause,
+    /// Toggle between playing and paused.
+    Toggle,
+    /// Skip to the next track.
+    Next,
+    /// Go back to the previous track.
+    Prev,
+    /// Seek by seconds with a sign, like +10 or -10, otherwise to the given second.
+    Seek {
+        #[arg(value_name = "POSITION", allow_hyphen_values = true)]
+        position: String,
+    },
+    /// Set the volume from 0 to 100, or change it with a sign, like +5 or -5.
+    Volume {
+        #[arg(value_name = "VOLUME", allow_hyphen_values = true)]
+        volume: String,
+    },
+    /// Add files, directories or playlists to the queue.
+    Add {
+        #[arg(value_name = "PATH", required = true)]
+        paths: Vec<String>,
+    },
+    /// Print the playing state and the current track.
+    Status {
+        /// Print as json.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Commands for the queue.
+    Queue {
+        #[command(subcommand)]
+        action: QueueAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum QueueAction {
+    /// Print the queue, starting with the current track.
+    List {
+        /// Print as json.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_args() {
+        let args = Args::try_parse_from(["termusic", "seek", "-10"]).unwrap();
+        assert!(matches!(
+            args.action,
+            Some(Action::Remote(Remote::Seek { position })) if position == "-10"
+        ));
+        let args = Args::try_parse_from(["termusic", "queue", "list", "--json"]).unwrap();
+        assert!(matches!(
+            args.action,
+            Some(Action::Remote(Remote::Queue {
+                action: QueueAction::List { json: true }
+            }))
+        ));
+    }
 }
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use ytd_rs::Arg;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioCodec {
+    Opus,
+    M4a,
+    Flac,
+    Mp3,
+}
+
+impl AudioCodec {
+    /// Extension of the file yt-dlp writes for this codec.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Opus => "opus",
+            Self::M4a => "m4a",
+            Self::Flac => "flac",
+            Self::Mp3 => "mp3",
+        }
+    }
+}
+
+impl std::fmt::Display for AudioCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+/// How youtube and tag editor downloads are converted and where they are saved.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadProfile {
+    pub name: String,
+    pub codec: AudioCodec,
+    /// Passed to `--audio-quality`, either a bitrate like `192K` or a vbr quality from 0 (best)
+    /// to 10. Leave empty for the yt-dlp default. Ignored for flac.
+    pub bitrate: String,
+    /// yt-dlp output template, relative to the destination. Only used for youtube downloads.
+    pub filename_template: String,
+    /// Folder to download to. Leave empty to use the folder selected in the library.
+    pub destination: String,
+}
+
+impl DownloadProfile {
+    pub fn new(name: &str, codec: AudioCodec, bitrate: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            codec,
+            bitrate: bitrate.to_string(),
+            filename_template: "%(title).90s.%(ext)s".to_string(),
+            destination: String::new(),
+        }
+    }
+
+    /// The yt-dlp arguments for extracting and converting the audio.
+    pub fn audio_args(&self) -> Vec<Arg> {
+        let mut args = vec![
+            Arg::new("--extract-audio"),
+            Arg::new_with_arg("--audio-format", self.codec.extension()),
+        ];
+        if !self.bitrate.is_empty() && self.codec != AudioCodec::Flac {
+            args.push(Arg::new_with_arg("--audio-quality", &self.bitrate));
+        }
+        args
+    }
+
+    /// The configured destination, or `fallback` if there is none.
+    pub fn destination_or(&self, fallback: PathBuf) -> PathBuf {
+        if self.destination.is_empty() {
+            fallback
+        } else {
+            PathBuf::from(shellexpand::tilde(&self.destination).to_string())
+        }
+    }
+}
+
+pub fn default_profiles() -> Vec<DownloadProfile> {
+    vec![
+        DownloadProfile::new("mp3", AudioCodec::Mp3, ""),
+        DownloadProfile::new("opus", AudioCodec::Opus, "0"),
+        DownloadProfile::new("m4a", AudioCodec::M4a, "0"),
+        DownloadProfile::new("flac", AudioCodec::Flac, ""),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_profile_toml() {
+        let profile: DownloadProfile = toml::from_str(
+            r#"
+            name = "archive"
+            codec = "flac"
+            bitrate = "320K"
+            filename_template = "%(uploader)s/%(title)s.%(ext)s"
+            destination = "/tmp/archive"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(profile.codec, AudioCodec::Flac);
+        // flac is lossless, so the bitrate is not passed on
+        assert_eq!(profile.audio_args().len(), 2);
+        assert_eq!(
+            profile.destination_or(PathBuf::from("/music")),
+            PathBuf::from("/tmp/archive")
+        );
+        let mp3 = DownloadProfile::new("mp3", AudioCodec::Mp3, "192K");
+        assert_eq!(mp3.audio_args().len(), 3);
+        assert_eq!(
+            mp3.destination_or(PathBuf::from("/music")),
+            PathBuf::from("/music")
+        );
+    }
+}
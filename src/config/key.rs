@@ -54,6 +54,8 @@ pub struct Keys {
     pub global_xywh_zoom_in: BindingForEvent,
     pub global_xywh_zoom_out: BindingForEvent,
     pub global_xywh_hide: BindingForEvent,
+    pub global_sleep_timer: BindingForEvent,
+    pub global_sleep_timer_cancel: BindingForEvent,
     pub library_load_dir: BindingForEvent,
     pub library_delete: BindingForEvent,
     pub library_yank: BindingForEvent,
@@ -75,17 +77,25 @@ pub struct Keys {
     pub playlist_swap_up: BindingForEvent,
     pub playlist_cmus_lqueue: BindingForEvent,
     pub playlist_cmus_tqueue: BindingForEvent,
+    pub playlist_download: BindingForEvent,
     pub database_add_all: BindingForEvent,
+    pub tag_editor_lookup: BindingForEvent,
+    pub youtube_search_mark: BindingForEvent,
+    pub youtube_search_mark_page: BindingForEvent,
+    pub youtube_search_play: BindingForEvent,
     pub config_save: BindingForEvent,
     pub podcast_mark_played: BindingForEvent,
     pub podcast_mark_all_played: BindingForEvent,
     pub podcast_episode_download: BindingForEvent,
     pub podcast_episode_delete_file: BindingForEvent,
+    pub podcast_episode_show_notes: BindingForEvent,
     pub podcast_delete_feed: BindingForEvent,
     pub podcast_delete_all_feeds: BindingForEvent,
     pub podcast_search_add_feed: BindingForEvent,
     pub podcast_refresh_feed: BindingForEvent,
     pub podcast_refresh_all_feeds: BindingForEvent,
+    pub podcast_toggle_skip_silence: BindingForEvent,
+    pub podcast_sync_now: BindingForEvent,
 }
 
 impl Keys {
@@ -127,6 +137,8 @@ impl Keys {
             .chain(once(self.global_xywh_zoom_in))
             .chain(once(self.global_xywh_zoom_out))
             .chain(once(self.global_xywh_hide))
+            .chain(once(self.global_sleep_timer))
+            .chain(once(self.global_sleep_timer_cancel))
         // .chain(once(self.config_save))
     }
 
@@ -155,6 +167,7 @@ impl Keys {
             .chain(once(self.playlist_swap_up))
             .chain(once(self.playlist_cmus_lqueue))
             .chain(once(self.playlist_cmus_tqueue))
+            .chain(once(self.playlist_download))
     }
 
     fn iter_podcast(&self) -> impl Iterator<Item = BindingForEvent> {
@@ -163,6 +176,8 @@ impl Keys {
             .chain(once(self.podcast_refresh_all_feeds))
             .chain(once(self.podcast_delete_feed))
             .chain(once(self.podcast_delete_all_feeds))
+            .chain(once(self.podcast_toggle_skip_silence))
+            .chain(once(self.podcast_sync_now))
     }
 
     fn iter_episode(&self) -> impl Iterator<Item = BindingForEvent> {
@@ -170,6 +185,19 @@ impl Keys {
             .chain(once(self.podcast_mark_all_played))
             .chain(once(self.podcast_episode_download))
             .chain(once(self.podcast_episode_delete_file))
+            .chain(once(self.podcast_episode_show_notes))
+    }
+
+    // the results popup also moves and closes with the global keys
+    fn iter_youtube_search(&self) -> impl Iterator<Item = BindingForEvent> {
+        once(self.youtube_search_mark)
+            .chain(once(self.youtube_search_mark_page))
+            .chain(once(self.youtube_search_play))
+            .chain(once(self.global_quit))
+            .chain(once(self.global_down))
+            .chain(once(self.global_up))
+            .chain(once(self.global_goto_top))
+            .chain(once(self.global_goto_bottom))
     }
 
     pub fn has_unique_elements(&self) -> bool {
@@ -178,11 +206,15 @@ impl Keys {
         let mut uniq_playlist = HashSet::new();
         let mut uniq_podcast = HashSet::new();
         let mut uniq_episode = HashSet::new();
+        let mut uniq_youtube_search = HashSet::new();
         self.iter_global().all(move |x| uniq_global.insert(x))
             && self.iter_library().all(move |x| uniq_library.insert(x))
             && self.iter_playlist().all(move |x| uniq_playlist.insert(x))
             && self.iter_podcast().all(move |x| uniq_podcast.insert(x))
             && self.iter_episode().all(move |x| uniq_episode.insert(x))
+            && self
+                .iter_youtube_search()
+                .all(move |x| uniq_youtube_search.insert(x))
     }
 }
 
@@ -506,6 +538,26 @@ impl Default for Keys {
                 code: Key::Char('s'),
                 modifier: KeyModifiers::NONE,
             },
+            playlist_download: BindingForEvent {
+                code: Key::Char('Y'),
+                modifier: KeyModifiers::SHIFT,
+            },
+            tag_editor_lookup: BindingForEvent {
+                code: Key::Char('M'),
+                modifier: KeyModifiers::SHIFT,
+            },
+            youtube_search_mark: BindingForEvent {
+                code: Key::Char(' '),
+                modifier: KeyModifiers::NONE,
+            },
+            youtube_search_mark_page: BindingForEvent {
+                code: Key::Char('a'),
+                modifier: KeyModifiers::CONTROL,
+            },
+            youtube_search_play: BindingForEvent {
+                code: Key::Char('p'),
+                modifier: KeyModifiers::NONE,
+            },
             global_layout_treeview: BindingForEvent {
                 code: Key::Char('1'),
                 modifier: KeyModifiers::NONE,
@@ -578,6 +630,10 @@ impl Default for Keys {
                 code: Key::Char('x'),
                 modifier: KeyModifiers::NONE,
             },
+            podcast_episode_show_notes: BindingForEvent {
+                code: Key::Char('i'),
+                modifier: KeyModifiers::NONE,
+            },
             podcast_delete_feed: BindingForEvent {
                 code: Key::Char('d'),
                 modifier: KeyModifiers::NONE,
@@ -586,6 +642,14 @@ impl Default for Keys {
                 code: Key::Char('D'),
                 modifier: KeyModifiers::SHIFT,
             },
+            podcast_toggle_skip_silence: BindingForEvent {
+                code: Key::Char('S'),
+                modifier: KeyModifiers::SHIFT,
+            },
+            podcast_sync_now: BindingForEvent {
+                code: Key::Char('u'),
+                modifier: KeyModifiers::NONE,
+            },
             global_xywh_move_left: BindingForEvent {
                 code: Key::Left,
                 modifier: CONTROL_SHIFT,
@@ -614,6 +678,29 @@ impl Default for Keys {
                 code: Key::End,
                 modifier: CONTROL_SHIFT,
             },
+            global_sleep_timer: BindingForEvent {
+                code: Key::Char('z'),
+                modifier: KeyModifiers::NONE,
+            },
+            global_sleep_timer_cancel: BindingForEvent {
+                code: Key::Char('Z'),
+                modifier: KeyModifiers::SHIFT,
+            },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_youtube_search_conflicts() {
+        let mut keys = Keys::default();
+        assert!(keys.has_unique_elements());
+        keys.youtube_search_play = keys.global_down;
+        assert!(!keys.has_unique_elements());
+        keys.youtube_search_play = keys.youtube_search_mark;
+        assert!(!keys.has_unique_elements());
+    }
+}
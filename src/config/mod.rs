@@ -21,13 +21,16 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+mod download;
 mod key;
+mod session;
 mod theme;
 
 use crate::player::Loop;
 use crate::ui::components::Xywh;
 use crate::utils::get_app_config_path;
 use anyhow::Result;
+pub use download::{AudioCodec, DownloadProfile};
 use figment::{
     providers::{Format, Serialized, Toml},
     Figment,
@@ -35,8 +38,10 @@ use figment::{
 pub use key::{BindingForEvent, Keys, ALT_SHIFT, CONTROL_ALT, CONTROL_ALT_SHIFT, CONTROL_SHIFT};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+pub use session::{load_session, save_session, PlayerSession};
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 pub use theme::{load_alacritty, ColorTermusic, StyleColorSymbol};
 
 // pub const MUSIC_DIR: [&str; 2] = ["~/Music/mp3", "~/Music"];
@@ -74,6 +79,23 @@ pub enum SeekStep {
     Auto,
 }
 
+impl SeekStep {
+    /// Seconds to seek for a track of the given duration.
+    pub fn seconds(&self, duration: Duration) -> i64 {
+        let long = match self {
+            Self::Short => false,
+            Self::Long => true,
+            // 10 minutes
+            Self::Auto => duration.as_secs() >= 600,
+        };
+        if long {
+            30
+        } else {
+            5
+        }
+    }
+}
+
 impl std::fmt::Display for SeekStep {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let seek_step = match self {
@@ -103,6 +125,24 @@ impl std::fmt::Display for LastPosition {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PodcastSyncService {
+    /// gpodder.net or a compatible server like mygpo
+    Gpodder,
+    /// the nextcloud gpoddersync app
+    Nextcloud,
+}
+
+impl std::fmt::Display for PodcastSyncService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let service = match self {
+            Self::Gpodder => "gpodder",
+            Self::Nextcloud => "nextcloud",
+        };
+        write!(f, "{service}")
+    }
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Settings {
@@ -123,13 +163,96 @@ pub struct Settings {
     pub podcast_simultanious_download: usize,
     pub podcast_max_retries: usize,
     pub podcast_dir: String,
+    /// Base urls of the podcast directories used for search, leave empty to disable one.
+    pub podcast_search_itunes_url: String,
+    pub podcast_search_podcastindex_url: String,
+    pub podcast_search_podcastindex_key: String,
+    pub podcast_search_podcastindex_secret: String,
+    pub podcast_search_gpodder_url: String,
+    /// Server to sync subscriptions and episode progress with, leave empty to disable sync.
+    pub podcast_sync_url: String,
+    pub podcast_sync_service: PodcastSyncService,
+    pub podcast_sync_username: String,
+    pub podcast_sync_password: String,
+    pub podcast_sync_device: String,
+    pub podcast_sync_on_start: bool,
+    /// Base urls of the invidious instances used for youtube, leave empty to use the public list
+    /// from api.invidious.io.
+    pub invidious_instances: Vec<String>,
+    /// Instance to try first, as long as it is not cooling down after a failure.
+    pub invidious_preferred_instance: String,
+    /// Minutes to skip an instance after a failed request.
+    pub invidious_cooldown_minutes: u64,
+    /// Name of the entry in `download_profiles` used for youtube and tag editor downloads.
+    pub download_profile: String,
+    /// Lines of synced lyrics shown above and below the current line.
+    pub lyric_context_lines: usize,
+    /// Seconds before the lyric view follows the song again after scrolling.
+    pub lyric_scroll_timeout: u64,
+    /// Directory searched for `.lrc` and `.txt` lyric files named after the track, besides the
+    /// directory of the track itself.
+    pub lyric_dir: String,
+    /// Base url of an LRCLIB compatible server used in lyric search, leave empty to disable.
+    pub lyric_lrclib_url: String,
+    /// Base url of the musicbrainz web service used to look up tags in the tag editor.
+    pub musicbrainz_url: String,
+    /// Base url of the Cover Art Archive, where covers of musicbrainz releases are fetched.
+    pub musicbrainz_cover_art_url: String,
+    /// Base url of the acoustid service matching tracks by fingerprint.
+    pub acoustid_url: String,
+    /// Acoustid application api key, leave empty to look up by tags only. Fingerprints are
+    /// calculated with `fpcalc` from Chromaprint.
+    pub acoustid_api_key: String,
+    /// Api endpoint of last.fm, scrobbling there is enabled once the api key and session key are
+    /// set. Api accounts are created at <https://www.last.fm/api/account/create>, the session key
+    /// is stored by `termusic lastfm-login`.
+    pub lastfm_url: String,
+    pub lastfm_api_key: String,
+    pub lastfm_api_secret: String,
+    pub lastfm_username: String,
+    pub lastfm_session_key: String,
+    /// Base url of a listenbrainz server, scrobbling there is enabled once the user token is set.
+    pub listenbrainz_url: String,
+    pub listenbrainz_token: String,
+    /// Which kinds of tracks are scrobbled.
+    pub scrobble_music: bool,
+    pub scrobble_podcast: bool,
+    pub scrobble_youtube: bool,
+    /// Shell commands run by `sh -c` on player events, empty to disable. The metadata is
+    /// passed as `TERMUSIC_*` environment variables and as a json object on stdin.
+    pub hook_track_change: String,
+    pub hook_pause: String,
+    pub hook_resume: String,
+    pub hook_stop: String,
+    pub hook_download_complete: String,
+    pub hook_new_episode: String,
+    /// Summary and body of the desktop notification shown on track change, built with the
+    /// `notify` feature. `{title}`, `{artist}`, `{album}`, `{genre}` and `{duration}` are
+    /// replaced by the tags of the track.
+    pub notify_summary: String,
+    pub notify_body: String,
+    /// Address of the http api and web page, built with the `web` feature. Both stay off until
+    /// a token is set, open `http://<address>/#token=<token>` on the phone.
+    pub web_address: String,
+    pub web_token: String,
+    /// Discord application whose assets are used, and the two lines of the presence. The
+    /// templates take the same fields as the notification ones.
+    pub discord_app_id: String,
+    pub discord_details: String,
+    pub discord_state: String,
+    /// Show the time left instead of the time played.
+    pub discord_remaining_time: bool,
     pub seek_step: SeekStep,
     pub remember_last_played_position: LastPosition,
+    /// Start playing again where the last session of the daemon left off, otherwise the track
+    /// only waits at that position for play to be pressed.
+    pub resume_playing_on_start: bool,
     pub enable_exit_confirmation: bool,
     pub playlist_display_symbol: bool,
     pub playlist_select_random_track_quantity: u32,
     pub playlist_select_random_album_quantity: u32,
     pub theme_selected: String,
+    pub download_profiles: Vec<DownloadProfile>,
     pub album_photo_xywh: Xywh,
     pub style_color_symbol: StyleColorSymbol,
     pub keys: Keys,
@@ -150,6 +273,7 @@ impl Default for Settings {
             add_playlist_front: false,
             gapless: true,
             remember_last_played_position: LastPosition::Auto,
+            resume_playing_on_start: false,
             enable_exit_confirmation: true,
             playlist_display_symbol: true,
             keys: Keys::default(),
@@ -164,12 +288,71 @@ impl Default for Settings {
             podcast_simultanious_download: 3,
             podcast_dir: PODCAST_DIR.to_string(),
             podcast_max_retries: 3,
+            podcast_search_itunes_url: "https://itunes.apple.com".to_string(),
+            podcast_search_podcastindex_url: "https://api.podcastindex.org".to_string(),
+            podcast_search_podcastindex_key: String::new(),
+            podcast_search_podcastindex_secret: String::new(),
+            podcast_search_gpodder_url: "https://gpodder.net".to_string(),
+            podcast_sync_url: String::new(),
+            podcast_sync_service: PodcastSyncService::Gpodder,
+            podcast_sync_username: String::new(),
+            podcast_sync_password: String::new(),
+            podcast_sync_device: "termusic".to_string(),
+            podcast_sync_on_start: true,
+            invidious_instances: Vec::new(),
+            invidious_preferred_instance: String::new(),
+            invidious_cooldown_minutes: 10,
+            download_profile: "mp3".to_string(),
+            lyric_context_lines: 5,
+            lyric_scroll_timeout: 5,
+            lyric_dir: String::new(),
+            lyric_lrclib_url: "https://lrclib.net".to_string(),
+            musicbrainz_url: "https://musicbrainz.org".to_string(),
+            musicbrainz_cover_art_url: "https://coverartarchive.org".to_string(),
+            acoustid_url: "https://api.acoustid.org".to_string(),
+            acoustid_api_key: String::new(),
+            lastfm_url: "https://ws.audioscrobbler.com/2.0/".to_string(),
+            lastfm_api_key: String::new(),
+            lastfm_api_secret: String::new(),
+            lastfm_username: String::new(),
+            lastfm_session_key: String::new(),
+            listenbrainz_url: "https://api.listenbrainz.org".to_string(),
+            listenbrainz_token: String::new(),
+            scrobble_music: true,
+            scrobble_podcast: false,
+            scrobble_youtube: false,
+            hook_track_change: String::new(),
+            hook_pause: String::new(),
+            hook_resume: String::new(),
+            hook_stop: String::new(),
+            hook_download_complete: String::new(),
+            hook_new_episode: String::new(),
+            notify_summary: "{title}".to_string(),
+            notify_body: "{artist}\n{album}".to_string(),
+            web_address: "127.0.0.1:7878".to_string(),
+            web_token: String::new(),
+            discord_app_id: "968407067889131520".to_string(),
+            discord_details: "{title}".to_string(),
+            discord_state: "{artist}".to_string(),
+            discord_remaining_time: true,
+            download_profiles: download::default_profiles(),
             seek_step: SeekStep::Auto,
         }
     }
 }
 
 impl Settings {
+    /// The selected download profile, falling back to the first one, or to mp3 if the list is
+    /// empty.
+    pub fn selected_download_profile(&self) -> DownloadProfile {
+        self.download_profiles
+            .iter()
+            .find(|p| p.name == self.download_profile)
+            .or_else(|| self.download_profiles.first())
+            .cloned()
+            .unwrap_or_else(|| DownloadProfile::new("mp3", AudioCodec::Mp3, ""))
+    }
+
     pub fn save(&self) -> Result<()> {
         let mut path = get_app_config_path()?;
         path.push("config.toml");
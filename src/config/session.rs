@@ -0,0 +1,68 @@
+use crate::player::{Loop, Status};
+use crate::utils::get_app_config_path;
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// What the daemon was playing, saved to `session.toml` so a restart picks up from there. The
+/// queue itself stays in `playlist.log`, with the current track as its first entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PlayerSession {
+    /// The track being played or paused, `None` when stopped.
+    pub current: Option<String>,
+    pub status: Status,
+    /// Seconds into the current track.
+    pub position: i64,
+    pub volume: Option<i32>,
+    pub speed: Option<i32>,
+    pub loop_mode: Option<Loop>,
+}
+
+impl PlayerSession {
+    /// `None` before the first session was saved.
+    pub fn load() -> Result<Option<Self>> {
+        load_session("session.toml")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_session("session.toml", self)
+    }
+}
+
+/// Reads a session file of the config folder, `None` before it was first saved.
+pub fn load_session<T: DeserializeOwned>(name: &str) -> Result<Option<T>> {
+    let mut path = get_app_config_path()?;
+    path.push(name);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(toml::from_str(&fs::read_to_string(path)?)?))
+}
+
+pub fn save_session<T: Serialize>(name: &str, session: &T) -> Result<()> {
+    let mut path = get_app_config_path()?;
+    path.push(name);
+    fs::write(path, toml::to_string(session)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_roundtrip() {
+        let session = PlayerSession {
+            current: Some("/music/a.mp3".to_string()),
+            status: Status::Paused,
+            position: 42,
+            volume: Some(55),
+            speed: None,
+            loop_mode: Some(Loop::Single),
+        };
+        let text = toml::to_string(&session).unwrap();
+        assert_eq!(toml::from_str::<PlayerSession>(&text).unwrap(), session);
+    }
+}
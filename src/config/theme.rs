@@ -135,6 +135,8 @@ pub struct StyleColorSymbol {
     pub lyric_foreground: ColorTermusic,
     pub lyric_background: ColorTermusic,
     pub lyric_border: ColorTermusic,
+    /// Words already sung in lyrics with word timing.
+    pub lyric_highlight: ColorTermusic,
     pub alacritty_theme: Alacritty,
 }
 
@@ -157,6 +159,7 @@ impl Default for StyleColorSymbol {
             lyric_foreground: ColorTermusic::Foreground,
             lyric_background: ColorTermusic::Reset,
             lyric_border: ColorTermusic::Blue,
+            lyric_highlight: ColorTermusic::LightYellow,
             alacritty_theme: Alacritty::default(),
         }
     }
@@ -206,6 +209,9 @@ impl StyleColorSymbol {
     pub fn lyric_border(&self) -> Option<Color> {
         self.lyric_border.color(&self.alacritty_theme)
     }
+    pub fn lyric_highlight(&self) -> Option<Color> {
+        self.lyric_highlight.color(&self.alacritty_theme)
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize, PartialEq, Eq)]
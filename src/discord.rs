@@ -1,141 +1,301 @@
-use crate::track::Track;
+use crate::config::Settings;
+use crate::player::Status;
+use crate::track::{MediaType, Track};
 use discord_rich_presence::{activity, DiscordIpc, DiscordIpcClient};
-const APP_ID: &str = "968407067889131520";
-use std::sync::mpsc::{self, Receiver, Sender};
-use std::thread::sleep;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-pub struct Rpc {
-    tx: Sender<RpcCommand>,
+// how often the presence is sent again, which also notices a restarted discord
+const REFRESH: Duration = Duration::from_secs(15);
+// positions further off than this from the expected one are seeks
+const SEEK_THRESHOLD: i64 = 2;
+
+/// What the worker shows, `None` clears the presence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Presence {
+    details: String,
+    state: String,
+    large_image: String,
+    large_text: String,
+    start: Option<i64>,
+    end: Option<i64>,
+}
+
+struct Playing {
+    details: String,
+    state: String,
+    image: Option<String>,
+    duration: i64,
 }
 
-enum RpcCommand {
-    Update(String, String),
-    Pause,
-    Resume(i64),
+pub struct Rpc {
+    tx: Sender<Option<Presence>>,
+    details: String,
+    state: String,
+    remaining: bool,
+    playing: Option<Playing>,
+    status: Status,
+    /// In tenths, like the config.
+    speed: i32,
+    /// The position last reported and when, to tell seeks from playing on.
+    position: Option<(i64, Instant)>,
 }
 
-impl Default for Rpc {
+impl Rpc {
+    pub fn new(config: &Settings) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let app_id = config.discord_app_id.clone();
+        std::thread::spawn(move || worker(&app_id, &rx));
+        Self {
+            tx,
+            details: config.discord_details.clone(),
+            state: config.discord_state.clone(),
+            remaining: config.discord_remaining_time,
+            playing: None,
+            status: Status::Stopped,
+            speed: config.speed,
+            position: None,
+        }
+    }
+
     #[allow(clippy::cast_possible_wrap)]
-    fn default() -> Self {
-        let mut client = DiscordIpcClient::new(APP_ID).unwrap();
-        let (tx, rx): (Sender<RpcCommand>, Receiver<RpcCommand>) = mpsc::channel();
-        let mut artist = String::new();
-        let mut title = String::new();
-
-        std::thread::spawn(move || loop {
-            match rx.try_recv() {
-                Ok(RpcCommand::Update(artist_cmd, title_cmd)) => {
-                    let assets = activity::Assets::new()
-                        .large_image("termusic")
-                        .large_text("terminal music player written in Rust");
-                    // .small_image(smol_image)
-                    // .small_text(state);
-                    let time = SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as i64;
-                    let timestamp = activity::Timestamps::new().start(time);
-                    // .end(self.time + self.duration);
-
-                    loop {
-                        if client.connect().is_ok() {
-                            break;
-                        }
-                        sleep(Duration::from_secs(2));
-                    }
-
-                    artist = artist_cmd;
-                    title = title_cmd;
-
-                    client
-                        .set_activity(
-                            activity::Activity::new()
-                                .assets(assets)
-                                .timestamps(timestamp)
-                                .state(&artist)
-                                .details(&title),
-                        )
-                        .ok();
-                }
-                Ok(RpcCommand::Pause) => {
-                    loop {
-                        if client.connect().is_ok() {
-                            break;
-                        }
-                        sleep(Duration::from_secs(2));
-                    }
-
-                    let assets = activity::Assets::new()
-                        .large_image("termusic")
-                        .large_text("terminal music player written in Rust");
-
-                    client
-                        .set_activity(
-                            activity::Activity::new()
-                                .assets(assets)
-                                .state(&artist)
-                                .details(format!("{}: Paused", title.as_str()).as_str()),
-                        )
-                        .ok();
-                }
-                Ok(RpcCommand::Resume(time_pos)) => {
-                    let assets = activity::Assets::new()
-                        .large_image("termusic")
-                        .large_text("terminal music player written in Rust");
-
-                    let time = SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as i64;
-                    let timestamp = activity::Timestamps::new().start(time - time_pos);
-
-                    loop {
-                        if client.connect().is_ok() {
-                            break;
-                        }
-                        sleep(Duration::from_secs(2));
-                    }
-
-                    client
-                        .set_activity(
-                            activity::Activity::new()
-                                .assets(assets)
-                                .timestamps(timestamp)
-                                .state(&artist)
-                                .details(&title),
-                        )
-                        .ok();
-                }
-                Err(_) => {}
-            }
-            sleep(Duration::from_secs(1));
+    pub fn update(&mut self, track: &Track) {
+        // podcast episodes show their own image, everything else the logo of the app
+        let image = match track.media_type {
+            Some(MediaType::Podcast) => track
+                .album_photo()
+                .filter(|url| url.starts_with("http"))
+                .map(ToString::to_string),
+            _ => None,
+        };
+        self.playing = Some(Playing {
+            details: track.fill_template(&self.details),
+            state: track.fill_template(&self.state),
+            image,
+            duration: track.duration().as_secs() as i64,
         });
+        self.status = Status::Running;
+        self.position = Some((0, Instant::now()));
+        self.send();
+    }
 
-        Self { tx }
+    /// Follows the playback, the presence is only sent again on pause, resume, seeks, speed
+    /// changes and stop.
+    pub fn progress(&mut self, status: Status, position: i64, duration: i64, speed: i32) {
+        let Some(playing) = &mut self.playing else {
+            return;
+        };
+        let mut changed = status != self.status || speed != self.speed;
+        if duration > 0 && duration != playing.duration {
+            playing.duration = duration;
+            changed |= self.remaining;
+        }
+        if let (Status::Running, Some((last, at))) = (status, self.position) {
+            let expected = last + played(at.elapsed(), self.speed);
+            changed |= (position - expected).abs() > SEEK_THRESHOLD;
+        }
+        self.status = status;
+        self.speed = speed;
+        if changed || status != Status::Running {
+            self.position = Some((position, Instant::now()));
+        }
+        if changed {
+            self.send();
+        }
+    }
+
+    fn send(&self) {
+        let presence = self.playing.as_ref().and_then(|playing| {
+            let (position, at) = self.position.unwrap_or((0, Instant::now()));
+            let position = position + played(at.elapsed(), self.speed);
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(0));
+            let (details, start, end) = match self.status {
+                Status::Stopped => return None,
+                Status::Paused => (format!("{}: Paused", playing.details), None, None),
+                Status::Running if self.remaining && playing.duration > 0 => (
+                    playing.details.clone(),
+                    None,
+                    Some(now + played_in(playing.duration - position, self.speed)),
+                ),
+                Status::Running => (playing.details.clone(), Some(now - position), None),
+            };
+            Some(Presence {
+                details,
+                state: playing.state.clone(),
+                large_image: playing
+                    .image
+                    .clone()
+                    .unwrap_or_else(|| "termusic".to_string()),
+                large_text: if playing.image.is_some() {
+                    playing.details.clone()
+                } else {
+                    "terminal music player written in Rust".to_string()
+                },
+                start,
+                end,
+            })
+        });
+        self.tx.send(presence).ok();
     }
 }
 
-impl Rpc {
-    pub fn update(&mut self, track: &Track) {
-        let artist = track.artist().unwrap_or("Unknown Artist").to_string();
-        let title = track.title().unwrap_or("Unknown Title").to_string();
-        self.tx.send(RpcCommand::Update(artist, title)).ok();
+// Seconds of the track played in `elapsed`.
+#[allow(clippy::cast_possible_truncation)]
+fn played(elapsed: Duration, speed: i32) -> i64 {
+    (elapsed.as_secs_f64() * f64::from(speed) / 10.0).round() as i64
+}
+
+// Seconds it takes to play `seconds` of the track.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn played_in(seconds: i64, speed: i32) -> i64 {
+    (seconds as f64 * 10.0 / f64::from(speed.max(1))).round() as i64
+}
+
+fn worker(app_id: &str, rx: &Receiver<Option<Presence>>) {
+    let mut client = None;
+    let mut presence = None;
+    // whether discord has seen the latest presence
+    let mut shown = true;
+
+    loop {
+        match rx.recv_timeout(REFRESH) {
+            Ok(latest) => {
+                presence = latest;
+                shown = false;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        while let Ok(latest) = rx.try_recv() {
+            presence = latest;
+            shown = false;
+        }
+        if shown && presence.is_none() {
+            continue;
+        }
+
+        if client.is_none() {
+            client = connect(app_id);
+        }
+        let Some(ipc) = &mut client else {
+            continue;
+        };
+        let result = match &presence {
+            Some(presence) => ipc.set_activity(activity(presence)),
+            None => ipc.clear_activity(),
+        };
+        if result.is_ok() {
+            shown = true;
+        } else {
+            // discord went away, connect again on the next round
+            ipc.close().ok();
+            client = None;
+        }
     }
-    pub fn pause(&mut self) {
-        self.tx.send(RpcCommand::Pause).ok();
+
+    if let Some(mut ipc) = client {
+        ipc.close().ok();
     }
+}
 
-    #[allow(clippy::cast_possible_wrap)]
-    pub fn resume(&mut self, time_pos: i64) {
-        self.tx.send(RpcCommand::Resume(time_pos)).ok();
+fn connect(app_id: &str) -> Option<DiscordIpcClient> {
+    let mut client = DiscordIpcClient::new(app_id).ok()?;
+    client.connect().ok()?;
+    Some(client)
+}
+
+fn activity(presence: &Presence) -> activity::Activity<'_> {
+    let assets = activity::Assets::new()
+        .large_image(&presence.large_image)
+        .large_text(&presence.large_text);
+    let mut activity = activity::Activity::new()
+        .assets(assets)
+        .details(&presence.details)
+        .state(&presence.state);
+    let mut timestamps = activity::Timestamps::new();
+    if let Some(start) = presence.start {
+        timestamps = timestamps.start(start);
+    }
+    if let Some(end) = presence.end {
+        timestamps = timestamps.end(end);
+    }
+    if presence.start.is_some() || presence.end.is_some() {
+        activity = activity.timestamps(timestamps);
     }
+    activity
 }
 
-// impl Drop for Rpc {
-//     fn drop(&mut self) {
-//         if self.connected {
-//             self.client.close().ok();
-//         }
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing(speed: i32) -> (Rpc, Receiver<Option<Presence>>) {
+        let (tx, rx) = mpsc::channel();
+        let rpc = Rpc {
+            tx,
+            details: String::new(),
+            state: String::new(),
+            remaining: true,
+            playing: Some(Playing {
+                details: "Song".to_string(),
+                state: String::new(),
+                image: None,
+                duration: 300,
+            }),
+            status: Status::Running,
+            speed,
+            position: None,
+        };
+        (rpc, rx)
+    }
+
+    fn ago(secs: u64) -> Instant {
+        Instant::now()
+            .checked_sub(Duration::from_secs(secs))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_seek_detection_follows_speed() {
+        let (mut rpc, rx) = playing(20);
+        rpc.position = Some((10, ago(10)));
+        // twice as fast is no seek
+        rpc.progress(Status::Running, 30, 300, 20);
+        assert!(rx.try_recv().is_err());
+        // playing on at normal speed is one, as far behind as a seek back
+        rpc.position = Some((10, ago(10)));
+        rpc.progress(Status::Running, 20, 300, 20);
+        assert!(rx.try_recv().is_ok());
+
+        let (mut rpc, rx) = playing(10);
+        rpc.position = Some((10, ago(10)));
+        rpc.progress(Status::Running, 21, 300, 10);
+        assert!(rx.try_recv().is_err());
+        rpc.progress(Status::Running, 120, 300, 10);
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_remaining_time_follows_speed() {
+        let (mut rpc, rx) = playing(10);
+        rpc.position = Some((100, Instant::now()));
+        rpc.progress(Status::Running, 100, 300, 20);
+        let presence = rx.try_recv().unwrap().unwrap();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let end = u64::try_from(presence.end.unwrap()).unwrap();
+        assert!((now + 99..=now + 101).contains(&end));
+    }
+
+    #[test]
+    fn test_played() {
+        assert_eq!(played(Duration::from_secs(10), 10), 10);
+        assert_eq!(played(Duration::from_secs(10), 15), 15);
+        assert_eq!(played_in(100, 20), 50);
+        assert_eq!(played_in(100, 5), 200);
+    }
+}
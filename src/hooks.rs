@@ -0,0 +1,239 @@
+//! User commands run on player and podcast events.
+use crate::config::Settings;
+use crate::track::Track;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// At most this many commands run at once, the others wait for their turn.
+const MAX_RUNNING: usize = 4;
+/// Commands still running after this long are killed, so they can't hold up the others.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+type Job = (String, Vec<(String, String)>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Hook {
+    TrackChange,
+    Pause,
+    Resume,
+    Stop,
+    DownloadComplete,
+    NewEpisode,
+}
+
+impl Hook {
+    const ALL: [Self; 6] = [
+        Self::TrackChange,
+        Self::Pause,
+        Self::Resume,
+        Self::Stop,
+        Self::DownloadComplete,
+        Self::NewEpisode,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::TrackChange => "track_change",
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::Stop => "stop",
+            Self::DownloadComplete => "download_complete",
+            Self::NewEpisode => "new_episode",
+        }
+    }
+
+    fn command(self, config: &Settings) -> &str {
+        match self {
+            Self::TrackChange => &config.hook_track_change,
+            Self::Pause => &config.hook_pause,
+            Self::Resume => &config.hook_resume,
+            Self::Stop => &config.hook_stop,
+            Self::DownloadComplete => &config.hook_download_complete,
+            Self::NewEpisode => &config.hook_new_episode,
+        }
+    }
+}
+
+/// Runs the configured commands in the daemon, on a few worker threads so that a burst of
+/// events, like a feed with many new episodes, doesn't start a shell for each at once.
+pub struct Hooks {
+    /// The configured commands, events without one are left out.
+    commands: Vec<(Hook, String)>,
+    tx: Sender<Job>,
+    errors: Receiver<String>,
+}
+
+impl Hooks {
+    pub fn new(config: &Settings) -> Self {
+        let commands = Hook::ALL
+            .into_iter()
+            .map(|hook| (hook, hook.command(config).trim().to_string()))
+            .filter(|(_, command)| !command.is_empty())
+            .collect();
+        Self::with_commands(commands)
+    }
+
+    fn with_commands(commands: Vec<(Hook, String)>) -> Self {
+        let (tx, rx) = mpsc::channel::<Job>();
+        let (error_tx, errors) = mpsc::channel();
+        let rx = Arc::new(Mutex::new(rx));
+        // no worker is needed without commands
+        let workers = if commands.is_empty() { 0 } else { MAX_RUNNING };
+        for _ in 0..workers {
+            let rx = Arc::clone(&rx);
+            let error_tx = error_tx.clone();
+            // the workers wait for each command, so that no zombie is left behind
+            std::thread::spawn(move || loop {
+                let Ok((command, info)) = rx.lock().expect("hook queue poisoned").recv() else {
+                    break;
+                };
+                if let Err(e) = execute(&command, &info, TIMEOUT) {
+                    error_tx
+                        .send(format!("error running {} hook: {e}", info[0].1))
+                        .ok();
+                }
+            });
+        }
+        Self {
+            commands,
+            tx,
+            errors,
+        }
+    }
+
+    /// Queues the command configured for `hook`, if any. Every `(key, value)` of `info` becomes
+    /// `TERMUSIC_<KEY>` in the environment and a field of the json on stdin, next to the name
+    /// of the event.
+    pub fn run<K: AsRef<str>>(&self, hook: Hook, info: &[(K, String)]) {
+        let Some((_, command)) = self.commands.iter().find(|(h, _)| *h == hook) else {
+            return;
+        };
+        let info = std::iter::once(("event".to_string(), hook.name().to_string()))
+            .chain(
+                info.iter()
+                    .map(|(k, v)| (k.as_ref().to_string(), v.clone())),
+            )
+            .collect();
+        self.tx.send((command.clone(), info)).ok();
+    }
+
+    /// The next failure of a command that ran.
+    pub fn try_recv_error(&self) -> Option<String> {
+        self.errors.try_recv().ok()
+    }
+}
+
+fn execute(command: &str, info: &[(String, String)], timeout: Duration) -> std::io::Result<()> {
+    let json: serde_json::Map<String, serde_json::Value> = info
+        .iter()
+        .map(|(k, v)| (k.clone(), serde_json::Value::from(v.as_str())))
+        .collect();
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(
+            info.iter()
+                .map(|(k, v)| (format!("TERMUSIC_{}", k.to_uppercase()), v)),
+        )
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the command may well ignore its input and exit early
+        stdin
+            .write_all(serde_json::Value::Object(json).to_string().as_bytes())
+            .ok();
+    }
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() >= timeout {
+            child.kill().ok();
+            child.wait()?;
+            return Err(std::io::Error::other(format!(
+                "still running after {}s, killed",
+                timeout.as_secs()
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    if !status.success() {
+        return Err(std::io::Error::other(status.to_string()));
+    }
+    Ok(())
+}
+
+/// The metadata of a track passed to hooks.
+pub fn track_info(track: &Track) -> Vec<(&'static str, String)> {
+    let mut info = vec![("duration", track.duration().as_secs().to_string())];
+    let tags = [
+        ("file", track.file()),
+        ("title", track.title()),
+        ("artist", track.artist()),
+        ("album", track.album()),
+    ];
+    info.extend(
+        tags.into_iter()
+            .map(|(k, v)| (k, v.unwrap_or_default().to_string())),
+    );
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_env_and_stdin() {
+        let dir = std::env::temp_dir().join(format!("termusic-hook-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let out = dir.join("out");
+        let command = format!(
+            "printf '%s\\n' \"$TERMUSIC_EVENT $TERMUSIC_TITLE\" > {0}; cat >> {0}",
+            out.display()
+        );
+        let info = [
+            ("event".to_string(), "pause".to_string()),
+            ("title".to_string(), "a \"b\"".to_string()),
+        ];
+        execute(&command, &info, TIMEOUT).unwrap();
+        let text = std::fs::read_to_string(&out).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(
+            text,
+            "pause a \"b\"\n{\"event\":\"pause\",\"title\":\"a \\\"b\\\"\"}"
+        );
+    }
+
+    #[test]
+    fn test_hooks_report_failures() {
+        let hooks = Hooks::with_commands(vec![(Hook::Pause, "exit 3".to_string())]);
+        hooks.run(Hook::Pause, &[("title", String::new())]);
+        hooks.run(Hook::Resume, &[("title", String::new())]);
+        let start = std::time::Instant::now();
+        let error = loop {
+            if let Some(error) = hooks.try_recv_error() {
+                break error;
+            }
+            assert!(start.elapsed().as_secs() < 5, "the hook did not fail");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert_eq!(error, "error running pause hook: exit status: 3");
+    }
+
+    #[test]
+    fn test_hook_timeout() {
+        let start = Instant::now();
+        let info = [("event".to_string(), "pause".to_string())];
+        let error = execute("sleep 10", &info, Duration::from_secs(1)).unwrap_err();
+        assert_eq!(error.to_string(), "still running after 1s, killed");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
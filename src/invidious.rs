@@ -21,12 +21,16 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+use crate::config::Settings;
 use anyhow::{anyhow, bail, Result};
+use lazy_static::lazy_static;
 use rand::seq::SliceRandom;
 use serde_json::Value;
+use std::collections::HashMap;
 // left for debug
 // use std::io::Write;
-use std::time::Duration;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use ureq::{Agent, AgentBuilder};
 
 const INVIDIOUS_INSTANCE_LIST: [&str; 7] = [
@@ -49,6 +53,145 @@ const INVIDIOUS_INSTANCE_LIST: [&str; 7] = [
 
 const INVIDIOUS_DOMAINS: &str = "https://api.invidious.io/instances.json?sort_by=type,users";
 
+lazy_static! {
+    static ref HEALTH: Mutex<HashMap<String, InstanceHealth>> = Mutex::new(HashMap::new());
+}
+
+/// Which instances to use, from the `invidious_*` settings.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InstanceSettings {
+    /// Base urls of the instances. When empty, the public list from api.invidious.io is used.
+    pub instances: Vec<String>,
+    /// Tried before all others, unless it is cooling down.
+    pub preferred: String,
+    /// How long an instance is skipped after a failed request.
+    pub cooldown: Duration,
+}
+
+impl InstanceSettings {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            instances: config
+                .invidious_instances
+                .iter()
+                .map(|i| i.trim().trim_end_matches('/').to_string())
+                .filter(|i| !i.is_empty())
+                .collect(),
+            preferred: config
+                .invidious_preferred_instance
+                .trim()
+                .trim_end_matches('/')
+                .to_string(),
+            cooldown: Duration::from_secs(config.invidious_cooldown_minutes * 60),
+        }
+    }
+}
+
+/// Latency and failures of an instance during this session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InstanceHealth {
+    /// Time of the last successful request, `None` if the last one failed.
+    pub latency: Option<Duration>,
+    /// Failed requests in a row.
+    pub failures: u32,
+    last_failure: Option<Instant>,
+}
+
+impl InstanceHealth {
+    fn cooling_down(&self, cooldown: Duration) -> bool {
+        self.last_failure
+            .is_some_and(|time| self.failures > 0 && time.elapsed() < cooldown)
+    }
+}
+
+pub fn instance_health(domain: &str) -> Option<InstanceHealth> {
+    HEALTH.lock().ok()?.get(domain).copied()
+}
+
+fn record_success(domain: &str, latency: Duration) {
+    if let Ok(mut health) = HEALTH.lock() {
+        health.insert(
+            domain.to_string(),
+            InstanceHealth {
+                latency: Some(latency),
+                failures: 0,
+                last_failure: None,
+            },
+        );
+    }
+}
+
+fn record_failure(domain: &str) {
+    if let Ok(mut health) = HEALTH.lock() {
+        let entry = health.entry(domain.to_string()).or_default();
+        entry.latency = None;
+        entry.failures += 1;
+        entry.last_failure = Some(Instant::now());
+    }
+}
+
+// Unreachable instances, server errors and garbage instead of json count against an instance,
+// but not a missing video or playlist.
+fn is_instance_failure(e: &anyhow::Error) -> bool {
+    match e.downcast_ref::<ureq::Error>() {
+        Some(ureq::Error::Status(code, _)) => *code >= 500 || *code == 429,
+        Some(ureq::Error::Transport(_)) => true,
+        None => e.is::<serde_json::Error>() || e.is::<std::io::Error>(),
+    }
+}
+
+/// Result of testing an instance from the config editor, the latency or the error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstanceCheck {
+    pub domain: String,
+    pub result: Result<Duration, String>,
+}
+
+/// Tests all instances at once by asking for their stats.
+pub fn check_instances(settings: &InstanceSettings) -> Vec<InstanceCheck> {
+    let client = AgentBuilder::new().timeout(Duration::from_secs(10)).build();
+    let domains = if settings.instances.is_empty() {
+        Instance::public_domains(&client)
+    } else {
+        settings.instances.clone()
+    };
+    let handles: Vec<_> = domains
+        .into_iter()
+        .map(|domain| {
+            let client = client.clone();
+            std::thread::spawn(move || {
+                let result = check_instance(&client, &domain).map_err(|e| e.to_string());
+                InstanceCheck { domain, result }
+            })
+        })
+        .collect();
+    handles
+        .into_iter()
+        .filter_map(|handle| handle.join().ok())
+        .collect()
+}
+
+fn check_instance(client: &Agent, domain: &str) -> Result<Duration> {
+    let start = Instant::now();
+    let result = client
+        .get(&format!("{domain}/api/v1/stats"))
+        .call()
+        .map_err(anyhow::Error::from)
+        .and_then(|response| Ok(response.into_string()?))
+        .and_then(|text| {
+            let value = serde_json::from_str::<Value>(&text)?;
+            if value.get("software").is_none() {
+                bail!("not an invidious instance");
+            }
+            Ok(start.elapsed())
+        });
+    match &result {
+        Ok(latency) => record_success(domain, *latency),
+        Err(_) => record_failure(domain),
+    }
+    result
+}
+
 #[derive(Clone)]
 pub struct Instance {
     pub domain: Option<String>,
@@ -71,6 +214,121 @@ pub struct YoutubeVideo {
     pub video_id: String,
 }
 
+/// A youtube playlist or channel, to list its videos instead of searching.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum YoutubeList {
+    Playlist(String),
+    Channel(String),
+    /// Channel urls with a handle or custom name, which the instance has to resolve to an id.
+    ChannelUrl(String),
+}
+
+impl YoutubeList {
+    /// Recognizes playlist and channel urls, as well as bare playlist ids, channel ids and
+    /// `@handles`.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if input.starts_with("http") {
+            // a video opened from a playlist or a mix is downloaded on its own
+            if input.contains("?v=") || input.contains("&v=") {
+                return None;
+            }
+            if let Some(start) = input.find("list=") {
+                let id: String = input[start + 5..]
+                    .chars()
+                    .take_while(|c| *c != '&' && *c != '#')
+                    .collect();
+                return (!id.is_empty()).then_some(Self::Playlist(id));
+            }
+            if let Some(start) = input.find("/channel/") {
+                let id: String = input[start + 9..]
+                    .chars()
+                    .take_while(|c| *c != '/' && *c != '?')
+                    .collect();
+                return (!id.is_empty()).then_some(Self::Channel(id));
+            }
+            if input.contains("/@") || input.contains("/c/") || input.contains("/user/") {
+                return Some(Self::ChannelUrl(input.to_string()));
+            }
+            return None;
+        }
+
+        let is_id = !input.is_empty()
+            && input
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if let Some(handle) = input.strip_prefix('@') {
+            return Some(Self::ChannelUrl(format!(
+                "https://www.youtube.com/@{handle}"
+            )));
+        }
+        if is_id && input.len() == 24 && input.starts_with("UC") {
+            return Some(Self::Channel(input.to_string()));
+        }
+        if is_id
+            && input.len() > 12
+            && ["PL", "OL", "UU", "FL"]
+                .iter()
+                .any(|p| input.starts_with(p))
+        {
+            return Some(Self::Playlist(input.to_string()));
+        }
+        None
+    }
+}
+
+/// The audio stream of a video, for playing it without downloading.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct YoutubeStream {
+    pub video_id: String,
+    pub title: String,
+    pub author: String,
+    pub length_seconds: u64,
+    pub thumbnail: Option<String>,
+    pub url: String,
+}
+
+pub fn video_url(video_id: &str) -> String {
+    format!("https://www.youtube.com/watch?v={video_id}")
+}
+
+/// The id of a `youtube.com/watch?v=` or `youtu.be/` url.
+pub fn video_id_from_url(url: &str) -> Option<&str> {
+    let start = url
+        .find("youtube.com/watch?v=")
+        .map(|i| i + 20)
+        .or_else(|| url.find("youtu.be/").map(|i| i + 9))?;
+    let id = &url[start..];
+    let id = id.split(['&', '#', '?']).next().unwrap_or(id);
+    (!id.is_empty()).then_some(id)
+}
+
+/// Stream urls carry their expiry time, after which they have to be resolved again.
+pub fn stream_expired(url: &str) -> bool {
+    let Some(start) = url.find("expire=") else {
+        return false;
+    };
+    let expire = url[start + 7..]
+        .split('&')
+        .next()
+        .and_then(|e| e.parse::<u64>().ok())
+        .unwrap_or(0);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    // leave a minute to start playing
+    expire < now + 60
+}
+
+/// One page of the videos of a playlist or channel.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct YoutubeListPage {
+    pub title: String,
+    pub videos: Vec<YoutubeVideo>,
+    /// Newer instances page channel videos with this token instead of a page number.
+    pub continuation: Option<String>,
+}
+
 impl Default for Instance {
     fn default() -> Self {
         let client = Agent::new();
@@ -87,59 +345,194 @@ impl Default for Instance {
 
 #[allow(unused)]
 impl Instance {
-    pub fn new(query: &str) -> Result<(Self, Vec<YoutubeVideo>)> {
-        let client = AgentBuilder::new().timeout(Duration::from_secs(10)).build();
-
-        let mut domain = String::new();
-        let mut domains = vec![];
+    // the public list from api.invidious.io, or the built in backups if that is down
+    fn public_domains(client: &Agent) -> Vec<String> {
+        Self::get_invidious_instance_list(client).unwrap_or_else(|_| {
+            INVIDIOUS_INSTANCE_LIST
+                .iter()
+                .map(|item| (*item).to_string())
+                .collect()
+        })
+    }
 
-        // prefor fetch invidious instance from website, but will provide 7 backups
-        if let Ok(domain_list) = Self::get_invidious_instance_list(&client) {
-            domains = domain_list;
+    // The preferred instance first, then the ones that answered fastest before and the untested
+    // ones in random order. Instances that failed recently are skipped until they cool down.
+    fn candidate_domains(client: &Agent, settings: &InstanceSettings) -> Vec<String> {
+        let mut domains = if settings.instances.is_empty() {
+            Self::public_domains(client)
         } else {
-            for item in &INVIDIOUS_INSTANCE_LIST {
-                domains.push((*item).to_string());
-            }
-        }
-
+            settings.instances.clone()
+        };
         domains.shuffle(&mut rand::thread_rng());
+        domains.sort_by_key(|domain| {
+            instance_health(domain)
+                .and_then(|health| health.latency)
+                .unwrap_or(Duration::MAX)
+        });
+        if !settings.preferred.is_empty() {
+            domains.retain(|domain| *domain != settings.preferred);
+            domains.insert(0, settings.preferred.clone());
+        }
 
-        let mut video_result: Vec<YoutubeVideo> = Vec::new();
-        for v in domains {
-            let url = format!("{v}/api/v1/search");
+        let (alive, cooling_down): (Vec<String>, Vec<String>) =
+            domains.into_iter().partition(|domain| {
+                !instance_health(domain).is_some_and(|h| h.cooling_down(settings.cooldown))
+            });
+        // retrying a dead instance is still better than giving up
+        if alive.is_empty() {
+            cooling_down
+        } else {
+            alive
+        }
+    }
 
-            if let Ok(result) = client
-                .get(&url)
+    pub fn new(query: &str, settings: &InstanceSettings) -> Result<(Self, Vec<YoutubeVideo>)> {
+        let (mut instance, video_result) = Self::connect(settings, |instance| {
+            let domain = instance
+                .domain
+                .as_ref()
+                .ok_or_else(|| anyhow!("No server available"))?;
+            let text = instance
+                .client
+                .get(&format!("{domain}/api/v1/search"))
                 .query("q", query)
                 .query("page", "1")
                 .query("type", "video")
                 .query("sort_by", "relevance")
-                .call()
-            {
-                if result.status() == 200 {
-                    if let Ok(text) = result.into_string() {
-                        if let Some(vr) = Self::parse_youtube_options(&text) {
-                            video_result = vr;
-                            domain = v;
-                            break;
-                        }
+                .call()?
+                .into_string()?;
+            // every working instance answers a search with a list, so this is an instance failure
+            Self::parse_youtube_options(&text).ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid search result").into()
+            })
+        })?;
+        instance.query = Some(query.to_string());
+        Ok((instance, video_result))
+    }
+
+    /// Tries the instances until `request` succeeds on one of them, keeping track of their
+    /// latency and failures.
+    fn connect<T>(
+        settings: &InstanceSettings,
+        request: impl Fn(&Self) -> Result<T>,
+    ) -> Result<(Self, T)> {
+        let client = AgentBuilder::new().timeout(Duration::from_secs(10)).build();
+
+        let mut last_error =
+            anyhow!("All invidious servers are down? Please check your network connection first.");
+        for domain in Self::candidate_domains(&client, settings) {
+            let instance = Self {
+                domain: Some(domain.clone()),
+                client: client.clone(),
+                query: None,
+            };
+            let start = Instant::now();
+            match request(&instance) {
+                Ok(result) => {
+                    record_success(&domain, start.elapsed());
+                    return Ok((instance, result));
+                }
+                Err(e) => {
+                    if is_instance_failure(&e) {
+                        record_failure(&domain);
                     }
+                    last_error = e;
                 }
             }
         }
-        if domain.len() < 2 {
-            bail!("All 7 invidious servers are down? Please check your network connection first.");
-        }
+        Err(last_error)
+    }
 
-        let domain = Some(domain);
-        Ok((
-            Self {
-                domain,
-                client,
-                query: Some(query.to_string()),
-            },
-            video_result,
-        ))
+    /// Like `new`, but fetches the first page of a playlist or channel. Channel urls are
+    /// resolved to their id, which is returned along with the page.
+    pub fn new_for_list(
+        list: &YoutubeList,
+        settings: &InstanceSettings,
+    ) -> Result<(Self, YoutubeList, YoutubeListPage)> {
+        let (instance, (page, list)) = Self::connect(settings, |instance| {
+            let list = instance.resolve_list(list)?;
+            Ok((instance.get_list_page(&list, 1, None)?, list))
+        })?;
+        Ok((instance, list, page))
+    }
+
+    /// Like `new`, but resolves the audio stream of a video.
+    pub fn new_for_video(
+        video_id: &str,
+        settings: &InstanceSettings,
+    ) -> Result<(Self, YoutubeStream)> {
+        Self::connect(settings, |instance| instance.get_audio_stream(video_id))
+    }
+
+    /// Resolves the audio stream of a video. The stream is proxied through the instance, as
+    /// youtube only serves it to the address that requested it.
+    pub fn get_audio_stream(&self, video_id: &str) -> Result<YoutubeStream> {
+        let domain = self
+            .domain
+            .as_ref()
+            .ok_or_else(|| anyhow!("No server available"))?;
+        let text = self
+            .client
+            .get(&format!("{domain}/api/v1/videos/{video_id}"))
+            .query("local", "true")
+            .call()?
+            .into_string()?;
+        Self::parse_audio_stream(&text, domain, video_id)
+            .ok_or_else(|| anyhow!("No audio stream found for {video_id}"))
+    }
+
+    fn resolve_list(&self, list: &YoutubeList) -> Result<YoutubeList> {
+        let YoutubeList::ChannelUrl(url) = list else {
+            return Ok(list.clone());
+        };
+        let domain = self
+            .domain
+            .as_ref()
+            .ok_or_else(|| anyhow!("No server available"))?;
+        let text = self
+            .client
+            .get(&format!("{domain}/api/v1/resolveurl"))
+            .query("url", url)
+            .call()?
+            .into_string()?;
+        let value = serde_json::from_str::<Value>(&text)?;
+        let ucid = value
+            .get("ucid")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("{url} is not a channel"))?;
+        Ok(YoutubeList::Channel(ucid.to_string()))
+    }
+
+    /// Fetches one page of a playlist or a resolved channel. `continuation` is the token of the
+    /// previous channel page, if the instance returned one.
+    pub fn get_list_page(
+        &self,
+        list: &YoutubeList,
+        page: u32,
+        continuation: Option<&str>,
+    ) -> Result<YoutubeListPage> {
+        let domain = self
+            .domain
+            .as_ref()
+            .ok_or_else(|| anyhow!("No server available"))?;
+        let request = match list {
+            YoutubeList::Playlist(id) => self
+                .client
+                .get(&format!("{domain}/api/v1/playlists/{id}"))
+                .query("page", &page.to_string()),
+            YoutubeList::Channel(ucid) => {
+                let request = self
+                    .client
+                    .get(&format!("{domain}/api/v1/channels/{ucid}/videos"));
+                match continuation {
+                    Some(token) => request.query("continuation", token),
+                    None => request.query("page", &page.to_string()),
+                }
+            }
+            YoutubeList::ChannelUrl(url) => bail!("channel {url} is not resolved"),
+        };
+        let text = request.call()?.into_string()?;
+        Self::parse_list_page(&text).ok_or_else(|| anyhow!("Invalid playlist or channel data"))
     }
 
     // GetSearchQuery fetches query result from an Invidious instance.
@@ -230,6 +623,97 @@ impl Instance {
         None
     }
 
+    // playlists come as an object with a title and a videos array, channel videos as a bare
+    // array on older instances and as an object with a continuation token on newer ones.
+    fn parse_list_page(data: &str) -> Option<YoutubeListPage> {
+        let value = serde_json::from_str::<Value>(data).ok()?;
+        let array = match value.get("videos") {
+            Some(videos) => videos.as_array()?,
+            None => value.as_array()?,
+        };
+        let videos: Vec<YoutubeVideo> = array
+            .iter()
+            .filter_map(Self::parse_youtube_item)
+            .map(|(title, video_id, length_seconds)| YoutubeVideo {
+                title,
+                length_seconds,
+                video_id,
+            })
+            .collect();
+        let title = value
+            .get("title")
+            .and_then(Value::as_str)
+            .or_else(|| array.first()?.get("author")?.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let continuation = value
+            .get("continuation")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        Some(YoutubeListPage {
+            title,
+            videos,
+            continuation,
+        })
+    }
+
+    // prefers mp4 audio, which every backend can decode, and the highest bitrate after that
+    fn parse_audio_stream(data: &str, domain: &str, video_id: &str) -> Option<YoutubeStream> {
+        let value = serde_json::from_str::<Value>(data).ok()?;
+        let bitrate = |format: &Value| match format.get("bitrate") {
+            Some(Value::String(s)) => s.parse::<u64>().unwrap_or(0),
+            Some(b) => b.as_u64().unwrap_or(0),
+            None => 0,
+        };
+        let format = value
+            .get("adaptiveFormats")?
+            .as_array()?
+            .iter()
+            .filter(|f| {
+                f.get("type")
+                    .and_then(Value::as_str)
+                    .is_some_and(|t| t.starts_with("audio/"))
+            })
+            .max_by_key(|f| {
+                let mp4 = f
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .is_some_and(|t| t.starts_with("audio/mp4"));
+                (mp4, bitrate(f))
+            })?;
+        let absolute = |url: &str| {
+            if url.starts_with('/') {
+                format!("{domain}{url}")
+            } else {
+                url.to_string()
+            }
+        };
+        let thumbnails = value.get("videoThumbnails").and_then(Value::as_array);
+        let thumbnail = thumbnails
+            .and_then(|t| {
+                t.iter()
+                    .find(|t| t.get("quality").and_then(Value::as_str) == Some("high"))
+                    .or_else(|| t.first())
+            })
+            .and_then(|t| t.get("url")?.as_str())
+            .map(absolute);
+        Some(YoutubeStream {
+            video_id: video_id.to_string(),
+            title: value.get("title")?.as_str()?.to_string(),
+            author: value
+                .get("author")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            length_seconds: value
+                .get("lengthSeconds")
+                .and_then(Value::as_u64)
+                .unwrap_or(0),
+            thumbnail,
+            url: absolute(format.get("url")?.as_str()?),
+        })
+    }
+
     fn parse_youtube_item(value: &Value) -> Option<(String, String, u64)> {
         let title = value.get("title")?.as_str()?.to_owned();
         let video_id = value.get("videoId")?.as_str()?.to_owned();
@@ -286,3 +770,142 @@ impl Instance {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{dead_server, mock_server};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_instance_failover_and_cooldown() {
+        let dead = dead_server();
+        let alive = mock_server(r#"[{"title":"Song","videoId":"a1","lengthSeconds":61}]"#);
+        let settings = InstanceSettings {
+            instances: vec![alive.clone(), dead.clone()],
+            preferred: dead.clone(),
+            cooldown: Duration::MAX,
+        };
+
+        let (instance, videos) = Instance::new("song", &settings).unwrap();
+        assert_eq!(instance.domain.as_deref(), Some(alive.as_str()));
+        assert_eq!(videos[0].video_id, "a1");
+        assert_eq!(instance_health(&dead).unwrap().failures, 1);
+        assert!(instance_health(&alive).unwrap().latency.is_some());
+
+        // the preferred instance is skipped while it cools down
+        let client = Agent::new();
+        assert_eq!(Instance::candidate_domains(&client, &settings), vec![alive]);
+        let no_cooldown = InstanceSettings {
+            cooldown: Duration::ZERO,
+            ..settings
+        };
+        assert_eq!(Instance::candidate_domains(&client, &no_cooldown)[0], dead);
+    }
+
+    #[test]
+    fn test_check_instances() {
+        let alive = mock_server(r#"{"version":"2.0","software":{"name":"invidious"}}"#);
+        let other = mock_server("<html>not invidious</html>");
+        let checks = check_instances(&InstanceSettings {
+            instances: vec![alive.clone(), other.clone()],
+            ..InstanceSettings::default()
+        });
+        assert_eq!(checks.len(), 2);
+        assert!(checks.iter().any(|c| c.domain == alive && c.result.is_ok()));
+        assert!(checks
+            .iter()
+            .any(|c| c.domain == other && c.result.is_err()));
+    }
+
+    #[test]
+    fn test_parse_youtube_list() {
+        assert_eq!(
+            YoutubeList::parse(
+                "https://www.youtube.com/playlist?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG"
+            ),
+            Some(YoutubeList::Playlist(
+                "PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG".to_string()
+            ))
+        );
+        assert_eq!(
+            YoutubeList::parse("https://www.youtube.com/watch?v=abc&list=RDabc&index=2"),
+            None
+        );
+        assert_eq!(
+            YoutubeList::parse("https://www.youtube.com/channel/UC_x5XG1OV2P6uZZ5FSM9Ttw/videos"),
+            Some(YoutubeList::Channel("UC_x5XG1OV2P6uZZ5FSM9Ttw".to_string()))
+        );
+        assert_eq!(
+            YoutubeList::parse("@GoogleDevelopers"),
+            Some(YoutubeList::ChannelUrl(
+                "https://www.youtube.com/@GoogleDevelopers".to_string()
+            ))
+        );
+        assert_eq!(
+            YoutubeList::parse("UC_x5XG1OV2P6uZZ5FSM9Ttw"),
+            Some(YoutubeList::Channel("UC_x5XG1OV2P6uZZ5FSM9Ttw".to_string()))
+        );
+        assert_eq!(
+            YoutubeList::parse("https://www.youtube.com/watch?v=abc"),
+            None
+        );
+        assert_eq!(YoutubeList::parse("lofi hip hop"), None);
+    }
+
+    #[test]
+    fn test_parse_list_page() {
+        let playlist = Instance::parse_list_page(
+            r#"{"title":"Mix","videos":[{"title":"One","videoId":"a1","lengthSeconds":61},{"title":"Broken"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(playlist.title, "Mix");
+        assert_eq!(playlist.videos.len(), 1);
+        assert_eq!(playlist.continuation, None);
+
+        let channel = Instance::parse_list_page(
+            r#"{"videos":[{"title":"Two","videoId":"b2","lengthSeconds":5,"author":"Someone"}],"continuation":"tok"}"#,
+        )
+        .unwrap();
+        assert_eq!(channel.title, "Someone");
+        assert_eq!(channel.continuation.as_deref(), Some("tok"));
+
+        let old_channel = Instance::parse_list_page(
+            r#"[{"title":"Three","videoId":"c3","lengthSeconds":9,"author":"Other"}]"#,
+        )
+        .unwrap();
+        assert_eq!(old_channel.videos[0].video_id, "c3");
+    }
+
+    #[test]
+    fn test_parse_audio_stream() {
+        let stream = Instance::parse_audio_stream(
+            r#"{"title":"Song","author":"Band","lengthSeconds":200,
+            "videoThumbnails":[{"quality":"maxres","url":"/vi/x/maxres.jpg"},{"quality":"high","url":"/vi/x/hq.jpg"}],
+            "adaptiveFormats":[
+                {"type":"audio/webm; codecs=\"opus\"","bitrate":"160000","url":"/videoplayback?itag=251"},
+                {"type":"audio/mp4; codecs=\"mp4a.40.2\"","bitrate":"130000","url":"/videoplayback?itag=140"},
+                {"type":"video/mp4","bitrate":"900000","url":"/videoplayback?itag=137"}
+            ]}"#,
+            "https://inv.example",
+            "x",
+        )
+        .unwrap();
+        assert_eq!(stream.url, "https://inv.example/videoplayback?itag=140");
+        assert_eq!(
+            stream.thumbnail.as_deref(),
+            Some("https://inv.example/vi/x/hq.jpg")
+        );
+        assert_eq!(stream.length_seconds, 200);
+
+        assert_eq!(
+            video_id_from_url("https://www.youtube.com/watch?v=abc_12&t=3"),
+            Some("abc_12")
+        );
+        assert_eq!(video_id_from_url("https://youtu.be/abc_12"), Some("abc_12"));
+        assert!(stream_expired(
+            "https://inv.example/videoplayback?expire=1000&id=1"
+        ));
+        assert!(!stream_expired("https://inv.example/videoplayback?id=1"));
+    }
+}
@@ -31,13 +31,21 @@ mod cli;
 mod config;
 #[cfg(feature = "discord")]
 mod discord;
+mod hooks;
 mod invidious;
 mod player;
 mod playlist;
 #[allow(unused)]
 mod podcast;
+mod scrobble;
+/// The playback daemon owns the audio backend and the playlist. Clients, like the tui, talk to
+/// it over a unix socket with one json message per line: clients send `Request`s and the
+/// daemon answers with `Event`s, most of them broadcast to every attached client.
+mod server;
 mod songtag;
 mod sqlite;
+#[cfg(test)]
+mod test_utils;
 mod track;
 #[cfg(feature = "cover")]
 mod ueberzug;
@@ -70,13 +78,23 @@ fn main() -> Result<()> {
         config.max_depth_cli = MAX_DEPTH;
     }
     match args.action {
-        Some(cli::Action::Import { file }) => {
+        Some(cli::Action::Import {
+            file,
+            dry_run,
+            json,
+        }) => {
             eprintln!("need to import from file {file}");
             if let Some(path_str) = get_path(&file) {
                 if let Ok(db_path) = utils::get_app_config_path() {
-                    if let Err(e) = podcast::import_from_opml(db_path.as_path(), &config, &path_str)
+                    match podcast::import_from_opml(db_path.as_path(), &config, &path_str, dry_run)
                     {
-                        println!("Error when import file {file}: {e}");
+                        Ok(summary) => {
+                            summary.print(json)?;
+                            if !summary.failed.is_empty() {
+                                process::exit(1);
+                            }
+                        }
+                        Err(e) => println!("Error when import file {file}: {e}"),
                     }
                 }
             }
@@ -94,6 +112,28 @@ fn main() -> Result<()> {
 
             process::exit(0);
         }
+        Some(cli::Action::Remote(remote)) => {
+            if let Err(e) = server::control(&remote) {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+            process::exit(0);
+        }
+        Some(cli::Action::LastfmLogin) => {
+            if let Err(e) = scrobble::lastfm_login(&mut config) {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+            process::exit(0);
+        }
+        Some(cli::Action::Daemon { stop }) => {
+            if stop {
+                server::stop()?;
+            } else {
+                server::run(&config)?;
+            }
+            process::exit(0);
+        }
         None => {}
     }
 
@@ -30,6 +30,7 @@ pub mod playlist;
 #[cfg(not(any(feature = "mpv", feature = "gst")))]
 mod rusty_backend;
 use crate::config::Settings;
+use crate::invidious::{Instance, YoutubeStream};
 use anyhow::Result;
 #[cfg(feature = "mpv")]
 use mpv_backend::MpvBackend;
@@ -52,6 +53,9 @@ pub enum PlayerMsg {
     AboutToFinish,
     CurrentTrackUpdated,
     Progress(i64, i64),
+    YoutubeStreamReady(YoutubeStream),
+    /// The video id and the error.
+    YoutubeStreamFail(String, String),
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -101,6 +105,11 @@ impl GeneralPlayer {
             }
         }
 
+        if let Some(video_id) = self.playlist.stale_youtube_stream() {
+            self.resolve_youtube_stream(video_id);
+            return;
+        }
+
         if let Some(file) = self.playlist.get_current_track() {
             if self.playlist.has_next_track() {
                 self.playlist.set_next_track(None);
@@ -128,6 +137,20 @@ impl GeneralPlayer {
         }
     }
 
+    /// Looks up a youtube stream off the playback path. It plays once `YoutubeStreamReady`
+    /// arrives.
+    fn resolve_youtube_stream(&self, video_id: String) {
+        let settings = self.playlist.invidious().clone();
+        let tx = self.message_tx.clone();
+        std::thread::spawn(move || {
+            let msg = match Instance::new_for_video(&video_id, &settings) {
+                Ok((_, stream)) => PlayerMsg::YoutubeStreamReady(stream),
+                Err(e) => PlayerMsg::YoutubeStreamFail(video_id, e.to_string()),
+            };
+            tx.send(msg).ok();
+        });
+    }
+
     pub fn enqueue_next(&mut self) {
         if self.playlist.next_track().is_some() {
             return;
@@ -138,6 +161,11 @@ impl GeneralPlayer {
             None => return,
         };
 
+        // youtube streams are resolved when they are played
+        if matches!(track.media_type, Some(crate::track::MediaType::Youtube)) {
+            return;
+        }
+
         self.playlist.set_next_track(Some(&track));
         if let Some(file) = track.file() {
             #[cfg(not(any(feature = "mpv", feature = "gst")))]
@@ -170,6 +198,20 @@ impl GeneralPlayer {
             self.message_tx.send(PlayerMsg::Eos).ok();
         }
     }
+
+    /// Shortens long pauses while playing. Only supported by the rusty backend.
+    #[allow(unused_variables)]
+    pub fn set_skip_silence(&mut self, skip_silence: bool) {
+        #[cfg(not(any(feature = "mpv", feature = "gst")))]
+        self.player.set_skip_silence(skip_silence);
+    }
+
+    pub fn skip_silence(&self) -> bool {
+        #[cfg(not(any(feature = "mpv", feature = "gst")))]
+        return self.player.skip_silence();
+        #[cfg(any(feature = "mpv", feature = "gst"))]
+        false
+    }
 }
 
 impl PlayerTrait for GeneralPlayer {
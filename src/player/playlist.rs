@@ -1,3 +1,4 @@
+use crate::invidious::{stream_expired, video_id_from_url, InstanceSettings, YoutubeStream};
 use crate::podcast::{db::Database as DBPod, Episode};
 use crate::track::MediaType;
 use crate::{
@@ -17,7 +18,7 @@ use std::path::{Path, PathBuf};
 #[cfg(not(any(feature = "mpv", feature = "gst")))]
 use std::time::Duration;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Status {
     Running,
     Stopped,
@@ -40,7 +41,7 @@ impl std::fmt::Display for Status {
     }
 }
 
-#[derive(Clone, Copy, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Loop {
     Single,
     Playlist,
@@ -83,6 +84,7 @@ pub struct Playlist {
     status: Status,
     loop_mode: Loop,
     add_playlist_front: bool,
+    invidious: InstanceSettings,
 }
 
 // #[allow(unused)]
@@ -106,9 +108,15 @@ impl Playlist {
             status: Status::Stopped,
             loop_mode,
             add_playlist_front,
+            invidious: InstanceSettings::new(config),
         })
     }
 
+    /// Picks up instance changes from the config editor.
+    pub fn set_invidious(&mut self, settings: InstanceSettings) {
+        self.invidious = settings;
+    }
+
     pub fn reload(&mut self) -> Result<()> {
         self.save()?;
         let mut tracks = Self::load()?;
@@ -137,17 +145,26 @@ impl Playlist {
             .map(|line| line.unwrap_or_else(|_| "Error".to_string()))
             .collect();
 
+        Self::load_from(&lines)
+    }
+
+    /// Builds tracks from playlist entries: local files, youtube urls and podcast episodes.
+    pub fn load_from(lines: &[String]) -> Result<VecDeque<Track>> {
         let mut playlist_items = VecDeque::new();
         let db_path = get_app_config_path()?;
         let db_podcast = DBPod::connect(&db_path)?;
         let podcasts = db_podcast
             .get_podcasts()
             .expect("failed to get podcasts from db.");
-        for line in &lines {
+        for line in lines {
             if let Ok(s) = Track::read_from_path(line, false) {
                 playlist_items.push_back(s);
                 continue;
             };
+            if let Some(track) = Track::from_youtube_url(line) {
+                playlist_items.push_back(track);
+                continue;
+            }
             if line.starts_with("http") {
                 'outer: for pod in &podcasts {
                     for ep in &pod.episodes {
@@ -234,12 +251,42 @@ impl Playlist {
                         result = Some(file.to_string());
                     }
                 }
+                Some(MediaType::Youtube) => {
+                    result = track.stream_url().map(str::to_string);
+                }
                 None => {}
             }
         }
         result
     }
 
+    /// The video of the current track when its stream has to be looked up before it can play.
+    // youtube tracks restored from the playlist file have no stream yet, and streams expire
+    // after a few hours
+    pub fn stale_youtube_stream(&self) -> Option<String> {
+        let track = self.current_track.as_ref()?;
+        if !matches!(track.media_type, Some(MediaType::Youtube))
+            || track.stream_url().is_some_and(|url| !stream_expired(url))
+        {
+            return None;
+        }
+        track.file().and_then(video_id_from_url).map(str::to_string)
+    }
+
+    /// Gives the current track the stream looked up for it. False when the queue moved on in
+    /// the meantime.
+    pub fn set_youtube_stream(&mut self, stream: &YoutubeStream) -> bool {
+        if self.stale_youtube_stream().as_deref() != Some(stream.video_id.as_str()) {
+            return false;
+        }
+        self.current_track = Some(Track::from_youtube(stream));
+        true
+    }
+
+    pub fn invidious(&self) -> &InstanceSettings {
+        &self.invidious
+    }
+
     pub fn fetch_next_track(&self) -> Option<&Track> {
         self.tracks.get(0)
     }
@@ -276,6 +323,10 @@ impl Playlist {
         }
     }
 
+    pub fn loop_mode(&self) -> Loop {
+        self.loop_mode
+    }
+
     pub fn cycle_loop_mode(&mut self) -> Loop {
         match self.loop_mode {
             Loop::Queue => {
@@ -392,8 +443,31 @@ impl Playlist {
     }
 
     pub fn remove_deleted_items(&mut self) {
-        self.tracks
-            .retain(|x| x.file().map_or(false, |p| Path::new(p).exists()));
+        self.tracks.retain(|x| {
+            matches!(x.media_type, Some(MediaType::Youtube))
+                || x.file().map_or(false, |p| Path::new(p).exists())
+        });
+    }
+
+    /// Point the entries of a file that was moved to `track`.
+    pub fn update_moved(&mut self, old_file: &str, track: &Track) {
+        let moved = |x: &Track| x.file() == Some(old_file);
+        for t in self.tracks.iter_mut().filter(|t| moved(t)) {
+            *t = track.clone();
+        }
+        if let Some(t) = self.current_track.as_mut().filter(|t| moved(t)) {
+            *t = track.clone();
+        }
+        if let Some(t) = self.next_track.as_mut().filter(|t| moved(t)) {
+            *t = track.clone();
+        }
+    }
+
+    /// Replaces the whole queue, as received from the daemon or one of its clients.
+    pub fn set_queue(&mut self, current: Option<Track>, tracks: VecDeque<Track>, loop_mode: Loop) {
+        self.current_track = current;
+        self.tracks = tracks;
+        self.loop_mode = loop_mode;
     }
 
     pub fn push_front(&mut self, track: &Track) {
@@ -79,6 +79,7 @@ enum PlayerCmd {
     Seek(i64),
     SeekRelative(i64),
     Skip,
+    SkipSilence(bool),
     Speed(i32),
     Stop,
     Volume(i64),
@@ -88,6 +89,7 @@ pub struct Player {
     pub total_duration: Option<Duration>,
     volume: u16,
     speed: i32,
+    skip_silence: bool,
     pub gapless: bool,
     pub message_tx: Sender<PlayerMsg>,
     command_tx: Sender<PlayerCmd>,
@@ -110,6 +112,7 @@ impl Player {
             total_duration: None,
             volume,
             speed,
+            skip_silence: false,
             gapless,
             message_tx: tx.clone(),
             command_tx,
@@ -229,8 +232,15 @@ impl Player {
                             let speed = speed as f32 / 10.0;
                             sink.set_speed(speed);
                         }
+                        PlayerCmd::SkipSilence(skip_silence) => {
+                            sink.set_skip_silence(skip_silence);
+                        }
                         PlayerCmd::Stop => {
+                            let speed = sink.speed();
+                            let skip_silence = sink.skip_silence();
                             sink = Sink::try_new(&handle, gapless, message_tx.clone()).unwrap();
+                            sink.set_speed(speed);
+                            sink.set_skip_silence(skip_silence);
                         }
                         PlayerCmd::Volume(volume) => {
                             sink.set_volume(volume as f32 / 100.0);
@@ -325,6 +335,17 @@ impl Player {
     pub fn message_on_end(&self) {
         self.command_tx.send(PlayerCmd::MessageOnEnd).ok();
     }
+
+    pub fn set_skip_silence(&mut self, skip_silence: bool) {
+        self.skip_silence = skip_silence;
+        self.command_tx
+            .send(PlayerCmd::SkipSilence(skip_silence))
+            .ok();
+    }
+
+    pub const fn skip_silence(&self) -> bool {
+        self.skip_silence
+    }
 }
 
 impl PlayerTrait for Player {
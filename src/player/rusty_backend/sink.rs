@@ -36,6 +36,7 @@ struct Controls {
     seek: Mutex<Option<Duration>>,
     stopped: AtomicBool,
     speed: Mutex<f32>,
+    skip_silence: AtomicBool,
     do_skip: AtomicBool,
 }
 
@@ -70,6 +71,7 @@ impl Sink {
                 stopped: AtomicBool::new(false),
                 seek: Mutex::new(None),
                 speed: Mutex::new(1.0),
+                skip_silence: AtomicBool::new(false),
                 do_skip: AtomicBool::new(false),
             }),
             sound_count: Arc::new(AtomicUsize::new(0)),
@@ -93,6 +95,7 @@ impl Sink {
         let tx = self.message_tx.clone();
         let elapsed = self.elapsed.clone();
         let source = source
+            .skip_silence(false)
             .speed(1.0)
             .pausable(false)
             .amplify(1.0)
@@ -140,6 +143,12 @@ impl Sink {
                         .inner_mut()
                         .inner_mut()
                         .set_factor(*controls.speed.lock().unwrap());
+                    src.inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .inner_mut()
+                        .set_enabled(controls.skip_silence.load(Ordering::SeqCst));
                 }
             })
             .convert_samples();
@@ -259,6 +268,18 @@ impl Sink {
         *self.controls.speed.lock().unwrap() = value;
     }
 
+    /// Returns true if long pauses are being shortened.
+    #[inline]
+    pub fn skip_silence(&self) -> bool {
+        self.controls.skip_silence.load(Ordering::SeqCst)
+    }
+
+    /// Turns shortening of long pauses on or off.
+    #[inline]
+    pub fn set_skip_silence(&self, value: bool) {
+        self.controls.skip_silence.store(value, Ordering::SeqCst);
+    }
+
     /// Removes all currently loaded `Source`s from the `Sink`, and pauses it.
     ///
     /// See `pause()` for information about pausing a `Sink`.
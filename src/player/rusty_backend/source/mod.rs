@@ -12,6 +12,7 @@ pub use self::fadein::FadeIn;
 pub use self::pausable::Pausable;
 pub use self::periodic::PeriodicAccess;
 pub use self::samples_converter::SamplesConverter;
+pub use self::skip_silence::SkipSilence;
 pub use self::skippable::Skippable;
 pub use self::speed::Speed;
 pub use self::stoppable::Stoppable;
@@ -27,6 +28,7 @@ mod fadein;
 mod pausable;
 mod periodic;
 mod samples_converter;
+mod skip_silence;
 mod skippable;
 mod speed;
 mod stoppable;
@@ -222,4 +224,14 @@ where
     {
         speed::speed(self, ratio)
     }
+
+    /// Shortens long stretches of silence. Can be turned on and off while playing.
+    #[inline]
+    fn skip_silence(self, enabled: bool) -> SkipSilence<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        skip_silence::skip_silence(self, enabled)
+    }
 }
@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use super::super::CpalSample;
+use super::{Sample, Source};
+
+/// Amplitude below which a sample is considered silent (about -40dB).
+const SILENCE_THRESHOLD: f32 = 0.01;
+
+/// Pauses shorter than this are left untouched, longer ones are cut down to it.
+const MAX_SILENCE: Duration = Duration::from_millis(500);
+
+/// Internal function that builds a `SkipSilence` object.
+pub fn skip_silence<I>(input: I, enabled: bool) -> SkipSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    SkipSilence {
+        input,
+        enabled,
+        frame: VecDeque::new(),
+        silent_frames: 0,
+    }
+}
+
+/// Filter that shortens long pauses, mostly useful for spoken-word audio.
+///
+/// Samples are inspected one frame (one sample per channel) at a time, so that dropping
+/// silence never shifts the channels against each other.
+#[derive(Clone, Debug)]
+pub struct SkipSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    enabled: bool,
+    frame: VecDeque<I::Item>,
+    silent_frames: u64,
+}
+
+#[allow(unused, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+impl<I> SkipSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Turns the silence skipping on or off.
+    #[inline]
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.silent_frames = 0;
+        }
+    }
+
+    /// Returns true if silence is currently being skipped.
+    #[inline]
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    fn max_silent_frames(&self) -> u64 {
+        (f64::from(self.input.sample_rate()) * MAX_SILENCE.as_secs_f64()) as u64
+    }
+
+    /// Reads the next frame into the buffer. Returns false when the input is exhausted.
+    fn fill_frame(&mut self) -> bool {
+        let channels = self.input.channels().max(1);
+        for _ in 0..channels {
+            match self.input.next() {
+                Some(sample) => self.frame.push_back(sample),
+                None => break,
+            }
+        }
+        !self.frame.is_empty()
+    }
+}
+
+impl<I> Iterator for SkipSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(sample) = self.frame.pop_front() {
+            return Some(sample);
+        }
+
+        if !self.enabled {
+            return self.input.next();
+        }
+
+        loop {
+            if !self.fill_frame() {
+                return None;
+            }
+
+            let silent = self
+                .frame
+                .iter()
+                .all(|s| CpalSample::to_f32(s).abs() < SILENCE_THRESHOLD);
+            if !silent {
+                self.silent_frames = 0;
+                break;
+            }
+
+            self.silent_frames += 1;
+            if self.silent_frames <= self.max_silent_frames() {
+                break;
+            }
+            self.frame.clear();
+        }
+
+        self.frame.pop_front()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, upper) = self.input.size_hint();
+        (self.frame.len(), upper.map(|u| u + self.frame.len()))
+    }
+}
+
+impl<I> Source for SkipSilence<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input
+            .current_frame_len()
+            .map(|len| len + self.frame.len())
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn elapsed(&mut self) -> Duration {
+        self.input.elapsed()
+    }
+
+    fn seek(&mut self, time: Duration) -> Option<Duration> {
+        self.frame.clear();
+        self.silent_frames = 0;
+        self.input.seek(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::buffer::SamplesBuffer;
+    use super::*;
+
+    #[test]
+    fn test_skip_silence_shortens_long_pause() {
+        // 1 second of silence between two loud samples, at 1000Hz mono
+        let mut data = vec![0.5_f32];
+        data.resize(1001, 0.0);
+        data.push(0.5);
+        let source = skip_silence(SamplesBuffer::new(1, 1000, data), true);
+        let out: Vec<f32> = source.collect();
+        assert_eq!(out.len(), 2 + 500);
+        assert!((out[0] - 0.5).abs() < f32::EPSILON);
+        assert!((out[out.len() - 1] - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_skip_silence_keeps_channels_aligned() {
+        // stereo, left channel loud after the pause, right channel silent
+        let mut data = vec![0.5_f32, 0.0];
+        data.resize(2002, 0.0);
+        data.extend([0.5, 0.0]);
+        let source = skip_silence(SamplesBuffer::new(2, 1000, data), true);
+        let out: Vec<f32> = source.collect();
+        assert_eq!(out.len() % 2, 0);
+        assert!((out[out.len() - 2] - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_skip_silence_disabled_passes_through() {
+        let data = vec![0.0_f32; 3000];
+        let source = skip_silence(SamplesBuffer::new(1, 1000, data), false);
+        assert_eq!(source.count(), 3000);
+    }
+}
@@ -6,10 +6,11 @@ use ahash::AHashMap;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use lazy_static::lazy_static;
 use regex::Regex;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use semver::Version;
 use std::time::Duration;
 
+use super::sync::{format_timestamp, parse_timestamp, EpisodeAction};
 use super::{Episode, EpisodeNoId, NewEpisode, Podcast, PodcastNoId};
 
 lazy_static! {
@@ -39,6 +40,19 @@ impl Database {
         std::fs::create_dir_all(&db_path)
             .with_context(|| "Unable to create subdirectory for database.")?;
         db_path.push("data.db");
+        Self::open(db_path)
+    }
+
+    /// An empty database that only lives as long as the returned value. `name` has to be
+    /// unique, as every connection opened for the same name shares the database.
+    #[cfg(test)]
+    pub fn in_memory(name: &str) -> Result<Database> {
+        Self::open(PathBuf::from(format!(
+            "file:{name}?mode=memory&cache=shared"
+        )))
+    }
+
+    fn open(db_path: PathBuf) -> Result<Database> {
         let conn = Connection::open(&db_path)?;
         let db_conn = Database {
             path: db_path,
@@ -103,12 +117,20 @@ impl Database {
                 author TEXT,
                 explicit INTEGER,
                 image_url TEXT,
-                last_checked INTEGER
+                last_checked INTEGER,
+                speed INTEGER,
+                skip_silence INTEGER,
+                category TEXT
             );",
             params![],
         )
         .with_context(|| "Could not create podcasts database table")?;
 
+        // columns added after the table was first released
+        Self::add_column_if_missing(conn, "podcasts", "speed", "INTEGER")?;
+        Self::add_column_if_missing(conn, "podcasts", "skip_silence", "INTEGER")?;
+        Self::add_column_if_missing(conn, "podcasts", "category", "TEXT")?;
+
         // create episodes table
         conn.execute(
             "CREATE TABLE IF NOT EXISTS episodes (
@@ -142,6 +164,39 @@ impl Database {
         )
         .with_context(|| "Could not create files database table")?;
 
+        // local changes not yet pushed to the gpodder sync server
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS sync_subscriptions (
+                url TEXT PRIMARY KEY NOT NULL,
+                added INTEGER NOT NULL
+            );",
+            params![],
+        )
+        .with_context(|| "Could not create sync_subscriptions database table")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS sync_episode_actions (
+                episode_url TEXT PRIMARY KEY NOT NULL,
+                podcast_url TEXT NOT NULL,
+                action TEXT NOT NULL,
+                position INTEGER,
+                total INTEGER,
+                timestamp INTEGER NOT NULL
+            );",
+            params![],
+        )
+        .with_context(|| "Could not create sync_episode_actions database table")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS sync_state (
+                id INTEGER PRIMARY KEY NOT NULL,
+                subscriptions_since INTEGER NOT NULL,
+                episodes_since INTEGER NOT NULL
+            );",
+            params![],
+        )
+        .with_context(|| "Could not create sync_state database table")?;
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS version (
                 id INTEGER PRIMARY KEY NOT NULL,
@@ -153,6 +208,29 @@ impl Database {
         Ok(())
     }
 
+    /// Adds a column to an existing table, for databases created by an
+    /// older version of the app.
+    fn add_column_if_missing(
+        conn: &Connection,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> Result<()> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({table});"))?;
+        let exists = stmt
+            .query_map(params![], |row| row.get::<_, String>("name"))?
+            .flatten()
+            .any(|name| name == column);
+        if !exists {
+            conn.execute(
+                &format!("ALTER TABLE {table} ADD COLUMN {column} {definition};"),
+                params![],
+            )
+            .with_context(|| format!("Could not add column {column} to table {table}"))?;
+        }
+        Ok(())
+    }
+
     /// If version stored in database is less than the current version
     /// of the app, this updates the value stored in the database to
     /// match.
@@ -203,6 +281,7 @@ impl Database {
             let mut stmt = tx.prepare_cached("SELECT id FROM podcasts WHERE url = ?")?;
             pod_id = stmt.query_row::<i64, _, _>(params![podcast.url], |row| row.get(0))?;
         }
+        Self::queue_subscription_change(&tx, &podcast.url, true)?;
         let mut ep_ids = Vec::new();
         for ep in podcast.episodes.iter().rev() {
             let id = Self::insert_episode(&tx, pod_id, ep)?;
@@ -296,6 +375,11 @@ impl Database {
         // and `files` tables, all associated episodes for this podcast
         // will also be deleted, and all associated file entries for
         // those episodes as well.
+        conn.execute(
+            "INSERT OR REPLACE INTO sync_subscriptions (url, added)
+            SELECT url, 0 FROM podcasts WHERE id = ?;",
+            params![podcast_id],
+        )?;
         let mut stmt = conn.prepare_cached("DELETE FROM podcasts WHERE id = ?;")?;
         stmt.execute(params![podcast_id])?;
         Ok(())
@@ -459,9 +543,34 @@ impl Database {
 
         let mut stmt = conn.prepare_cached("UPDATE episodes SET played = ? WHERE id = ?;")?;
         stmt.execute(params![played, episode_id])?;
+        Self::queue_played_action(conn, episode_id, played)?;
         Ok(())
     }
 
+    /// Marks the episode at `url` as played when playback starts, and returns the speed and
+    /// silence skipping of its podcast. None when no episode has that url. Unlike
+    /// `set_played_status`, this is not sent to the sync server, which only learns about the
+    /// progress through `set_last_position`, so other devices don't consider it finished.
+    pub fn start_episode(&self, url: &str) -> Result<Option<(Option<i32>, bool)>> {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+
+        let settings = conn
+            .query_row(
+                "SELECT p.speed, p.skip_silence FROM episodes e
+                INNER JOIN podcasts p ON e.podcast_id = p.id WHERE e.url = ?1;",
+                params![url],
+                |row| Ok((row.get(0)?, row.get::<_, Option<bool>>(1)?.unwrap_or(false))),
+            )
+            .optional()?;
+        if settings.is_some() {
+            conn.execute(
+                "UPDATE episodes SET played = 1 WHERE url = ?1;",
+                params![url],
+            )?;
+        }
+        Ok(settings)
+    }
+
     /// Updates an episode to mark it as played or unplayed.
     pub fn set_all_played_status(&self, episode_id_vec: &[i64], played: bool) -> Result<()> {
         let mut conn = Connection::open(&self.path).expect("Error connecting to database.");
@@ -470,6 +579,7 @@ impl Database {
         for episode_id in episode_id_vec {
             let mut stmt = tx.prepare_cached("UPDATE episodes SET played = ? WHERE id = ?;")?;
             stmt.execute(params![played, episode_id])?;
+            Self::queue_played_action(&tx, *episode_id, played)?;
         }
         tx.commit()?;
         Ok(())
@@ -486,6 +596,36 @@ impl Database {
         Ok(())
     }
 
+    /// Sets the playback speed for all episodes of a podcast, or
+    /// resets it to the global speed if `None`.
+    pub fn set_podcast_speed(&self, podcast_id: i64, speed: Option<i32>) -> Result<()> {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+
+        let mut stmt = conn.prepare_cached("UPDATE podcasts SET speed = ? WHERE id = ?;")?;
+        stmt.execute(params![speed, podcast_id])?;
+        Ok(())
+    }
+
+    /// Sets whether long pauses are shortened when playing episodes of
+    /// a podcast.
+    pub fn set_podcast_skip_silence(&self, podcast_id: i64, skip_silence: bool) -> Result<()> {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+
+        let mut stmt = conn.prepare_cached("UPDATE podcasts SET skip_silence = ? WHERE id = ?;")?;
+        stmt.execute(params![skip_silence, podcast_id])?;
+        Ok(())
+    }
+
+    /// Sets the category of a podcast, which is a folder path like `News/Tech` coming from the
+    /// nesting of an imported OPML file.
+    pub fn set_podcast_category(&self, url: &str, category: Option<&str>) -> Result<()> {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+
+        let mut stmt = conn.prepare_cached("UPDATE podcasts SET category = ? WHERE url = ?;")?;
+        stmt.execute(params![category, url])?;
+        Ok(())
+    }
+
     /// Generates list of all podcasts in database.
     /// TODO: This should probably use a JOIN statement instead.
     pub fn get_podcasts(&self) -> Result<Vec<Podcast>> {
@@ -514,6 +654,9 @@ impl Database {
                 explicit: row.get("explicit")?,
                 last_checked: convert_date(&row.get("last_checked")).unwrap(),
                 image_url: row.get("image_url")?,
+                speed: row.get("speed")?,
+                skip_silence: row.get::<_, Option<bool>>("skip_silence")?.unwrap_or(false),
+                category: row.get("category")?,
                 episodes,
             })
         })?;
@@ -522,6 +665,8 @@ impl Database {
             podcasts.push(pc?);
         }
         // podcasts.sort_unstable();
+        // keep feeds of the same category together, uncategorized ones first
+        podcasts.sort_by(|a, b| a.category.cmp(&b.category));
 
         Ok(podcasts)
     }
@@ -572,6 +717,10 @@ impl Database {
     /// Deletes all rows in all tables
     pub fn clear_db(&self) -> Result<()> {
         let conn = self.conn.as_ref().expect("Error connecting to database.");
+        conn.execute(
+            "INSERT OR REPLACE INTO sync_subscriptions (url, added) SELECT url, 0 FROM podcasts;",
+            params![],
+        )?;
         conn.execute("DELETE FROM files;", params![])?;
         conn.execute("DELETE FROM episodes;", params![])?;
         conn.execute("DELETE FROM podcasts;", params![])?;
@@ -616,6 +765,153 @@ impl Database {
         )
         .expect("update last position failed.");
         // eprintln!("set last position as {}", last_position.as_secs());
+        conn.execute(
+            "INSERT OR REPLACE INTO sync_episode_actions
+            (episode_url, podcast_url, action, position, total, timestamp)
+            SELECT e.url, p.url, 'play', ?1, e.duration, ?2
+            FROM episodes e INNER JOIN podcasts p ON e.podcast_id = p.id
+            WHERE e.url = ?3;",
+            params![
+                last_position.as_secs(),
+                Utc::now().timestamp(),
+                track.file().unwrap_or("Unknown File Name").to_string(),
+            ],
+        )
+        .ok();
+    }
+
+    fn queue_subscription_change(conn: &Connection, url: &str, added: bool) -> Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO sync_subscriptions (url, added) VALUES (?, ?);",
+            params![url, added],
+        )?;
+        Ok(())
+    }
+
+    /// Records a `play` action at the end of the episode, or a `new` action when an episode
+    /// is marked as unplayed.
+    fn queue_played_action(conn: &Connection, episode_id: i64, played: bool) -> Result<()> {
+        let (action, position) = if played {
+            ("play", "e.duration")
+        } else {
+            ("new", "NULL")
+        };
+        conn.execute(
+            &format!(
+                "INSERT OR REPLACE INTO sync_episode_actions
+                (episode_url, podcast_url, action, position, total, timestamp)
+                SELECT e.url, p.url, '{action}', {position}, e.duration, ?1
+                FROM episodes e INNER JOIN podcasts p ON e.podcast_id = p.id
+                WHERE e.id = ?2;"
+            ),
+            params![Utc::now().timestamp(), episode_id],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the server timestamps of the last subscription and episode action sync.
+    pub fn get_sync_state(&self) -> (i64, i64) {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+        conn.query_row(
+            "SELECT subscriptions_since, episodes_since FROM sync_state WHERE id = 1;",
+            params![],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap_or((0, 0))
+    }
+
+    pub fn set_sync_state(&self, subscriptions_since: i64, episodes_since: i64) -> Result<()> {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+        conn.execute(
+            "INSERT OR REPLACE INTO sync_state (id, subscriptions_since, episodes_since)
+            VALUES (1, ?, ?);",
+            params![subscriptions_since, episodes_since],
+        )?;
+        Ok(())
+    }
+
+    /// Returns feed urls subscribed (`true`) or unsubscribed (`false`) since the last sync.
+    pub fn get_pending_subscriptions(&self) -> Result<Vec<(String, bool)>> {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+        let mut stmt = conn.prepare_cached("SELECT url, added FROM sync_subscriptions;")?;
+        let rows = stmt.query_map(params![], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.flatten().collect())
+    }
+
+    pub fn clear_pending_subscriptions(&self, added: &[String], removed: &[String]) -> Result<()> {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+        let mut stmt =
+            conn.prepare_cached("DELETE FROM sync_subscriptions WHERE url = ? AND added = ?;")?;
+        for url in added {
+            stmt.execute(params![url, true])?;
+        }
+        for url in removed {
+            stmt.execute(params![url, false])?;
+        }
+        Ok(())
+    }
+
+    pub fn get_pending_episode_actions(&self) -> Result<Vec<EpisodeAction>> {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+        let mut stmt = conn.prepare_cached(
+            "SELECT episode_url, podcast_url, action, position, total, timestamp
+            FROM sync_episode_actions ORDER BY timestamp;",
+        )?;
+        let rows = stmt.query_map(params![], |row| {
+            let action: String = row.get("action")?;
+            let started = if action == "play" { Some(0) } else { None };
+            Ok(EpisodeAction {
+                podcast: row.get("podcast_url")?,
+                episode: row.get("episode_url")?,
+                action,
+                device: None,
+                timestamp: Some(format_timestamp(row.get("timestamp")?)),
+                started,
+                position: row.get("position")?,
+                total: row.get("total")?,
+            })
+        })?;
+        Ok(rows.flatten().collect())
+    }
+
+    /// Removes the given actions from the queue, unless the episode changed again meanwhile.
+    pub fn clear_pending_episode_actions(&self, actions: &[EpisodeAction]) -> Result<()> {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+        let mut stmt = conn.prepare_cached(
+            "DELETE FROM sync_episode_actions WHERE episode_url = ? AND timestamp <= ?;",
+        )?;
+        for action in actions {
+            stmt.execute(params![
+                action.episode,
+                parse_timestamp(action.timestamp.as_deref())
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Applies an action received from the sync server, without queueing it to be sent back.
+    /// Returns false if the episode is unknown or the action is not supported.
+    pub fn apply_episode_action(&self, action: &EpisodeAction) -> Result<bool> {
+        let conn = self.conn.as_ref().expect("Error connecting to database.");
+        let updated = match action.action.to_lowercase().as_str() {
+            "play" => {
+                let position = action.position.unwrap_or(0);
+                // a position within the last few seconds means the episode was finished
+                let finished = action.total.is_some_and(|t| t > 0 && position + 5 >= t);
+                conn.execute(
+                    "UPDATE episodes SET last_position = ?1,
+                    played = CASE WHEN ?2 THEN 1 ELSE played END
+                    WHERE url = ?3;",
+                    params![position, finished, action.episode],
+                )?
+            }
+            "new" => conn.execute(
+                "UPDATE episodes SET last_position = 0, played = 0 WHERE url = ?;",
+                params![action.episode],
+            )?,
+            _ => 0,
+        };
+        Ok(updated > 0)
     }
 }
 
@@ -2,6 +2,9 @@
 
 #[allow(unused)]
 pub mod db;
+pub mod search;
+pub mod show_notes;
+pub mod sync;
 
 use crate::config::Settings;
 use crate::ui::{Msg, PCMsg};
@@ -15,6 +18,7 @@ use regex::{Match, Regex};
 use rfc822_sanitizer::parse_from_rfc2822_with_fallback;
 use rss::{Channel, Item};
 use sanitize_filename::{sanitize_with_options, Options};
+use serde::Serialize;
 use std::cmp::Ordering;
 use std::fs::File;
 use std::io::{Read, Write};
@@ -71,6 +75,11 @@ pub struct Podcast {
     pub last_checked: DateTime<Utc>,
     pub episodes: Vec<Episode>,
     pub image_url: Option<String>,
+    /// Playback speed for this podcast, `None` uses the global speed.
+    pub speed: Option<i32>,
+    pub skip_silence: bool,
+    /// Folder the podcast was filed under, nested folders are separated by `/`.
+    pub category: Option<String>,
 }
 
 impl Podcast {
@@ -610,11 +619,68 @@ impl Worker {
     }
 }
 
-/// Imports a list of podcasts from OPML format, either reading from a
-/// file or from stdin. If the `replace` flag is set, this replaces all
-/// existing data in the database.
-pub fn import_from_opml(db_path: &Path, config: &Settings, filepath: &str) -> Result<()> {
-    // read from file or from stdin
+/// Outcome of an OPML import, one entry per feed found in the file.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    pub dry_run: bool,
+    /// Feeds that were added, or would be added in a dry run.
+    pub added: Vec<ImportEntry>,
+    /// Feeds that are already subscribed to, or listed more than once in the file.
+    pub duplicates: Vec<ImportEntry>,
+    pub failed: Vec<ImportEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportEntry {
+    pub url: String,
+    pub title: Option<String>,
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ImportSummary {
+    /// Prints the summary to stdout, as json if `json` is set.
+    pub fn print(&self, json: bool) -> Result<()> {
+        if json {
+            println!("{}", serde_json::to_string_pretty(self)?);
+            return Ok(());
+        }
+        let added = if self.dry_run { "Would add" } else { "Added" };
+        for (label, entries) in [
+            (added, &self.added),
+            ("Already subscribed", &self.duplicates),
+            ("Failed", &self.failed),
+        ] {
+            for entry in entries {
+                let name = entry.title.as_deref().unwrap_or(&entry.url);
+                match (&entry.category, &entry.error) {
+                    (_, Some(e)) => println!("{label}: {name} ({e})"),
+                    (Some(c), None) => println!("{label}: {name} [{c}]"),
+                    (None, None) => println!("{label}: {name}"),
+                }
+            }
+        }
+        println!(
+            "{} {}, {} duplicates, {} failed.",
+            self.added.len(),
+            if self.dry_run { "to add" } else { "added" },
+            self.duplicates.len(),
+            self.failed.len()
+        );
+        Ok(())
+    }
+}
+
+/// Imports a list of podcasts from an OPML file. Folders in the file are kept as podcast
+/// categories. With `dry_run`, the feeds are only fetched to see if they work, and nothing is
+/// written to the database.
+pub fn import_from_opml(
+    db_path: &Path,
+    config: &Settings,
+    filepath: &str,
+    dry_run: bool,
+) -> Result<ImportSummary> {
     let mut f =
         File::open(filepath).with_context(|| format!("Could not open OPML file: {filepath}"))?;
     let mut contents = String::new();
@@ -622,51 +688,51 @@ pub fn import_from_opml(db_path: &Path, config: &Settings, filepath: &str) -> Re
         .with_context(|| format!("Failed to read from OPML file: {filepath}"))?;
     let xml = contents;
 
-    let mut podcast_list = import_opml_feeds(&xml).with_context(|| {
+    let podcast_list = import_opml_feeds(&xml).with_context(|| {
         "Could not properly parse OPML file -- file may be formatted improperly or corrupted."
     })?;
 
-    if podcast_list.is_empty() {
-        println!("No podcasts to import.");
-        return Ok(());
-    }
-
+    let mut summary = ImportSummary {
+        dry_run,
+        ..ImportSummary::default()
+    };
     let db_inst = db::Database::connect(db_path)?;
-
-    // delete database if we are replacing the data
-    // if args.is_present("replace") {
-    //     db_inst
-    //         .clear_db()
-    //         .with_context(|| "Error clearing database")?;
-    // } else {
     let old_podcasts = db_inst.get_podcasts()?;
 
-    // if URL is already in database, remove it from import
-    podcast_list.retain(|pod| {
-        for op in &old_podcasts {
-            if pod.url == op.url {
-                return false;
-            }
+    // if URL is already in database or earlier in the file, leave it out of the import
+    let mut to_check: Vec<ImportEntry> = Vec::new();
+    for (feed, category) in podcast_list {
+        let entry = ImportEntry {
+            url: feed.url,
+            title: feed.title,
+            category,
+            error: None,
+        };
+        if old_podcasts.iter().any(|op| op.url == entry.url)
+            || to_check.iter().any(|e| e.url == entry.url)
+        {
+            summary.duplicates.push(entry);
+        } else {
+            to_check.push(entry);
         }
-        true
-    });
-    // }
+    }
 
-    // check again, now that we may have removed feeds after looking at
-    // the database
-    if podcast_list.is_empty() {
-        println!("No podcasts to import.");
-        return Ok(());
+    if to_check.is_empty() {
+        return Ok(summary);
     }
 
-    println!("Importing {} podcasts...", podcast_list.len());
+    if dry_run {
+        eprintln!("Checking {} podcasts...", to_check.len());
+    } else {
+        eprintln!("Importing {} podcasts...", to_check.len());
+    }
 
     let threadpool = Threadpool::new(config.podcast_simultanious_download);
     let (tx_to_main, rx_to_main) = mpsc::channel();
 
-    for pod in &podcast_list {
+    for entry in &to_check {
         check_feed(
-            pod.clone(),
+            PodcastFeed::new(None, &entry.url, entry.title.clone()),
             config.podcast_max_retries,
             &threadpool,
             tx_to_main.clone(),
@@ -674,51 +740,58 @@ pub fn import_from_opml(db_path: &Path, config: &Settings, filepath: &str) -> Re
     }
 
     let mut msg_counter: usize = 0;
-    let mut failure = false;
     while let Some(message) = rx_to_main.iter().next() {
         match message {
             Msg::Podcast(PCMsg::NewData(pod)) => {
                 msg_counter += 1;
-                let title = pod.title.clone();
-                let db_result = db_inst.insert_podcast(&pod);
+                let Some(mut entry) = to_check.iter().find(|e| e.url == pod.url).cloned() else {
+                    continue;
+                };
+                entry.title = Some(pod.title.clone());
+                if dry_run {
+                    summary.added.push(entry);
+                    continue;
+                }
+                let db_result = db_inst.insert_podcast(&pod).and_then(|_| {
+                    db_inst.set_podcast_category(&pod.url, entry.category.as_deref())
+                });
                 match db_result {
-                    Ok(_) => {
-                        println!("Added {title}");
+                    Ok(()) => {
+                        eprintln!("Added {}", pod.title);
+                        summary.added.push(entry);
                     }
-                    Err(_err) => {
-                        failure = true;
-                        eprintln!("Error adding {title}");
+                    Err(err) => {
+                        eprintln!("Error adding {}", pod.title);
+                        entry.error = Some(err.to_string());
+                        summary.failed.push(entry);
                     }
                 }
             }
 
-            Msg::Podcast(PCMsg::Error(_, feed)) => {
+            Msg::Podcast(PCMsg::Error(url, _feed)) => {
                 msg_counter += 1;
-                failure = true;
-                if let Some(t) = feed.title {
-                    eprintln!("Error retrieving RSS feed: {t}");
-                } else {
-                    eprintln!("Error retrieving RSS feed");
+                if let Some(mut entry) = to_check.iter().find(|e| e.url == url).cloned() {
+                    eprintln!(
+                        "Error retrieving RSS feed: {}",
+                        entry.title.as_deref().unwrap_or(&url)
+                    );
+                    entry.error = Some("Error retrieving RSS feed".to_string());
+                    summary.failed.push(entry);
                 }
             }
 
-            Msg::Podcast((PCMsg::SyncData((_id, _pod)))) => {
+            Msg::Podcast(PCMsg::SyncData((_id, _pod))) => {
                 msg_counter += 1;
             }
             _ => {}
         }
 
-        if msg_counter >= podcast_list.len() {
+        if msg_counter >= to_check.len() {
             break;
         }
     }
 
-    if failure {
-        return Err(anyhow!("Process finished with errors."));
-    }
-    println!("Import successful.");
-
-    Ok(())
+    Ok(summary)
 }
 
 /// Exports all podcasts to OPML format, either printing to stdout or
@@ -740,38 +813,49 @@ pub fn export_to_opml(db_path: &Path, file: &str) -> Result<()> {
     Ok(())
 }
 
-/// Import a list of podcast feeds from an OPML file. Supports
-/// v1.0, v1.1, and v2.0 OPML files.
-fn import_opml_feeds(xml: &str) -> Result<Vec<PodcastFeed>> {
-    match OPML::from_str(xml) {
-        Err(err) => Err(anyhow!(err)),
-        Ok(opml) => {
-            let mut feeds = Vec::new();
-            for pod in opml.body.outlines {
-                if pod.xml_url.is_some() {
-                    // match against title attribute first -- if this is
-                    // not set or empty, then match against the text
-                    // attribute; this must be set, but can be empty
-                    let temp_title = pod.title.filter(|t| !t.is_empty());
-                    let title = match temp_title {
-                        Some(t) => Some(t),
-                        None => {
-                            if pod.text.is_empty() {
-                                None
-                            } else {
-                                Some(pod.text)
-                            }
-                        }
-                    };
-                    feeds.push(PodcastFeed::new(None, &pod.xml_url.unwrap(), title));
-                }
-            }
-            Ok(feeds)
+/// Import a list of podcast feeds from an OPML file, together with the
+/// folder each one is nested in. Supports v1.0, v1.1, and v2.0 OPML files.
+fn import_opml_feeds(xml: &str) -> Result<Vec<(PodcastFeed, Option<String>)>> {
+    let opml = OPML::from_str(xml).map_err(|err| anyhow!(err))?;
+    let mut feeds = Vec::new();
+    collect_opml_outlines(opml.body.outlines, None, &mut feeds);
+    Ok(feeds)
+}
+
+fn collect_opml_outlines(
+    outlines: Vec<Outline>,
+    category: Option<&str>,
+    feeds: &mut Vec<(PodcastFeed, Option<String>)>,
+) {
+    for pod in outlines {
+        // match against title attribute first -- if this is
+        // not set or empty, then match against the text
+        // attribute; this must be set, but can be empty
+        let title = pod
+            .title
+            .filter(|t| !t.is_empty())
+            .or_else(|| Some(pod.text).filter(|t| !t.is_empty()));
+        if let Some(url) = pod.xml_url {
+            feeds.push((
+                PodcastFeed::new(None, &url, title),
+                category.map(ToString::to_string),
+            ));
+        } else if let Some(folder) = title {
+            // an outline without a feed is a folder, `/` separates nested ones
+            let folder = folder.replace('/', "-");
+            let path = match category {
+                Some(parent) => format!("{parent}/{folder}"),
+                None => folder,
+            };
+            collect_opml_outlines(pod.outlines, Some(&path), feeds);
+        } else {
+            collect_opml_outlines(pod.outlines, category, feeds);
         }
     }
 }
 
-/// Converts the current set of podcast feeds to the OPML format
+/// Converts the current set of podcast feeds to the OPML format, with
+/// categories as nested folders
 fn export_opml_feeds(podcasts: &[Podcast]) -> OPML {
     let date = Utc::now();
     let mut opml = OPML {
@@ -786,8 +870,23 @@ fn export_opml_feeds(podcasts: &[Podcast]) -> OPML {
     let mut outlines = Vec::new();
 
     for pod in podcasts {
+        let mut folder: &mut Vec<Outline> = &mut outlines;
+        for name in pod.category.iter().flat_map(|c| c.split('/')) {
+            let index = folder
+                .iter()
+                .position(|o| o.xml_url.is_none() && o.text == name)
+                .unwrap_or_else(|| {
+                    folder.push(Outline {
+                        text: name.to_string(),
+                        title: Some(name.to_string()),
+                        ..Outline::default()
+                    });
+                    folder.len() - 1
+                });
+            folder = &mut folder[index].outlines;
+        }
         // opml.add_feed(&pod.title, &pod.url);
-        outlines.push(Outline {
+        folder.push(Outline {
             text: pod.title.clone(),
             r#type: Some("rss".to_string()),
             xml_url: Some(pod.url.clone()),
@@ -903,3 +1002,79 @@ fn download_file(mut ep_data: EpData, destination_path: PathBuf, mut max_retries
         Err(_) => PCMsg::DLFileWriteError(ep_data),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_opml_folders_as_categories() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0"><head><title>feeds</title></head><body>
+<outline text="Loose" type="rss" xmlUrl="https://loose/rss"/>
+<outline text="News"><outline text="Tech"><outline text="Deep" type="rss" xmlUrl="https://deep/rss"/></outline>
+<outline title="Daily" text="" type="rss" xmlUrl="https://daily/rss"/></outline>
+</body></opml>"#;
+        let feeds = import_opml_feeds(xml).unwrap();
+        let result: Vec<(&str, Option<&str>)> = feeds
+            .iter()
+            .map(|(feed, category)| (feed.url.as_str(), category.as_deref()))
+            .collect();
+        assert_eq!(
+            result,
+            vec![
+                ("https://loose/rss", None),
+                ("https://deep/rss", Some("News/Tech")),
+                ("https://daily/rss", Some("News")),
+            ]
+        );
+        assert_eq!(feeds[2].0.title.as_deref(), Some("Daily"));
+    }
+
+    #[test]
+    fn test_export_nested_categories() {
+        let podcast = |title: &str, category: Option<&str>| Podcast {
+            id: 0,
+            title: title.to_string(),
+            sort_title: title.to_lowercase(),
+            url: format!("https://{title}/rss"),
+            description: None,
+            author: None,
+            explicit: None,
+            last_checked: Utc::now(),
+            episodes: Vec::new(),
+            image_url: None,
+            speed: None,
+            skip_silence: false,
+            category: category.map(ToString::to_string),
+        };
+        let opml = export_opml_feeds(&[
+            podcast("a", Some("News/Tech")),
+            podcast("b", Some("News")),
+            podcast("c", None),
+        ]);
+        let outlines = &opml.body.outlines;
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(outlines[0].text, "News");
+        assert_eq!(outlines[0].outlines[0].text, "Tech");
+        assert_eq!(outlines[0].outlines[1].text, "b");
+        assert_eq!(outlines[1].xml_url.as_deref(), Some("https://c/rss"));
+
+        // and back again
+        let xml = opml.to_string().unwrap();
+        let categories: Vec<Option<String>> = import_opml_feeds(&xml)
+            .unwrap()
+            .into_iter()
+            .map(|(_, category)| category)
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                Some("News/Tech".to_string()),
+                Some("News".to_string()),
+                None
+            ]
+        );
+    }
+}
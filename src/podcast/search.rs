@@ -0,0 +1,260 @@
+use super::PodcastFeed;
+use crate::config::Settings;
+use anyhow::{anyhow, bail, Result};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::Value;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A podcast directory that can be searched by a term.
+pub trait SearchProvider: Send {
+    fn name(&self) -> &'static str;
+    fn search(&self, agent: &ureq::Agent, term: &str) -> Result<Vec<PodcastFeed>>;
+}
+
+pub struct Itunes {
+    base_url: String,
+}
+
+impl Itunes {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl SearchProvider for Itunes {
+    fn name(&self) -> &'static str {
+        "iTunes"
+    }
+
+    fn search(&self, agent: &ureq::Agent, term: &str) -> Result<Vec<PodcastFeed>> {
+        let url = format!(
+            "{}/search?media=podcast&entity=podcast&term={}",
+            self.base_url,
+            encode(term)
+        );
+        let text = agent.get(&url).call()?.into_string()?;
+        parse_itunes_results(&text).ok_or_else(|| anyhow!("Error parsing result"))
+    }
+}
+
+/// <https://podcastindex-org.github.io/docs-api/>, which requires a free api key and secret.
+pub struct PodcastIndex {
+    base_url: String,
+    key: String,
+    secret: String,
+}
+
+impl PodcastIndex {
+    pub fn new(base_url: &str, key: &str, secret: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            key: key.to_string(),
+            secret: secret.to_string(),
+        }
+    }
+
+    /// The `Authorization` header is the sha1 hex digest of key, secret and the unix time
+    /// sent as `X-Auth-Date`.
+    fn auth_header(&self, date: u64) -> String {
+        sha1::Sha1::from(format!("{}{}{date}", self.key, self.secret))
+            .digest()
+            .to_string()
+    }
+}
+
+impl SearchProvider for PodcastIndex {
+    fn name(&self) -> &'static str {
+        "Podcast Index"
+    }
+
+    fn search(&self, agent: &ureq::Agent, term: &str) -> Result<Vec<PodcastFeed>> {
+        let url = format!("{}/api/1.0/search/byterm?q={}", self.base_url, encode(term));
+        let date = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let text = agent
+            .get(&url)
+            .set("User-Agent", &format!("termusic/{}", crate::VERSION))
+            .set("X-Auth-Key", &self.key)
+            .set("X-Auth-Date", &date.to_string())
+            .set("Authorization", &self.auth_header(date))
+            .call()?
+            .into_string()?;
+        parse_podcastindex_results(&text).ok_or_else(|| anyhow!("Error parsing result"))
+    }
+}
+
+pub struct Gpodder {
+    base_url: String,
+}
+
+impl Gpodder {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl SearchProvider for Gpodder {
+    fn name(&self) -> &'static str {
+        "gpodder.net"
+    }
+
+    fn search(&self, agent: &ureq::Agent, term: &str) -> Result<Vec<PodcastFeed>> {
+        let url = format!("{}/search.json?q={}", self.base_url, encode(term));
+        let text = agent.get(&url).call()?.into_string()?;
+        parse_gpodder_results(&text).ok_or_else(|| anyhow!("Error parsing result"))
+    }
+}
+
+/// Returns all providers enabled in the config. A provider is disabled by leaving its url empty,
+/// and Podcast Index is only used when a key and secret are set.
+pub fn providers(config: &Settings) -> Vec<Box<dyn SearchProvider>> {
+    let mut providers: Vec<Box<dyn SearchProvider>> = Vec::new();
+    if !config.podcast_search_itunes_url.is_empty() {
+        providers.push(Box::new(Itunes::new(&config.podcast_search_itunes_url)));
+    }
+    if !config.podcast_search_podcastindex_url.is_empty()
+        && !config.podcast_search_podcastindex_key.is_empty()
+        && !config.podcast_search_podcastindex_secret.is_empty()
+    {
+        providers.push(Box::new(PodcastIndex::new(
+            &config.podcast_search_podcastindex_url,
+            &config.podcast_search_podcastindex_key,
+            &config.podcast_search_podcastindex_secret,
+        )));
+    }
+    if !config.podcast_search_gpodder_url.is_empty() {
+        providers.push(Box::new(Gpodder::new(&config.podcast_search_gpodder_url)));
+    }
+    providers
+}
+
+/// Searches all providers one after another, retrying each up to `max_retries` times, and merges
+/// the results. Feeds found by more than one provider are only listed once. Only fails if no
+/// provider returned a result.
+pub fn search_all(
+    providers: &[Box<dyn SearchProvider>],
+    term: &str,
+    max_retries: usize,
+) -> Result<Vec<PodcastFeed>> {
+    if providers.is_empty() {
+        bail!("No podcast search provider enabled");
+    }
+    let agent = ureq::builder()
+        .timeout_connect(Duration::from_secs(5))
+        .timeout_read(Duration::from_secs(20))
+        .build();
+
+    let mut results: Vec<PodcastFeed> = Vec::new();
+    let mut errors = Vec::new();
+    let mut any_success = false;
+    for provider in providers {
+        let mut retries = max_retries.max(1);
+        let result = loop {
+            match provider.search(&agent, term) {
+                Ok(feeds) => break Ok(feeds),
+                Err(e) => {
+                    retries -= 1;
+                    if retries == 0 {
+                        break Err(e);
+                    }
+                }
+            }
+        };
+        match result {
+            Ok(feeds) => {
+                any_success = true;
+                for feed in feeds {
+                    if !results.iter().any(|f| f.url == feed.url) {
+                        results.push(feed);
+                    }
+                }
+            }
+            Err(e) => errors.push(format!("{}: {e}", provider.name())),
+        }
+    }
+
+    if !any_success {
+        bail!(errors.join("\n"));
+    }
+    Ok(results)
+}
+
+fn encode(term: &str) -> String {
+    utf8_percent_encode(term, NON_ALPHANUMERIC).to_string()
+}
+
+fn parse_itunes_results(data: &str) -> Option<Vec<PodcastFeed>> {
+    let value = serde_json::from_str::<Value>(data).ok()?;
+    let array = value.get("results")?.as_array()?;
+    Some(
+        array
+            .iter()
+            .filter_map(|v| parse_item(v, "collectionName", "feedUrl"))
+            .collect(),
+    )
+}
+
+fn parse_podcastindex_results(data: &str) -> Option<Vec<PodcastFeed>> {
+    let value = serde_json::from_str::<Value>(data).ok()?;
+    let array = value.get("feeds")?.as_array()?;
+    Some(
+        array
+            .iter()
+            .filter_map(|v| parse_item(v, "title", "url"))
+            .collect(),
+    )
+}
+
+fn parse_gpodder_results(data: &str) -> Option<Vec<PodcastFeed>> {
+    let value = serde_json::from_str::<Value>(data).ok()?;
+    let array = value.as_array()?;
+    Some(
+        array
+            .iter()
+            .filter_map(|v| parse_item(v, "title", "url"))
+            .collect(),
+    )
+}
+
+fn parse_item(v: &Value, title_key: &str, url_key: &str) -> Option<PodcastFeed> {
+    let title = v.get(title_key)?.as_str()?.to_owned();
+    let url = v.get(url_key)?.as_str()?.to_owned();
+    Some(PodcastFeed::new(None, &url, Some(title)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_server;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_search_merges_providers() {
+        let itunes = mock_server(
+            r#"{"results":[{"collectionName":"Foo","feedUrl":"https://foo/rss"},{"collectionName":"Bar"}]}"#,
+        );
+        let gpodder = mock_server(
+            r#"[{"title":"Foo again","url":"https://foo/rss"},{"title":"Baz","url":"https://baz/rss"}]"#,
+        );
+        let providers: Vec<Box<dyn SearchProvider>> = vec![
+            Box::new(Itunes::new(&itunes)),
+            Box::new(Gpodder::new(&gpodder)),
+        ];
+        let result = search_all(&providers, "foo", 1).unwrap();
+        let urls: Vec<&str> = result.iter().map(|f| f.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://foo/rss", "https://baz/rss"]);
+    }
+
+    #[test]
+    fn test_parse_podcastindex() {
+        let result = parse_podcastindex_results(
+            r#"{"status":"true","feeds":[{"id":1,"title":"Foo","url":"https://foo/rss"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(result[0].title.as_deref(), Some("Foo"));
+        assert_eq!(result[0].url, "https://foo/rss");
+    }
+}
@@ -0,0 +1,438 @@
+//! Turns the html show notes of an episode into styled lines for the terminal. Links are
+//! numbered like footnotes and listed at the end, and timestamps such as "12:34" are picked up
+//! so the player can jump to them.
+use lazy_static::lazy_static;
+use regex::Regex;
+use unicode_width::UnicodeWidthStr;
+
+lazy_static! {
+    /// Regex for an html tag, capturing the closing slash, the tag name and the attributes
+    static ref RE_TAG: Regex = Regex::new(r"<(/?)([a-zA-Z][a-zA-Z0-9]*)([^<>]*)>").expect("Regex error");
+
+    /// Regex for html comments, which are dropped entirely
+    static ref RE_COMMENT: Regex = Regex::new(r"(?s)<!--.*?-->").expect("Regex error");
+
+    /// Regex for the href attribute of a link
+    static ref RE_HREF: Regex = Regex::new(r#"(?i)href\s*=\s*["']([^"']*)["']"#).expect("Regex error");
+
+    /// Regex for timestamps of the form MM:SS or H:MM:SS
+    static ref RE_TIMESTAMP: Regex = Regex::new(r"\b(?:(\d{1,2}):)?(\d{1,3}):([0-5]\d)\b").expect("Regex error");
+}
+
+/// Something in the notes the user can select and activate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Link(String),
+    /// Position in the episode, in seconds.
+    Timestamp(u64),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoteSpan {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    /// Index into `ShowNotes::targets`.
+    pub target: Option<usize>,
+}
+
+pub type NoteLine = Vec<NoteSpan>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShowNotes {
+    pub lines: Vec<NoteLine>,
+    pub targets: Vec<Target>,
+}
+
+#[derive(Default)]
+struct Parser {
+    notes: ShowNotes,
+    line: NoteLine,
+    links: Vec<String>,
+    bold: usize,
+    italic: usize,
+    href: Option<String>,
+    /// One entry per open list, with the next number for ordered lists.
+    lists: Vec<Option<usize>>,
+    blank_line_pending: bool,
+    skip_depth: usize,
+}
+
+impl Parser {
+    fn flush_line(&mut self) {
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            self.notes.lines.push(line);
+        }
+    }
+
+    fn line_break(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        self.notes.lines.push(line);
+    }
+
+    fn paragraph(&mut self) {
+        self.flush_line();
+        self.blank_line_pending = true;
+    }
+
+    fn push_span(&mut self, text: &str, target: Option<usize>) {
+        if self.blank_line_pending {
+            if self.notes.lines.last().is_some_and(|l| !l.is_empty()) {
+                self.notes.lines.push(Vec::new());
+            }
+            self.blank_line_pending = false;
+        }
+        let bold = self.bold > 0;
+        let italic = self.italic > 0;
+        match self.line.last_mut() {
+            Some(last)
+                if last.bold == bold
+                    && last.italic == italic
+                    && last.target.is_none()
+                    && target.is_none() =>
+            {
+                last.text.push_str(text);
+            }
+            _ => self.line.push(NoteSpan {
+                text: text.to_string(),
+                bold,
+                italic,
+                target,
+            }),
+        }
+    }
+
+    /// Adds text, splitting out any timestamps as their own targets.
+    fn push_text(&mut self, text: &str) {
+        let mut last = 0;
+        for cap in RE_TIMESTAMP.captures_iter(text) {
+            let whole = cap.get(0).expect("capture group 0 always exists");
+            let number = |i: usize| {
+                cap.get(i)
+                    .and_then(|m| m.as_str().parse::<u64>().ok())
+                    .unwrap_or(0)
+            };
+            let secs = number(1) * 3600 + number(2) * 60 + number(3);
+            if whole.start() > last {
+                self.push_span(&text[last..whole.start()], None);
+            }
+            self.notes.targets.push(Target::Timestamp(secs));
+            let target = self.notes.targets.len() - 1;
+            self.push_span(whole.as_str(), Some(target));
+            last = whole.end();
+        }
+        if last < text.len() {
+            self.push_span(&text[last..], None);
+        }
+    }
+
+    fn text(&mut self, raw: &str, preformatted: bool) {
+        if self.skip_depth > 0 {
+            return;
+        }
+        let decoded = escaper::decode_html(raw).unwrap_or_else(|_| raw.to_string());
+        if preformatted {
+            for (i, l) in decoded.lines().enumerate() {
+                if i > 0 {
+                    self.line_break();
+                }
+                let l = l.trim_end();
+                if !l.is_empty() {
+                    self.push_text(l);
+                }
+            }
+            return;
+        }
+        let mut collapsed = String::with_capacity(decoded.len());
+        let mut space = false;
+        for c in decoded.chars() {
+            if c.is_whitespace() {
+                space = true;
+                continue;
+            }
+            if space {
+                collapsed.push(' ');
+            }
+            space = false;
+            collapsed.push(c);
+        }
+        if space {
+            collapsed.push(' ');
+        }
+        // no space at the start of a line or right after another one
+        let after_space = !matches!(self.line.last(), Some(s) if !s.text.ends_with(' '));
+        let text = if after_space {
+            collapsed.trim_start()
+        } else {
+            collapsed.as_str()
+        };
+        if !text.is_empty() {
+            self.push_text(text);
+        }
+    }
+
+    fn tag(&mut self, closing: bool, name: &str, attributes: &str) {
+        match name {
+            "script" | "style" => {
+                if closing {
+                    self.skip_depth = self.skip_depth.saturating_sub(1);
+                } else {
+                    self.skip_depth += 1;
+                }
+            }
+            "br" => self.line_break(),
+            "p" | "div" | "blockquote" | "pre" | "table" | "hr" => self.paragraph(),
+            "tr" => self.flush_line(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.paragraph();
+                self.style(closing, true);
+            }
+            "b" | "strong" => self.style(closing, true),
+            "i" | "em" | "cite" => self.style(closing, false),
+            "ul" | "ol" => {
+                self.flush_line();
+                if closing {
+                    self.lists.pop();
+                    if self.lists.is_empty() {
+                        self.paragraph();
+                    }
+                } else {
+                    if self.lists.is_empty() {
+                        self.paragraph();
+                    }
+                    self.lists.push((name == "ol").then_some(1));
+                }
+            }
+            "li" => {
+                self.flush_line();
+                if !closing {
+                    let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                    let bullet = match self.lists.last_mut() {
+                        Some(Some(n)) => {
+                            *n += 1;
+                            format!("{indent}{}. ", *n - 1)
+                        }
+                        _ => format!("{indent}• "),
+                    };
+                    self.push_span(&bullet, None);
+                }
+            }
+            "a" => {
+                if closing {
+                    if let Some(href) = self.href.take() {
+                        let number = if let Some(i) = self.links.iter().position(|url| *url == href)
+                        {
+                            i + 1
+                        } else {
+                            self.links.push(href.clone());
+                            self.links.len()
+                        };
+                        self.notes.targets.push(Target::Link(href));
+                        let target = self.notes.targets.len() - 1;
+                        self.push_span(&format!("[{number}]"), Some(target));
+                    }
+                } else {
+                    self.href = RE_HREF
+                        .captures(attributes)
+                        .and_then(|c| c.get(1))
+                        .map(|m| {
+                            escaper::decode_html(m.as_str())
+                                .unwrap_or_else(|_| m.as_str().to_string())
+                        })
+                        .filter(|href| !href.is_empty() && !href.starts_with('#'));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn style(&mut self, closing: bool, bold: bool) {
+        let counter = if bold {
+            &mut self.bold
+        } else {
+            &mut self.italic
+        };
+        if closing {
+            *counter = counter.saturating_sub(1);
+        } else {
+            *counter += 1;
+        }
+    }
+
+    fn finish(mut self) -> ShowNotes {
+        self.flush_line();
+        while self.notes.lines.last().is_some_and(Vec::is_empty) {
+            self.notes.lines.pop();
+        }
+        if !self.links.is_empty() {
+            self.notes.lines.push(Vec::new());
+            self.bold = 1;
+            self.push_span("Links:", None);
+            self.bold = 0;
+            self.flush_line();
+            for (i, url) in std::mem::take(&mut self.links).into_iter().enumerate() {
+                // the footnote list points to the first mention of the link
+                let target = self
+                    .notes
+                    .targets
+                    .iter()
+                    .position(|t| *t == Target::Link(url.clone()));
+                self.push_span(&format!("[{}] ", i + 1), None);
+                self.push_span(&url, target);
+                self.flush_line();
+            }
+        }
+        self.notes
+    }
+}
+
+impl ShowNotes {
+    /// Parses show notes, which may be html or plain text. Plain text keeps its line breaks.
+    pub fn parse(description: &str) -> Self {
+        let description = RE_COMMENT.replace_all(description, "");
+        let mut parser = Parser::default();
+        let is_html = RE_TAG.is_match(&description);
+        let mut preformatted = !is_html;
+        let mut last = 0;
+        for cap in RE_TAG.captures_iter(&description) {
+            let whole = cap.get(0).expect("capture group 0 always exists");
+            parser.text(&description[last..whole.start()], preformatted);
+            let closing = &cap[1] == "/";
+            let name = cap[2].to_lowercase();
+            if name == "pre" {
+                preformatted = !closing;
+            }
+            parser.tag(closing, &name, &cap[3]);
+            last = whole.end();
+        }
+        parser.text(&description[last..], preformatted);
+        parser.finish()
+    }
+
+    /// Returns the lines wrapped at word boundaries to fit into `width` columns.
+    pub fn wrap(&self, width: usize) -> Vec<NoteLine> {
+        let width = width.max(1);
+        let mut rows = Vec::new();
+        for line in &self.lines {
+            let mut row: NoteLine = Vec::new();
+            let mut row_width = 0;
+            for span in line {
+                for word in span.text.split_inclusive(' ') {
+                    let mut word = word.to_string();
+                    let word_width = UnicodeWidthStr::width(word.trim_end());
+                    if row_width > 0 && row_width + word_width > width {
+                        rows.push(std::mem::take(&mut row));
+                        row_width = 0;
+                    }
+                    // words longer than a whole row are broken up
+                    while UnicodeWidthStr::width(word.trim_end()) > width {
+                        let mut split = 0;
+                        let mut split_width = 0;
+                        for (i, c) in word.char_indices() {
+                            let w = unicode_width::UnicodeWidthChar::width(c).unwrap_or(0);
+                            if split_width + w > width - row_width && split > 0 {
+                                break;
+                            }
+                            split_width += w;
+                            split = i + c.len_utf8();
+                        }
+                        let rest = word.split_off(split);
+                        push_to_row(&mut row, span, &word);
+                        rows.push(std::mem::take(&mut row));
+                        row_width = 0;
+                        word = rest;
+                    }
+                    row_width += UnicodeWidthStr::width(word.as_str());
+                    push_to_row(&mut row, span, &word);
+                }
+            }
+            rows.push(row);
+        }
+        rows
+    }
+
+    /// Returns the text of every line without any styling.
+    pub fn plain_lines(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .map(|l| l.iter().map(|s| s.text.as_str()).collect())
+            .collect()
+    }
+}
+
+fn push_to_row(row: &mut NoteLine, span: &NoteSpan, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match row.last_mut() {
+        Some(last)
+            if last.bold == span.bold
+                && last.italic == span.italic
+                && last.target == span.target =>
+        {
+            last.text.push_str(text);
+        }
+        _ => row.push(NoteSpan {
+            text: text.to_string(),
+            ..span.clone()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_html_notes() {
+        let notes = ShowNotes::parse(
+            "<p>Hello <b>world</b> &amp; <a href=\"https://example.com\">friends</a>.</p>\
+             <ul><li>intro at 1:05</li><li>outro</li></ul>",
+        );
+        assert_eq!(
+            notes.plain_lines(),
+            vec![
+                "Hello world & friends[1].",
+                "",
+                "• intro at 1:05",
+                "• outro",
+                "",
+                "Links:",
+                "[1] https://example.com",
+            ]
+        );
+        assert!(notes.lines[0][1].bold);
+        assert_eq!(
+            notes.targets,
+            vec![
+                Target::Link("https://example.com".to_string()),
+                Target::Timestamp(65)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_plain_notes() {
+        let notes = ShowNotes::parse("First line\nSecond at 1:02:03\n\nThird");
+        assert_eq!(
+            notes.plain_lines(),
+            vec!["First line", "Second at 1:02:03", "", "Third"]
+        );
+        assert_eq!(notes.targets, vec![Target::Timestamp(3723)]);
+    }
+
+    #[test]
+    fn test_wrap_keeps_targets() {
+        let notes = ShowNotes::parse("<p>one two three <a href=\"https://a\">four</a></p>");
+        let rows = notes.wrap(14);
+        let text: Vec<String> = rows
+            .iter()
+            .map(|r| r.iter().map(|s| s.text.as_str()).collect())
+            .collect();
+        assert_eq!(text[0], "one two three ");
+        assert_eq!(text[1], "four[1]");
+        assert!(rows[1].iter().any(|s| s.target == Some(0)));
+    }
+}
@@ -0,0 +1,513 @@
+//! Client for the gpodder.net api v2 and the nextcloud gpodder sync app, which share the same
+//! subscription and episode action formats but live under different paths.
+use super::db::Database;
+use crate::config::{PodcastSyncService, Settings};
+use anyhow::Result;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// An episode action as defined by the gpodder api. termusic only sends `play` and `new`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpisodeAction {
+    pub podcast: String,
+    pub episode: String,
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SubscriptionChanges {
+    #[serde(default)]
+    add: Vec<String>,
+    #[serde(default)]
+    remove: Vec<String>,
+    #[serde(default)]
+    timestamp: i64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct EpisodeActions {
+    #[serde(default)]
+    actions: Vec<EpisodeAction>,
+    #[serde(default)]
+    timestamp: i64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct UploadResponse {
+    #[serde(default)]
+    timestamp: i64,
+}
+
+/// What changed locally after a sync, for the ui to pick up.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncOutcome {
+    /// Feeds subscribed on another device, which still need to be fetched.
+    pub new_feeds: Vec<String>,
+    /// Feeds unsubscribed on another device.
+    pub removed_feeds: Vec<String>,
+    pub episodes_updated: usize,
+}
+
+pub struct SyncClient {
+    agent: ureq::Agent,
+    service: PodcastSyncService,
+    base_url: String,
+    username: String,
+    auth: String,
+    device: String,
+}
+
+impl SyncClient {
+    /// Returns `None` if no sync server is configured.
+    pub fn from_config(config: &Settings) -> Option<Self> {
+        if config.podcast_sync_url.is_empty() {
+            return None;
+        }
+        let agent = ureq::builder()
+            .timeout_connect(Duration::from_secs(5))
+            .timeout_read(Duration::from_secs(20))
+            .build();
+        let auth = general_purpose::STANDARD.encode(format!(
+            "{}:{}",
+            config.podcast_sync_username, config.podcast_sync_password
+        ));
+        Some(Self {
+            agent,
+            service: config.podcast_sync_service.clone(),
+            base_url: config.podcast_sync_url.trim_end_matches('/').to_string(),
+            username: config.podcast_sync_username.clone(),
+            auth: format!("Basic {auth}"),
+            device: config.podcast_sync_device.clone(),
+        })
+    }
+
+    fn subscriptions_url(&self, upload: bool) -> String {
+        match self.service {
+            PodcastSyncService::Gpodder => format!(
+                "{}/api/2/subscriptions/{}/{}.json",
+                self.base_url, self.username, self.device
+            ),
+            PodcastSyncService::Nextcloud if upload => format!(
+                "{}/index.php/apps/gpoddersync/subscription_change/create",
+                self.base_url
+            ),
+            PodcastSyncService::Nextcloud => {
+                format!("{}/index.php/apps/gpoddersync/subscriptions", self.base_url)
+            }
+        }
+    }
+
+    fn episodes_url(&self, upload: bool) -> String {
+        match self.service {
+            PodcastSyncService::Gpodder => {
+                format!("{}/api/2/episodes/{}.json", self.base_url, self.username)
+            }
+            PodcastSyncService::Nextcloud if upload => format!(
+                "{}/index.php/apps/gpoddersync/episode_action/create",
+                self.base_url
+            ),
+            PodcastSyncService::Nextcloud => {
+                format!(
+                    "{}/index.php/apps/gpoddersync/episode_action",
+                    self.base_url
+                )
+            }
+        }
+    }
+
+    fn get<T: for<'de> Deserialize<'de>>(&self, url: &str, since: i64) -> Result<T> {
+        let resp = self
+            .agent
+            .get(url)
+            .set("Authorization", &self.auth)
+            .query("since", &since.to_string())
+            .call()?;
+        Ok(serde_json::from_str(&resp.into_string()?)?)
+    }
+
+    fn post<T: Serialize>(&self, url: &str, body: &T) -> Result<i64> {
+        let resp = self
+            .agent
+            .post(url)
+            .set("Authorization", &self.auth)
+            .set("Content-Type", "application/json")
+            .send_string(&serde_json::to_string(body)?)?;
+        let upload: UploadResponse = serde_json::from_str(&resp.into_string()?).unwrap_or_default();
+        Ok(upload.timestamp)
+    }
+
+    fn pull_subscriptions(&self, since: i64) -> Result<SubscriptionChanges> {
+        self.get(&self.subscriptions_url(false), since)
+    }
+
+    fn push_subscriptions(&self, add: &[String], remove: &[String]) -> Result<i64> {
+        let body = serde_json::json!({ "add": add, "remove": remove });
+        self.post(&self.subscriptions_url(true), &body)
+    }
+
+    fn pull_episode_actions(&self, since: i64) -> Result<EpisodeActions> {
+        self.get(&self.episodes_url(false), since)
+    }
+
+    fn push_episode_actions(&self, actions: &[EpisodeAction]) -> Result<i64> {
+        let actions: Vec<EpisodeAction> = actions
+            .iter()
+            .cloned()
+            .map(|mut a| {
+                a.device = Some(self.device.clone());
+                a
+            })
+            .collect();
+        self.post(&self.episodes_url(true), &actions)
+    }
+
+    /// Pulls changes made on other devices into `db`, then pushes the local changes recorded
+    /// since the last sync. When both sides changed the same episode, the newer action wins.
+    pub fn sync(&self, db: &Database) -> Result<SyncOutcome> {
+        let mut outcome = SyncOutcome::default();
+        let (subscriptions_since, episodes_since) = db.get_sync_state();
+
+        // subscriptions
+        let remote = self.pull_subscriptions(subscriptions_since)?;
+        let pending = db.get_pending_subscriptions()?;
+        let local_urls: Vec<String> = db.get_podcasts()?.into_iter().map(|p| p.url).collect();
+        for url in remote.add {
+            if !local_urls.contains(&url) && !pending.iter().any(|(u, added)| *u == url && !added) {
+                outcome.new_feeds.push(url);
+            }
+        }
+        for url in remote.remove {
+            if local_urls.contains(&url) && !pending.iter().any(|(u, added)| *u == url && *added) {
+                outcome.removed_feeds.push(url);
+            }
+        }
+
+        let (mut added, removed) = split_subscriptions(pending);
+        if subscriptions_since == 0 {
+            // first sync, the server doesn't know about anything subscribed before
+            for url in local_urls {
+                if !added.contains(&url) && !removed.contains(&url) {
+                    added.push(url);
+                }
+            }
+        }
+        let mut subscriptions_since = remote.timestamp;
+        if !added.is_empty() || !removed.is_empty() {
+            subscriptions_since =
+                subscriptions_since.max(self.push_subscriptions(&added, &removed)?);
+        }
+        db.clear_pending_subscriptions(&added, &removed)?;
+
+        // episode actions
+        let mut remote = self.pull_episode_actions(episodes_since)?;
+        let pending = db.get_pending_episode_actions()?;
+        remote
+            .actions
+            .sort_by_key(|a| parse_timestamp(a.timestamp.as_deref()));
+        for action in &remote.actions {
+            let remote_time = parse_timestamp(action.timestamp.as_deref());
+            let newer_local = pending.iter().any(|p| {
+                p.episode == action.episode && parse_timestamp(p.timestamp.as_deref()) > remote_time
+            });
+            if !newer_local && db.apply_episode_action(action)? {
+                outcome.episodes_updated += 1;
+            }
+        }
+        let mut episodes_since = remote.timestamp;
+        if !pending.is_empty() {
+            episodes_since = episodes_since.max(self.push_episode_actions(&pending)?);
+        }
+        db.clear_pending_episode_actions(&pending)?;
+
+        db.set_sync_state(subscriptions_since, episodes_since)?;
+        Ok(outcome)
+    }
+
+    /// Only sends the local changes, without pulling anything. Used on exit, where feeds
+    /// subscribed elsewhere could not be fetched anymore.
+    pub fn push(&self, db: &Database) -> Result<()> {
+        let (added, removed) = split_subscriptions(db.get_pending_subscriptions()?);
+        if !added.is_empty() || !removed.is_empty() {
+            self.push_subscriptions(&added, &removed)?;
+        }
+        db.clear_pending_subscriptions(&added, &removed)?;
+
+        let pending = db.get_pending_episode_actions()?;
+        if !pending.is_empty() {
+            self.push_episode_actions(&pending)?;
+        }
+        db.clear_pending_episode_actions(&pending)?;
+        Ok(())
+    }
+}
+
+fn split_subscriptions(pending: Vec<(String, bool)>) -> (Vec<String>, Vec<String>) {
+    let (add, remove): (Vec<_>, Vec<_>) = pending.into_iter().partition(|(_, added)| *added);
+    (
+        add.into_iter().map(|(url, _)| url).collect(),
+        remove.into_iter().map(|(url, _)| url).collect(),
+    )
+}
+
+pub fn format_timestamp(timestamp: i64) -> String {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .unwrap_or_else(Utc::now)
+        .format(TIMESTAMP_FORMAT)
+        .to_string()
+}
+
+/// Returns the unix time of a gpodder timestamp, or 0 if it is missing or malformed.
+pub fn parse_timestamp(timestamp: Option<&str>) -> i64 {
+    timestamp
+        .and_then(|t| NaiveDateTime::parse_from_str(t, TIMESTAMP_FORMAT).ok())
+        .map_or(0, |t| t.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::podcast::{EpisodeNoId, PodcastNoId};
+    use crate::test_utils::mock_server_sequence;
+    use pretty_assertions::assert_eq;
+
+    const SUBSCRIPTIONS: &str = "/api/2/subscriptions/user/phone.json";
+    const EPISODES: &str = "/api/2/episodes/user.json";
+
+    fn client(base_url: String) -> SyncClient {
+        SyncClient {
+            agent: ureq::agent(),
+            service: PodcastSyncService::Gpodder,
+            base_url,
+            username: "user".to_string(),
+            auth: "Basic dXNlcjpwYXNz".to_string(),
+            device: "phone".to_string(),
+        }
+    }
+
+    fn podcast(url: &str, episodes: &[&str]) -> PodcastNoId {
+        let episodes = episodes
+            .iter()
+            .map(|ep| EpisodeNoId {
+                title: (*ep).to_string(),
+                url: (*ep).to_string(),
+                guid: (*ep).to_string(),
+                description: String::new(),
+                pubdate: None,
+                duration: Some(600),
+                image_url: None,
+            })
+            .collect();
+        PodcastNoId {
+            title: url.to_string(),
+            url: url.to_string(),
+            description: None,
+            author: None,
+            explicit: None,
+            last_checked: Utc::now(),
+            episodes,
+            image_url: None,
+        }
+    }
+
+    /// Adds a feed with one episode per url, without leaving it queued for the server.
+    fn add_podcast(db: &Database, url: &str, episodes: &[&str]) -> i64 {
+        db.insert_podcast(&podcast(url, episodes)).unwrap();
+        db.clear_pending_subscriptions(&[url.to_string()], &[])
+            .unwrap();
+        db.get_podcasts()
+            .unwrap()
+            .into_iter()
+            .find(|p| p.url == url)
+            .unwrap()
+            .id
+    }
+
+    fn episode(db: &Database, pod_id: i64, url: &str) -> crate::podcast::Episode {
+        db.get_episodes(pod_id, true)
+            .unwrap()
+            .into_iter()
+            .find(|ep| ep.url == url)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_first_sync_seeds_subscriptions() {
+        let db = Database::in_memory("test_first_sync_seeds_subscriptions").unwrap();
+        add_podcast(&db, "http://a/rss", &[]);
+        let (url, requests) = mock_server_sequence(vec![
+            r#"{"add":[],"remove":[],"timestamp":10}"#,
+            r#"{"timestamp":12}"#,
+            r#"{"actions":[],"timestamp":20}"#,
+        ]);
+
+        let outcome = client(url).sync(&db).unwrap();
+
+        assert_eq!(outcome, SyncOutcome::default());
+        assert!(requests
+            .recv()
+            .unwrap()
+            .starts_with(&format!("GET {SUBSCRIPTIONS}?since=0")));
+        assert_eq!(
+            requests.recv().unwrap(),
+            format!(
+                r#"POST {SUBSCRIPTIONS}{}{{"add":["http://a/rss"],"remove":[]}}"#,
+                '\n'
+            )
+        );
+        assert!(requests
+            .recv()
+            .unwrap()
+            .starts_with(&format!("GET {EPISODES}?since=0")));
+        assert_eq!(db.get_sync_state(), (12, 20));
+    }
+
+    #[test]
+    fn test_pending_subscriptions_win() {
+        let db = Database::in_memory("test_pending_subscriptions_win").unwrap();
+        db.set_sync_state(5, 5).unwrap();
+        let removed = add_podcast(&db, "http://removed/rss", &[]);
+        db.remove_podcast(removed).unwrap();
+        db.insert_podcast(&podcast("http://added/rss", &[]))
+            .unwrap();
+        // the server saw the opposite from another device before this one synced
+        let (url, requests) = mock_server_sequence(vec![
+            r#"{"add":["http://removed/rss","http://other/rss"],"remove":["http://added/rss"],"timestamp":10}"#,
+            r#"{"timestamp":12}"#,
+            r#"{"actions":[],"timestamp":20}"#,
+        ]);
+
+        let outcome = client(url).sync(&db).unwrap();
+
+        assert_eq!(outcome.new_feeds, vec!["http://other/rss".to_string()]);
+        assert!(outcome.removed_feeds.is_empty());
+        requests.recv().unwrap();
+        assert_eq!(
+            requests.recv().unwrap(),
+            format!(
+                r#"POST {SUBSCRIPTIONS}{}{{"add":["http://added/rss"],"remove":["http://removed/rss"]}}"#,
+                '\n'
+            )
+        );
+        assert!(db.get_pending_subscriptions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_newer_local_action_wins() {
+        let db = Database::in_memory("test_newer_local_action_wins").unwrap();
+        db.set_sync_state(5, 5).unwrap();
+        let pod_id = add_podcast(&db, "http://a/rss", &["http://a/1.mp3", "http://a/2.mp3"]);
+        let first = episode(&db, pod_id, "http://a/1.mp3");
+        db.set_played_status(first.id, true).unwrap();
+        let (url, requests) = mock_server_sequence(vec![
+            r#"{"add":[],"remove":[],"timestamp":10}"#,
+            r#"{"actions":[
+                {"podcast":"http://a/rss","episode":"http://a/1.mp3","action":"new","timestamp":"2009-12-12T09:00:00"},
+                {"podcast":"http://a/rss","episode":"http://a/2.mp3","action":"play","position":100,"total":600,"timestamp":"2009-12-12T09:00:00"}
+            ],"timestamp":20}"#,
+            r#"{"timestamp":22}"#,
+        ]);
+
+        let outcome = client(url).sync(&db).unwrap();
+
+        assert_eq!(outcome.episodes_updated, 1);
+        assert!(episode(&db, pod_id, "http://a/1.mp3").played);
+        let second = episode(&db, pod_id, "http://a/2.mp3");
+        assert_eq!(second.last_position, Some(100));
+        assert!(!second.played);
+        requests.recv().unwrap();
+        requests.recv().unwrap();
+        let pushed = requests.recv().unwrap();
+        assert!(pushed.starts_with(&format!("POST {EPISODES}")));
+        let pushed: Vec<EpisodeAction> =
+            serde_json::from_str(pushed.split_once('\n').unwrap().1).unwrap();
+        assert_eq!(pushed.len(), 1);
+        assert_eq!(pushed[0].episode, "http://a/1.mp3");
+        assert_eq!(pushed[0].action, "play");
+        assert_eq!(pushed[0].device.as_deref(), Some("phone"));
+        assert_eq!(db.get_sync_state(), (10, 22));
+    }
+
+    #[test]
+    fn test_remote_actions_are_not_echoed() {
+        let db = Database::in_memory("test_remote_actions_are_not_echoed").unwrap();
+        db.set_sync_state(5, 5).unwrap();
+        let pod_id = add_podcast(&db, "http://a/rss", &["http://a/1.mp3"]);
+        // no response is left for a push, so pushing anything fails the sync
+        let (url, requests) = mock_server_sequence(vec![
+            r#"{"add":[],"remove":[],"timestamp":10}"#,
+            r#"{"actions":[{"podcast":"http://a/rss","episode":"http://a/1.mp3","action":"play","position":598,"total":600}],"timestamp":20}"#,
+        ]);
+
+        let outcome = client(url).sync(&db).unwrap();
+
+        assert_eq!(outcome.episodes_updated, 1);
+        assert!(episode(&db, pod_id, "http://a/1.mp3").played);
+        assert!(db.get_pending_episode_actions().unwrap().is_empty());
+        assert_eq!(requests.iter().count(), 2);
+        assert_eq!(db.get_sync_state(), (10, 20));
+    }
+
+    #[test]
+    fn test_push() {
+        let db = Database::in_memory("test_push").unwrap();
+        let pod_id = add_podcast(&db, "http://a/rss", &["http://a/1.mp3"]);
+        let removed = add_podcast(&db, "http://b/rss", &[]);
+        db.remove_podcast(removed).unwrap();
+        db.set_played_status(episode(&db, pod_id, "http://a/1.mp3").id, false)
+            .unwrap();
+        let (url, requests) =
+            mock_server_sequence(vec![r#"{"timestamp":12}"#, r#"{"timestamp":22}"#]);
+
+        client(url).push(&db).unwrap();
+
+        assert_eq!(
+            requests.recv().unwrap(),
+            format!(
+                r#"POST {SUBSCRIPTIONS}{}{{"add":[],"remove":["http://b/rss"]}}"#,
+                '\n'
+            )
+        );
+        let pushed = requests.recv().unwrap();
+        assert!(pushed.starts_with(&format!("POST {EPISODES}")));
+        assert!(pushed.contains(r#""action":"new""#));
+        assert!(db.get_pending_subscriptions().unwrap().is_empty());
+        assert!(db.get_pending_episode_actions().unwrap().is_empty());
+        // pushing doesn't count as a sync, the next one still pulls everything
+        assert_eq!(db.get_sync_state(), (0, 0));
+    }
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        let ts = 1_260_608_400;
+        assert_eq!(format_timestamp(ts), "2009-12-12T09:00:00");
+        assert_eq!(parse_timestamp(Some("2009-12-12T09:00:00")), ts);
+        assert_eq!(parse_timestamp(Some("garbage")), 0);
+    }
+
+    #[test]
+    fn test_episode_action_json() {
+        let action: EpisodeAction = serde_json::from_str(
+            r#"{"podcast":"http://a/rss","episode":"http://a/1.mp3","action":"PLAY","position":120,"total":600}"#,
+        )
+        .unwrap();
+        assert_eq!(action.position, Some(120));
+        assert_eq!(action.timestamp, None);
+        let json = serde_json::to_string(&action).unwrap();
+        assert!(!json.contains("started"));
+    }
+}
@@ -0,0 +1,101 @@
+use super::Listen;
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+/// Scrobbles that were not accepted yet, one row per service.
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute(
+            "create table if not exists scrobbles(
+             id integer primary key,
+             service TEXT NOT NULL,
+             artist TEXT NOT NULL,
+             title TEXT NOT NULL,
+             album TEXT,
+             duration INTEGER NOT NULL,
+             timestamp INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        Ok(Self { conn })
+    }
+
+    pub fn push(&self, service: &str, listen: &Listen) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO scrobbles (service, artist, title, album, duration, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                service,
+                listen.artist,
+                listen.title,
+                listen.album,
+                listen.duration,
+                listen.timestamp
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The oldest scrobbles waiting for `service`, with their ids.
+    pub fn pending(&self, service: &str, limit: usize) -> Result<Vec<(i64, Listen)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, artist, title, album, duration, timestamp FROM scrobbles
+             WHERE service = ?1 ORDER BY timestamp LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![service, limit], |row| {
+            Ok((
+                row.get(0)?,
+                Listen {
+                    artist: row.get(1)?,
+                    title: row.get(2)?,
+                    album: row.get(3)?,
+                    duration: row.get(4)?,
+                    timestamp: row.get(5)?,
+                },
+            ))
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    pub fn remove(&self, ids: &[i64]) -> Result<()> {
+        for id in ids {
+            self.conn
+                .execute("DELETE FROM scrobbles WHERE id = ?1", params![id])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_queue() {
+        let db = Database::open(Path::new(":memory:")).unwrap();
+        let listen = |title: &str, timestamp| Listen {
+            artist: "Artist".to_string(),
+            title: title.to_string(),
+            album: None,
+            duration: 200,
+            timestamp,
+        };
+        db.push("lastfm", &listen("second", 20)).unwrap();
+        db.push("lastfm", &listen("first", 10)).unwrap();
+        db.push("listenbrainz", &listen("first", 10)).unwrap();
+
+        let pending = db.pending("lastfm", 50).unwrap();
+        let titles: Vec<&str> = pending.iter().map(|(_, l)| l.title.as_str()).collect();
+        assert_eq!(titles, ["first", "second"]);
+
+        db.remove(&[pending[0].0]).unwrap();
+        assert_eq!(db.pending("lastfm", 50).unwrap().len(), 1);
+        assert_eq!(db.pending("listenbrainz", 50).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,159 @@
+use super::{Listen, Rejected, Service};
+use crate::config::Settings;
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+use std::time::Duration;
+
+// error codes of last.fm about the parameters, anything else may work when tried again
+const INVALID_PARAMETERS: i64 = 6;
+const INVALID_RESOURCE: i64 = 7;
+
+pub struct LastFm {
+    agent: ureq::Agent,
+    url: String,
+    api_key: String,
+    api_secret: String,
+    session: String,
+}
+
+impl LastFm {
+    /// Returns `None` if the api key or the session key is missing.
+    pub fn from_config(config: &Settings) -> Option<Self> {
+        if config.lastfm_api_key.is_empty() || config.lastfm_session_key.is_empty() {
+            return None;
+        }
+        Some(Self::new(config, config.lastfm_session_key.clone()))
+    }
+
+    fn new(config: &Settings, session: String) -> Self {
+        let agent = ureq::builder()
+            .timeout_connect(Duration::from_secs(5))
+            .timeout_read(Duration::from_secs(20))
+            .build();
+        Self {
+            agent,
+            url: config.lastfm_url.clone(),
+            api_key: config.lastfm_api_key.clone(),
+            api_secret: config.lastfm_api_secret.clone(),
+            session,
+        }
+    }
+
+    /// Logs in once and returns the session key, so the password is never stored.
+    pub fn login(config: &Settings, username: &str, password: &str) -> Result<String> {
+        if config.lastfm_api_key.is_empty() || config.lastfm_api_secret.is_empty() {
+            bail!("set lastfm_api_key and lastfm_api_secret in the config first");
+        }
+        let value = Self::new(config, String::new()).call(vec![
+            ("method".to_string(), "auth.getMobileSession".to_string()),
+            ("username".to_string(), username.to_string()),
+            ("password".to_string(), password.to_string()),
+        ])?;
+        Ok(value["session"]["key"]
+            .as_str()
+            .ok_or_else(|| anyhow!("no session key in the answer of last.fm"))?
+            .to_string())
+    }
+
+    /// Calls a signed api method.
+    fn call(&self, mut params: Vec<(String, String)>) -> Result<Value> {
+        params.push(("api_key".to_string(), self.api_key.clone()));
+        let signature = signature(&params, &self.api_secret);
+        params.push(("api_sig".to_string(), signature));
+        params.push(("format".to_string(), "json".to_string()));
+        let form: Vec<(&str, &str)> = params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+
+        // errors come with a json body as well
+        let text = match self.agent.post(&self.url).send_form(&form) {
+            Ok(resp) | Err(ureq::Error::Status(_, resp)) => resp.into_string()?,
+            Err(e) => return Err(e.into()),
+        };
+        let value: Value = serde_json::from_str(&text)?;
+        if let Some(message) = value.get("message").and_then(Value::as_str) {
+            return Err(match value.get("error").and_then(Value::as_i64) {
+                Some(INVALID_PARAMETERS | INVALID_RESOURCE) => Rejected(message.to_string()).into(),
+                _ => anyhow!("{message}"),
+            });
+        }
+        Ok(value)
+    }
+
+    fn call_with_session(&self, mut params: Vec<(String, String)>) -> Result<()> {
+        params.push(("sk".to_string(), self.session.clone()));
+        self.call(params)?;
+        Ok(())
+    }
+}
+
+impl Service for LastFm {
+    fn name(&self) -> &'static str {
+        "lastfm"
+    }
+
+    fn now_playing(&mut self, listen: &Listen) -> Result<()> {
+        let mut params = vec![("method".to_string(), "track.updateNowPlaying".to_string())];
+        params.extend(track_params(listen, None));
+        self.call_with_session(params)
+    }
+
+    fn scrobble(&mut self, listens: &[Listen]) -> Result<()> {
+        let mut params = vec![("method".to_string(), "track.scrobble".to_string())];
+        for (idx, listen) in listens.iter().enumerate() {
+            params.extend(track_params(listen, Some(idx)));
+        }
+        self.call_with_session(params)
+    }
+}
+
+/// The track parameters, numbered like `artist[0]` in batches.
+fn track_params(listen: &Listen, idx: Option<usize>) -> Vec<(String, String)> {
+    let key = |name: &str| match idx {
+        Some(idx) => format!("{name}[{idx}]"),
+        None => name.to_string(),
+    };
+    let mut params = vec![
+        (key("artist"), listen.artist.clone()),
+        (key("track"), listen.title.clone()),
+        (key("duration"), listen.duration.to_string()),
+    ];
+    if let Some(album) = &listen.album {
+        params.push((key("album"), album.clone()));
+    }
+    if idx.is_some() {
+        params.push((key("timestamp"), listen.timestamp.to_string()));
+    }
+    params
+}
+
+/// md5 of all parameters sorted by name, followed by the secret.
+fn signature(params: &[(String, String)], secret: &str) -> String {
+    let mut sorted: Vec<&(String, String)> = params.iter().collect();
+    sorted.sort();
+    let mut text = String::new();
+    for (name, value) in sorted {
+        text.push_str(name);
+        text.push_str(value);
+    }
+    text.push_str(secret);
+    format!("{:x}", md5::compute(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        let params = [
+            ("method", "auth.getMobileSession"),
+            ("username", "u"),
+            ("password", "p"),
+            ("api_key", "k"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        assert_eq!(signature(&params, "s"), "9cdeaa639a2fd1ea5deb8daeee625799");
+    }
+}
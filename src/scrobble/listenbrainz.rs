@@ -0,0 +1,88 @@
+use super::{Listen, Rejected, Service};
+use crate::config::Settings;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+pub struct ListenBrainz {
+    agent: ureq::Agent,
+    url: String,
+    token: String,
+}
+
+impl ListenBrainz {
+    /// Returns `None` if no user token is set.
+    pub fn from_config(config: &Settings) -> Option<Self> {
+        if config.listenbrainz_token.is_empty() {
+            return None;
+        }
+        let agent = ureq::builder()
+            .timeout_connect(Duration::from_secs(5))
+            .timeout_read(Duration::from_secs(20))
+            .build();
+        Some(Self {
+            agent,
+            url: config.listenbrainz_url.trim_end_matches('/').to_string(),
+            token: config.listenbrainz_token.clone(),
+        })
+    }
+
+    fn submit(&self, listen_type: &str, payload: &[Value]) -> Result<()> {
+        let body = json!({ "listen_type": listen_type, "payload": payload });
+        let result = self
+            .agent
+            .post(&format!("{}/1/submit-listens", self.url))
+            .set("Authorization", &format!("Token {}", self.token))
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string());
+        match result {
+            Ok(_) => Ok(()),
+            // the listens themselves are invalid
+            Err(ureq::Error::Status(400, resp)) => Err(Rejected(resp.into_string()?).into()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl Service for ListenBrainz {
+    fn name(&self) -> &'static str {
+        "listenbrainz"
+    }
+
+    fn now_playing(&mut self, listen: &Listen) -> Result<()> {
+        self.submit("playing_now", &[payload(listen, false)])
+    }
+
+    fn scrobble(&mut self, listens: &[Listen]) -> Result<()> {
+        let listen_type = if listens.len() == 1 {
+            "single"
+        } else {
+            "import"
+        };
+        let payload: Vec<Value> = listens.iter().map(|l| payload(l, true)).collect();
+        self.submit(listen_type, &payload)
+    }
+}
+
+fn payload(listen: &Listen, listened: bool) -> Value {
+    let mut value = json!({
+        "track_metadata": {
+            "artist_name": listen.artist,
+            "track_name": listen.title,
+            "additional_info": {
+                "duration_ms": listen.duration * 1000,
+                "media_player": "termusic",
+                "submission_client": "termusic",
+                "submission_client_version": crate::VERSION,
+            },
+        },
+    });
+    if let Some(album) = &listen.album {
+        value["track_metadata"]["release_name"] = json!(album);
+    }
+    // "playing_now" must not have a time
+    if listened {
+        value["listened_at"] = json!(listen.timestamp);
+    }
+    value
+}
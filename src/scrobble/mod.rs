@@ -0,0 +1,337 @@
+//! Scrobbling to last.fm and listenbrainz. Listens wait in a small sqlite queue until a service
+//! accepted them, so nothing is lost while offline.
+mod db;
+mod lastfm;
+mod listenbrainz;
+
+use crate::config::Settings;
+use crate::track::{MediaType, Track};
+use crate::utils::get_app_config_path;
+use anyhow::{bail, Result};
+use db::Database;
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+use tuirealm::terminal::TerminalBridge;
+
+// The rules of last.fm, which listenbrainz follows as well: tracks longer than 30 seconds
+// count once half of them, or 4 minutes, have been played.
+const MIN_DURATION: i64 = 30;
+const MAX_WAIT: i64 = 240;
+// last.fm takes at most 50 scrobbles per request
+const BATCH: usize = 50;
+// pending scrobbles are retried this often, even if nothing is playing
+const RETRY_SECS: u64 = 300;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Listen {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    /// Length of the track in seconds.
+    pub duration: i64,
+    /// Unix time the track started playing.
+    pub timestamp: i64,
+}
+
+/// A service refused the scrobbles themselves, so sending them again won't help.
+#[derive(Debug)]
+struct Rejected(String);
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rejected: {}", self.0)
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+trait Service: Send {
+    /// Name of the service in the queue.
+    fn name(&self) -> &'static str;
+    fn now_playing(&mut self, listen: &Listen) -> Result<()>;
+    fn scrobble(&mut self, listens: &[Listen]) -> Result<()>;
+}
+
+enum Command {
+    NowPlaying(Listen),
+    Scrobble(Listen),
+}
+
+pub struct Scrobbler {
+    tx: Sender<Command>,
+    music: bool,
+    podcast: bool,
+    youtube: bool,
+    /// The playing track until it is scrobbled.
+    current: Option<Listen>,
+}
+
+impl Scrobbler {
+    /// Returns `None` if no service is configured.
+    pub fn from_config(config: &Settings) -> Option<Self> {
+        let mut services: Vec<Box<dyn Service>> = Vec::new();
+        if let Some(lastfm) = lastfm::LastFm::from_config(config) {
+            services.push(Box::new(lastfm));
+        }
+        if let Some(listenbrainz) = listenbrainz::ListenBrainz::from_config(config) {
+            services.push(Box::new(listenbrainz));
+        }
+        if services.is_empty() {
+            return None;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            if let Err(e) = worker(services, &rx) {
+                eprintln!("scrobbling stopped: {e}");
+            }
+        });
+        Some(Self {
+            tx,
+            music: config.scrobble_music,
+            podcast: config.scrobble_podcast,
+            youtube: config.scrobble_youtube,
+            current: None,
+        })
+    }
+
+    /// Sends "now playing" for a track that just started.
+    pub fn track_started(&mut self, track: Option<&Track>) {
+        self.current = track.and_then(|t| self.listen(t));
+        if let Some(listen) = &self.current {
+            self.tx.send(Command::NowPlaying(listen.clone())).ok();
+        }
+    }
+
+    /// Scrobbles the current track once enough of it was played.
+    pub fn progress(&mut self, position: i64) {
+        let Some(listen) = &self.current else {
+            return;
+        };
+        if listen.duration <= MIN_DURATION {
+            return;
+        }
+        if position >= (listen.duration / 2).min(MAX_WAIT) {
+            if let Some(listen) = self.current.take() {
+                self.tx.send(Command::Scrobble(listen)).ok();
+            }
+        }
+    }
+
+    fn listen(&self, track: &Track) -> Option<Listen> {
+        let enabled = match track.media_type {
+            Some(MediaType::Music) => self.music,
+            Some(MediaType::Podcast) => self.podcast,
+            Some(MediaType::Youtube) => self.youtube,
+            None => false,
+        };
+        if !enabled {
+            return None;
+        }
+        Some(Listen {
+            artist: track.artist()?.to_string(),
+            title: track.title()?.to_string(),
+            album: track
+                .album()
+                .filter(|album| !album.is_empty())
+                .map(ToString::to_string),
+            duration: i64::try_from(track.duration().as_secs()).unwrap_or(0),
+            timestamp: chrono::Utc::now().timestamp(),
+        })
+    }
+}
+
+fn worker(mut services: Vec<Box<dyn Service>>, rx: &Receiver<Command>) -> Result<()> {
+    let mut path = get_app_config_path()?;
+    path.push("scrobbles.db");
+    let db = Database::open(&path)?;
+    loop {
+        match rx.recv_timeout(Duration::from_secs(RETRY_SECS)) {
+            Ok(Command::NowPlaying(listen)) => {
+                // "now playing" is only worth sending right away, so it is not queued
+                for service in &mut services {
+                    if let Err(e) = service.now_playing(&listen) {
+                        eprintln!("{} now playing failed: {e}", service.name());
+                    }
+                }
+            }
+            Ok(Command::Scrobble(listen)) => {
+                for service in &services {
+                    db.push(service.name(), &listen)?;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        flush(&db, &mut services)?;
+    }
+}
+
+/// Submits the queued scrobbles, oldest first, until a service fails. Scrobbles the service
+/// rejects are dropped, so they don't hold up the rest of the queue.
+fn flush(db: &Database, services: &mut [Box<dyn Service>]) -> Result<()> {
+    for service in services {
+        let mut batch = BATCH;
+        loop {
+            let pending = db.pending(service.name(), batch)?;
+            if pending.is_empty() {
+                break;
+            }
+            let (ids, listens): (Vec<i64>, Vec<Listen>) = pending.into_iter().unzip();
+            match service.scrobble(&listens) {
+                Ok(()) => db.remove(&ids)?,
+                // send them one by one to find the rejected ones
+                Err(e) if e.is::<Rejected>() && listens.len() > 1 => batch = 1,
+                Err(e) if e.is::<Rejected>() => {
+                    eprintln!(
+                        "{} scrobble of {} - {} dropped: {e}",
+                        service.name(),
+                        listens[0].artist,
+                        listens[0].title
+                    );
+                    db.remove(&ids)?;
+                }
+                Err(e) => {
+                    eprintln!("{} scrobble failed, will retry: {e}", service.name());
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Asks for the last.fm account and stores the session key in the config. The password is
+/// read from stdin without echo and only sent to last.fm once.
+pub fn lastfm_login(config: &mut Settings) -> Result<()> {
+    let mut stdin = io::stdin().lock();
+    let mut username = config.lastfm_username.clone();
+    if username.is_empty() {
+        print!("last.fm username: ");
+        io::stdout().flush()?;
+        stdin.read_line(&mut username)?;
+        username = username.trim().to_string();
+    }
+    print!("last.fm password for {username}: ");
+    io::stdout().flush()?;
+    let password = read_hidden_line(&mut stdin)?;
+
+    config.lastfm_session_key = lastfm::LastFm::login(config, &username, &password)?;
+    config.lastfm_username = username;
+    config.save()?;
+    println!("logged in to last.fm, scrobbling starts with the next run");
+    Ok(())
+}
+
+/// Reads a line with the terminal in raw mode, so it is not shown while typing. Input that
+/// doesn't come from a terminal is read as it is.
+fn read_hidden_line(input: &mut impl BufRead) -> Result<String> {
+    let mut terminal = TerminalBridge::new().ok();
+    let raw = terminal
+        .as_mut()
+        .is_some_and(|t| t.enable_raw_mode().is_ok());
+    let line = read_until_enter(input);
+    if raw {
+        if let Some(t) = terminal.as_mut() {
+            t.disable_raw_mode().ok();
+        }
+        println!();
+    }
+    line
+}
+
+// Raw mode turns off the line editing of the terminal too, so backspace and ctrl+c are
+// handled here.
+fn read_until_enter(input: &mut impl BufRead) -> Result<String> {
+    let mut line = Vec::new();
+    for byte in input.by_ref().bytes() {
+        match byte? {
+            b'\r' | b'\n' => break,
+            3 => bail!("cancelled"),
+            // drop the whole last character, which may be several bytes long
+            8 | 127 => while line.pop().is_some_and(|b| b & 0xC0 == 0x80) {},
+            b => line.push(b),
+        }
+    }
+    Ok(String::from_utf8(line)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    /// Rejects every scrobble of a track called "bad", and all of them while offline.
+    struct Fake {
+        offline: bool,
+        accepted: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Service for Fake {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn now_playing(&mut self, _listen: &Listen) -> Result<()> {
+            Ok(())
+        }
+
+        fn scrobble(&mut self, listens: &[Listen]) -> Result<()> {
+            if self.offline {
+                bail!("offline");
+            }
+            if listens.iter().any(|l| l.title == "bad") {
+                return Err(Rejected("invalid track".to_string()).into());
+            }
+            let mut accepted = self.accepted.lock().unwrap();
+            accepted.extend(listens.iter().map(|l| l.title.clone()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_flush_drops_rejected() {
+        let db = Database::open(Path::new(":memory:")).unwrap();
+        for (timestamp, title) in [(1, "first"), (2, "bad"), (3, "last")] {
+            let listen = Listen {
+                artist: "Artist".to_string(),
+                title: title.to_string(),
+                album: None,
+                duration: 200,
+                timestamp,
+            };
+            db.push("fake", &listen).unwrap();
+        }
+        let accepted = Arc::new(Mutex::new(Vec::new()));
+        let fake = |offline| -> Vec<Box<dyn Service>> {
+            vec![Box::new(Fake {
+                offline,
+                accepted: Arc::clone(&accepted),
+            })]
+        };
+
+        // failures that may pass later keep everything queued
+        flush(&db, &mut fake(true)).unwrap();
+        assert_eq!(db.pending("fake", BATCH).unwrap().len(), 3);
+
+        flush(&db, &mut fake(false)).unwrap();
+        assert!(db.pending("fake", BATCH).unwrap().is_empty());
+        assert_eq!(*accepted.lock().unwrap(), ["first", "last"]);
+    }
+
+    #[test]
+    fn test_read_until_enter() {
+        let read = |input: &[u8]| read_until_enter(&mut &input[..]).map_err(|e| e.to_string());
+        assert_eq!(read(b"secret\r\nnext"), Ok("secret".to_string()));
+        assert_eq!(read(b"secrex\x7ft\n"), Ok("secret".to_string()));
+        assert_eq!(
+            read("p\u{e4}\u{e4}\x08s".as_bytes()),
+            Ok("p\u{e4}s".to_string())
+        );
+        assert_eq!(read(b"\x7f\x7fok"), Ok("ok".to_string()));
+        assert_eq!(read(b"sec\x03ret\n"), Err("cancelled".to_string()));
+    }
+}
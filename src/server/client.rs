@@ -0,0 +1,447 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::timer::{countdown, Alarm, Sleep};
+use super::{send, socket_path, Event, Queue, Request, State};
+use crate::config::Settings;
+use crate::hooks::Hook;
+use crate::player::{PlayerTrait, Playlist, Status};
+use anyhow::{bail, Context, Result};
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// The player as seen by the tui: commands go to the daemon, and the playlist is a copy that is
+/// kept in sync both ways.
+pub struct RemotePlayer {
+    stream: UnixStream,
+    events: Receiver<Event>,
+    connected: bool,
+    state: State,
+    // the queue as the daemon last knew it
+    synced: Option<Queue>,
+    // an edit the daemon has yet to answer
+    sent: Option<Queue>,
+    pub playlist: Playlist,
+}
+
+impl RemotePlayer {
+    /// Attaches to the daemon, starting one when none is running.
+    pub fn connect(config: &Settings) -> Result<Self> {
+        let path = socket_path()?;
+        let stream = if let Ok(stream) = UnixStream::connect(&path) {
+            stream
+        } else {
+            spawn_daemon()?;
+            let started = Instant::now();
+            loop {
+                sleep(Duration::from_millis(100));
+                match UnixStream::connect(&path) {
+                    Ok(stream) => break stream,
+                    Err(e) if started.elapsed() > Duration::from_secs(5) => {
+                        bail!("failed to start the playback daemon: {e}")
+                    }
+                    Err(_) => {}
+                }
+            }
+        };
+
+        let reader = stream.try_clone()?;
+        let (tx, events) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                if let Ok(event) = serde_json::from_str(&line) {
+                    if tx.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        let mut player = Self {
+            stream,
+            events,
+            connected: true,
+            state: State {
+                status: Status::Stopped,
+                volume: config.volume,
+                speed: config.speed,
+                gapless: config.gapless,
+                skip_silence: false,
+                position: 0,
+                duration: 0,
+                file: None,
+                title: None,
+                artist: None,
+                album: None,
+                sleep: None,
+                alarm: None,
+            },
+            synced: None,
+            sent: None,
+            // tracks of the saved playlist are reused when the queue of the daemon arrives
+            playlist: Playlist::new(config).unwrap_or_default(),
+        };
+
+        // the daemon greets every client with its state and queue
+        while player.synced.is_none() {
+            let event = player
+                .events
+                .recv_timeout(Duration::from_secs(5))
+                .context("no answer from the playback daemon")?;
+            player.apply(&event)?;
+        }
+        Ok(player)
+    }
+
+    /// Sends local edits of the playlist, then returns the next event of the daemon with its
+    /// state and queue already applied.
+    pub fn try_recv(&mut self) -> Result<Option<Event>> {
+        self.sync_queue();
+        match self.events.try_recv() {
+            Ok(event) => {
+                self.apply(&event)?;
+                Ok(Some(event))
+            }
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => {
+                if self.connected {
+                    self.connected = false;
+                    bail!("lost the connection to the playback daemon");
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn apply(&mut self, event: &Event) -> Result<()> {
+        match event {
+            Event::State(state) => {
+                self.playlist.set_status(state.status);
+                self.state = state.clone();
+            }
+            Event::Queue(queue) => {
+                if let Some(sent) = &self.sent {
+                    // the daemon has yet to get to the edit
+                    if queue.version == sent.version {
+                        return Ok(());
+                    }
+                    let accepted = queue.version == sent.version + 1 && queue.same_entries(sent);
+                    self.sent = None;
+                    self.synced = Some(queue.clone());
+                    // later edits stay, they are sent next
+                    if accepted {
+                        return Ok(());
+                    }
+                    queue.apply(&mut self.playlist)?;
+                    bail!("the queue was changed elsewhere in the meantime, please edit it again");
+                }
+                if !queue.matches(&self.playlist) {
+                    queue.apply(&mut self.playlist)?;
+                }
+                self.synced = Some(queue.clone());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Pushes the playlist to the daemon if it was changed here. One edit at a time, so each
+    /// is based on the queue the daemon answered with.
+    pub fn sync_queue(&mut self) {
+        let Some(synced) = &self.synced else {
+            return;
+        };
+        if self.sent.is_some() {
+            return;
+        }
+        let queue = Queue {
+            version: synced.version,
+            ..Queue::new(&self.playlist)
+        };
+        if queue.same_entries(synced) {
+            return;
+        }
+        self.request(&Request::SetQueue(queue.clone()));
+        self.sent = Some(queue);
+    }
+
+    fn request(&self, request: &Request) {
+        // a lost connection is reported by `try_recv`
+        send(&self.stream, request).ok();
+    }
+
+    pub fn start_play(&mut self) {
+        self.sync_queue();
+        self.request(&Request::Play);
+        self.playlist.set_status(Status::Running);
+    }
+
+    /// Drops the current track and plays the front of the queue.
+    pub fn play_now(&mut self) {
+        self.stop();
+        self.start_play();
+    }
+
+    pub fn skip(&mut self) {
+        self.sync_queue();
+        self.request(&Request::Skip);
+    }
+
+    pub fn previous(&mut self) {
+        self.sync_queue();
+        self.request(&Request::Previous);
+    }
+
+    pub fn toggle_gapless(&mut self) -> bool {
+        self.request(&Request::ToggleGapless);
+        self.state.gapless = !self.state.gapless;
+        self.state.gapless
+    }
+
+    pub fn set_skip_silence(&mut self, skip_silence: bool) {
+        self.request(&Request::SetSkipSilence(skip_silence));
+        self.state.skip_silence = skip_silence;
+    }
+
+    pub fn skip_silence(&self) -> bool {
+        self.state.skip_silence
+    }
+
+    pub fn set_sleep(&mut self, sleep: Option<Sleep>) {
+        self.request(&Request::SetSleep(sleep));
+        self.state.sleep = sleep;
+    }
+
+    pub fn set_alarm(&mut self, alarm: Option<Alarm>) {
+        self.request(&Request::SetAlarm(alarm.clone()));
+        self.state.alarm = alarm;
+    }
+
+    /// Leaves running the command of `hook` to the daemon, which reports failures as errors.
+    pub fn run_hook(&self, hook: Hook, info: &[(&str, String)]) {
+        let info = info.iter().map(|(k, v)| ((*k).to_string(), v.clone()));
+        self.request(&Request::RunHook(hook, info.collect()));
+    }
+
+    /// What the sleep timer and alarm have left, for the progress bar.
+    pub fn countdown(&self) -> Option<String> {
+        countdown(self.state.sleep.as_ref(), self.state.alarm.as_ref())
+    }
+}
+
+// The getters answer from the last state of the daemon, updated right away with the expected
+// result of a command, so the ui doesn't wait for the round trip.
+impl PlayerTrait for RemotePlayer {
+    fn add_and_play(&mut self, current_track: &str) {
+        self.request(&Request::PlayUri(current_track.to_string()));
+    }
+    fn volume(&self) -> i32 {
+        self.state.volume
+    }
+    fn volume_up(&mut self) {
+        self.request(&Request::VolumeUp);
+        self.state.volume = (self.state.volume + 5).min(100);
+    }
+    fn volume_down(&mut self) {
+        self.request(&Request::VolumeDown);
+        self.state.volume = (self.state.volume - 5).max(0);
+    }
+    fn set_volume(&mut self, volume: i32) {
+        self.request(&Request::SetVolume(volume));
+        self.state.volume = volume.clamp(0, 100);
+    }
+    fn pause(&mut self) {
+        self.request(&Request::Pause);
+        self.playlist.set_status(Status::Paused);
+    }
+    fn resume(&mut self) {
+        self.request(&Request::Resume);
+        self.playlist.set_status(Status::Running);
+    }
+    fn is_paused(&self) -> bool {
+        self.playlist.is_paused()
+    }
+    fn seek(&mut self, secs: i64) -> Result<()> {
+        send(&self.stream, &Request::Seek(secs))
+    }
+    fn seek_to(&mut self, last_pos: Duration) {
+        self.request(&Request::SeekTo(last_pos.as_secs()));
+    }
+
+    fn set_speed(&mut self, speed: i32) {
+        self.request(&Request::SetSpeed(speed));
+        self.state.speed = speed;
+    }
+
+    fn speed_up(&mut self) {
+        self.request(&Request::SpeedUp);
+        self.state.speed = (self.state.speed + 1).min(30);
+    }
+
+    fn speed_down(&mut self) {
+        self.request(&Request::SpeedDown);
+        self.state.speed = (self.state.speed - 1).max(1);
+    }
+
+    fn speed(&self) -> i32 {
+        self.state.speed
+    }
+
+    fn stop(&mut self) {
+        self.sync_queue();
+        self.request(&Request::Stop);
+        self.playlist.set_status(Status::Stopped);
+        self.playlist.set_next_track(None);
+        self.playlist.set_current_track(None);
+    }
+}
+
+fn spawn_daemon() -> Result<()> {
+    Command::new(std::env::current_exe()?)
+        .arg("daemon")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // a process group of its own, so closing the terminal doesn't hang it up
+        .process_group(0)
+        .spawn()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invidious::{video_url, YoutubeStream};
+    use crate::track::Track;
+    use std::collections::VecDeque;
+    use std::io::Read;
+    use std::sync::mpsc::Sender;
+
+    fn state(status: Status) -> State {
+        State {
+            status,
+            volume: 50,
+            speed: 10,
+            gapless: true,
+            skip_silence: false,
+            position: 0,
+            duration: 0,
+            file: None,
+            title: None,
+            artist: None,
+            album: None,
+            sleep: None,
+            alarm: None,
+        }
+    }
+
+    fn track(video_id: &str) -> Track {
+        Track::from_youtube(&YoutubeStream {
+            video_id: video_id.to_string(),
+            title: video_id.to_string(),
+            author: String::new(),
+            length_seconds: 60,
+            thumbnail: None,
+            url: String::new(),
+        })
+    }
+
+    /// A player attached to a fake daemon, with these tracks queued.
+    fn attach(video_ids: &[&str]) -> (RemotePlayer, UnixStream, Sender<Event>) {
+        let (stream, daemon) = UnixStream::pair().unwrap();
+        let (tx, events) = mpsc::channel();
+        let mut playlist = Playlist::default();
+        let tracks: VecDeque<Track> = video_ids.iter().map(|id| track(id)).collect();
+        playlist.set_queue(None, tracks, playlist.loop_mode());
+        let player = RemotePlayer {
+            stream,
+            events,
+            connected: true,
+            state: state(Status::Running),
+            synced: Some(Queue::new(&playlist)),
+            sent: None,
+            playlist,
+        };
+        (player, daemon, tx)
+    }
+
+    fn sent(player: RemotePlayer, mut daemon: UnixStream) -> Vec<Request> {
+        drop(player);
+        let mut sent = String::new();
+        daemon.read_to_string(&mut sent).unwrap();
+        sent.lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_daemon_stop_stays_stopped() {
+        let (mut player, daemon, tx) = attach(&[]);
+
+        // a sleep timer, mpris or the web page stopped the daemon
+        tx.send(Event::State(state(Status::Stopped))).unwrap();
+        for _ in 0..100 {
+            player.try_recv().unwrap();
+        }
+        assert!(player.playlist.is_stopped());
+        assert_eq!(sent(player, daemon), vec![]);
+    }
+
+    #[test]
+    fn test_queue_edits() {
+        let files = |player: &RemotePlayer| -> Vec<String> { Queue::new(&player.playlist).tracks };
+
+        // the daemon takes the edit
+        let (mut player, daemon, tx) = attach(&["a", "b"]);
+        player.playlist.swap_down(0);
+        player.try_recv().unwrap();
+        let mut queue = Queue::new(&player.playlist);
+        queue.version = 1;
+        tx.send(Event::Queue(queue)).unwrap();
+        player.try_recv().unwrap();
+        assert_eq!(files(&player), vec![video_url("b"), video_url("a")]);
+        let [Request::SetQueue(edit)] = &sent(player, daemon)[..] else {
+            panic!("expected one edit");
+        };
+        assert_eq!(edit.version, 0);
+
+        // another client got there first, its queue wins
+        let (mut player, daemon, tx) = attach(&["a", "b"]);
+        player.playlist.swap_down(0);
+        player.try_recv().unwrap();
+        let mut queue = Queue::new(&player.playlist);
+        queue.tracks = vec![video_url("b")];
+        queue.version = 1;
+        tx.send(Event::Queue(queue)).unwrap();
+        assert!(player.try_recv().is_err());
+        assert_eq!(files(&player), vec![video_url("b")]);
+        // and nothing is sent until the next edit
+        player.try_recv().unwrap();
+        assert_eq!(sent(player, daemon).len(), 1);
+    }
+}
@@ -0,0 +1,194 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::{send, socket_path, Event, Queue, Request, State};
+use crate::cli::{QueueAction, Remote};
+use crate::track::Track;
+use crate::utils::{filetype_supported, is_playlist, playlist_get_vec};
+use anyhow::{anyhow, bail, Context, Result};
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+use walkdir::WalkDir;
+
+/// Runs a command of the command line against the running daemon.
+pub fn control(remote: &Remote) -> Result<()> {
+    let stream = UnixStream::connect(socket_path()?).context("termusic is not running")?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let (state, queue) = greeting(&stream)?;
+
+    let request = match remote {
+        Remote::Play => Request::Play,
+        Remote::Pause => Request::Pause,
+        Remote::Toggle => Request::TogglePause,
+        Remote::Next => Request::Skip,
+        Remote::Prev => Request::Previous,
+        Remote::Seek { position } => match parse_change(position)? {
+            Change::By(offset) => Request::Seek(offset),
+            Change::To(position) => Request::SeekTo(u64::try_from(position)?),
+        },
+        Remote::Volume { volume } => match parse_change(volume)? {
+            Change::By(delta) => Request::SetVolume(clamp_volume(i64::from(state.volume) + delta)),
+            Change::To(volume) => Request::SetVolume(clamp_volume(volume)),
+        },
+        Remote::Add { paths } => Request::Add(collect_files(paths)?),
+        Remote::Status { json } => {
+            print_status(&state, *json)?;
+            return Ok(());
+        }
+        Remote::Queue {
+            action: QueueAction::List { json },
+        } => {
+            print_queue(&queue, *json)?;
+            return Ok(());
+        }
+    };
+    send(&stream, &request)
+}
+
+// the daemon greets every client with its state and queue
+pub(super) fn greeting(stream: &UnixStream) -> Result<(State, Queue)> {
+    let mut state = None;
+    for line in BufReader::new(stream).lines() {
+        match serde_json::from_str(&line?)? {
+            Event::State(s) => state = Some(s),
+            Event::Queue(queue) => {
+                let state = state.ok_or_else(|| anyhow!("no state received from termusic"))?;
+                return Ok((state, queue));
+            }
+            _ => {}
+        }
+    }
+    bail!("termusic closed the connection")
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Change {
+    By(i64),
+    To(i64),
+}
+
+/// `+10` and `-10` are relative, `10` is absolute.
+fn parse_change(value: &str) -> Result<Change> {
+    let number = |v: &str| {
+        v.parse::<i64>()
+            .with_context(|| format!("invalid number: {value}"))
+    };
+    if let Some(v) = value.strip_prefix('+') {
+        return Ok(Change::By(number(v)?));
+    }
+    if value.starts_with('-') {
+        return Ok(Change::By(number(value)?));
+    }
+    Ok(Change::To(number(value)?))
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn clamp_volume(volume: i64) -> i32 {
+    volume.clamp(0, 100) as i32
+}
+
+// the daemon runs elsewhere, so paths are made absolute and directories and playlists are
+// expanded here
+pub(super) fn collect_files(paths: &[String]) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = Path::new(path)
+            .canonicalize()
+            .with_context(|| format!("no such file: {path}"))?;
+        if path.is_dir() {
+            let mut entries: Vec<String> = WalkDir::new(&path)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_file())
+                .map(|e| e.path().to_string_lossy().to_string())
+                .filter(|f| filetype_supported(f))
+                .collect();
+            entries.sort();
+            files.extend(entries);
+            continue;
+        }
+        let path = path.to_string_lossy().to_string();
+        if is_playlist(&path) {
+            files.extend(playlist_get_vec(&path)?);
+            continue;
+        }
+        files.push(path);
+    }
+    Ok(files)
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn print_status(state: &State, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(state)?);
+        return Ok(());
+    }
+    let Some(file) = &state.file else {
+        println!("{}", state.status);
+        return Ok(());
+    };
+    let name = match (&state.artist, &state.title) {
+        (Some(artist), Some(title)) => format!("{artist} - {title}"),
+        (None, Some(title)) => title.clone(),
+        _ => file.clone(),
+    };
+    let time =
+        |secs: i64| Track::duration_formatted_short(&Duration::from_secs(secs.max(0) as u64));
+    println!(
+        "{}: {name} [{}/{}] volume {}",
+        state.status,
+        time(state.position),
+        time(state.duration),
+        state.volume
+    );
+    Ok(())
+}
+
+fn print_queue(queue: &Queue, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(queue)?);
+        return Ok(());
+    }
+    if let Some(current) = &queue.current {
+        println!("> {current}");
+    }
+    for (idx, file) in queue.tracks.iter().enumerate() {
+        println!("{:>3} {file}", idx + 1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_change() {
+        assert_eq!(parse_change("+10").unwrap(), Change::By(10));
+        assert_eq!(parse_change("-5").unwrap(), Change::By(-5));
+        assert_eq!(parse_change("50").unwrap(), Change::To(50));
+        assert!(parse_change("+x").is_err());
+    }
+}
@@ -0,0 +1,901 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#[cfg(feature = "mpris")]
+use super::mpris::Mpris;
+#[cfg(feature = "notify")]
+use super::notify::Notifier;
+use super::timer::{self, Alarm, Sleep, SleepAction, SleepUntil};
+use super::{send, socket_path, Event, Queue, Request, State};
+use crate::config::{LastPosition, PlayerSession, Settings};
+use crate::hooks::{self, Hook, Hooks};
+use crate::player::{GeneralPlayer, Loop, PlayerMsg, PlayerTrait, Playlist, Status};
+use crate::podcast::db::Database as DBPod;
+use crate::scrobble::Scrobbler;
+use crate::sqlite::DataBase;
+use crate::track::{MediaType, Track};
+use crate::utils::{get_app_config_path, playlist_get_vec};
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+enum Incoming {
+    Connected(usize, UnixStream),
+    Request(usize, Request),
+    Disconnected(usize),
+}
+
+/// Runs the daemon in the foreground until it is asked to quit.
+pub fn run(config: &Settings) -> Result<()> {
+    let path = socket_path()?;
+    if UnixStream::connect(&path).is_ok() {
+        bail!("a daemon is already listening on {}", path.display());
+    }
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || accept(&listener, &tx));
+    #[cfg(feature = "web")]
+    super::web::spawn(config);
+
+    let mut daemon = Daemon::new(config)?;
+    daemon.restore_session();
+    daemon.run(&rx);
+    daemon.shutdown();
+    std::fs::remove_file(&path).ok();
+    Ok(())
+}
+
+/// Asks a running daemon to quit.
+pub fn stop() -> Result<()> {
+    let stream = UnixStream::connect(socket_path()?).context("no daemon is running")?;
+    send(&stream, &Request::Quit)
+}
+
+fn accept(listener: &UnixListener, tx: &Sender<Incoming>) {
+    for (id, stream) in listener.incoming().flatten().enumerate() {
+        let Ok(writer) = stream.try_clone() else {
+            continue;
+        };
+        // a client that stops reading must not block playback
+        writer.set_write_timeout(Some(Duration::from_secs(1))).ok();
+        if tx.send(Incoming::Connected(id, writer)).is_err() {
+            return;
+        }
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                match serde_json::from_str(&line) {
+                    Ok(request) => {
+                        if tx.send(Incoming::Request(id, request)).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => eprintln!("invalid request {line}: {e}"),
+                }
+            }
+            tx.send(Incoming::Disconnected(id)).ok();
+        });
+    }
+}
+
+struct Daemon {
+    player: GeneralPlayer,
+    config: Settings,
+    db: DataBase,
+    db_podcast: DBPod,
+    clients: Vec<(usize, UnixStream)>,
+    /// The queue last sent to clients.
+    queue: Queue,
+    position: i64,
+    duration: i64,
+    /// The status last broadcast, to tell when hooks are due.
+    status: Status,
+    sleep: Option<Sleep>,
+    alarm: Option<Alarm>,
+    /// The volume to go back to while fading in or out.
+    fade_volume: Option<i32>,
+    /// When the alarm went off, while its fade in lasts.
+    fade_in: Option<Instant>,
+    /// The track and position the last session stopped at, until play is pressed.
+    resume: Option<(String, u64)>,
+    quit: bool,
+    hooks: Hooks,
+    scrobbler: Option<Scrobbler>,
+    /// None without a session bus.
+    #[cfg(feature = "mpris")]
+    mpris: Option<Mpris>,
+    #[cfg(feature = "notify")]
+    notifier: Notifier,
+}
+
+impl Daemon {
+    fn new(config: &Settings) -> Result<Self> {
+        let db_podcast = DBPod::connect(&get_app_config_path()?)?;
+        let player = GeneralPlayer::new(config);
+        let queue = Queue::new(&player.playlist);
+        Ok(Self {
+            player,
+            config: config.clone(),
+            db: DataBase::new(config),
+            db_podcast,
+            clients: Vec::new(),
+            queue,
+            position: 0,
+            duration: 0,
+            status: Status::Stopped,
+            sleep: None,
+            alarm: None,
+            fade_volume: None,
+            fade_in: None,
+            resume: None,
+            quit: false,
+            hooks: Hooks::new(config),
+            scrobbler: Scrobbler::from_config(config),
+            #[cfg(feature = "mpris")]
+            mpris: Mpris::new(config)
+                .map_err(|e| eprintln!("running without mpris: {e}"))
+                .ok(),
+            #[cfg(feature = "notify")]
+            notifier: Notifier::new(config),
+        })
+    }
+
+    fn run(&mut self, rx: &Receiver<Incoming>) {
+        while !self.quit {
+            while let Ok(msg) = self.player.message_rx.try_recv() {
+                self.handle_player_msg(msg);
+            }
+            #[cfg(feature = "mpris")]
+            while let Some(request) = self.mpris.as_ref().and_then(Mpris::try_recv) {
+                self.handle_request(request);
+            }
+            #[cfg(feature = "notify")]
+            while let Some(request) = self.notifier.try_recv() {
+                self.handle_request(request);
+            }
+            while let Some(e) = self.hooks.try_recv_error() {
+                self.broadcast(&Event::Error(e));
+            }
+            self.tick_timers();
+            match rx.recv_timeout(Duration::from_millis(20)) {
+                Ok(Incoming::Connected(id, stream)) => {
+                    self.send_status(&stream);
+                    self.clients.push((id, stream));
+                }
+                Ok(Incoming::Request(id, Request::Status)) => {
+                    if let Some(stream) = self
+                        .clients
+                        .iter()
+                        .find(|(i, _)| *i == id)
+                        .and_then(|(_, stream)| stream.try_clone().ok())
+                    {
+                        self.send_status(&stream);
+                    }
+                }
+                Ok(Incoming::Request(_, request)) => self.handle_request(request),
+                Ok(Incoming::Disconnected(id)) => self.clients.retain(|(i, _)| *i != id),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+
+    fn shutdown(&mut self) {
+        self.restore_volume();
+        self.save_last_position();
+        if let Err(e) = self.player.playlist.save() {
+            eprintln!("error saving playlist: {e}");
+        }
+        self.save_session();
+        self.player.stop();
+    }
+
+    /// Picks up the queue, volume, speed and track of the last session.
+    fn restore_session(&mut self) {
+        let session = match PlayerSession::load() {
+            Ok(Some(session)) => session,
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("error loading session: {e}");
+                return;
+            }
+        };
+        if let Some(volume) = session.volume {
+            self.player.set_volume(volume);
+        }
+        if let Some(speed) = session.speed {
+            self.player.set_speed(speed);
+        }
+
+        // `playlist.log` starts with the current track, which goes back to the queue when
+        // nothing was playing
+        let playlist = &mut self.player.playlist;
+        let mut current = playlist.current_track().cloned();
+        let mut tracks = playlist.tracks().clone();
+        if session.current.is_none() {
+            if let Some(track) = current.take() {
+                tracks.push_front(track);
+            }
+        }
+        let loop_mode = session.loop_mode.unwrap_or_else(|| playlist.loop_mode());
+        playlist.set_queue(current, tracks, loop_mode);
+
+        let (Some(file), Some(current)) = (
+            session.current,
+            self.player.playlist.current_track().and_then(Track::file),
+        ) else {
+            return;
+        };
+        if file != current {
+            return;
+        }
+        let position = u64::try_from(session.position).unwrap_or(0);
+        if !self.config.resume_playing_on_start {
+            self.resume = Some((file, position));
+            return;
+        }
+        self.player.start_play();
+        self.player.seek_to(Duration::from_secs(position));
+        self.position = session.position;
+        if session.status == Status::Paused {
+            self.player.pause();
+        }
+    }
+
+    /// Seeks to where the last session left off, the first time its track is played.
+    fn resume_session(&mut self) {
+        let Some((file, position)) = self.resume.take() else {
+            return;
+        };
+        if self.player.playlist.current_track().and_then(Track::file) == Some(file.as_str()) {
+            self.player.seek_to(Duration::from_secs(position));
+        }
+    }
+
+    fn save_session(&self) {
+        let status = self.player.playlist.status();
+        let session = PlayerSession {
+            current: self
+                .player
+                .playlist
+                .current_track()
+                .filter(|_| status != Status::Stopped)
+                .and_then(Track::file)
+                .map(ToString::to_string),
+            status,
+            position: self.position,
+            volume: Some(self.fade_volume.unwrap_or_else(|| self.player.volume())),
+            speed: Some(self.player.speed()),
+            loop_mode: Some(self.player.playlist.loop_mode()),
+        };
+        if let Err(e) = session.save() {
+            eprintln!("error saving session: {e}");
+        }
+    }
+
+    fn handle_player_msg(&mut self, msg: PlayerMsg) {
+        match msg {
+            PlayerMsg::Eos => {
+                if self.player.playlist.is_empty() {
+                    if let Some(Sleep {
+                        until: SleepUntil::Tracks(_),
+                        ..
+                    }) = self.sleep
+                    {
+                        // the queue ran out before the sleep timer
+                        self.sleep = None;
+                        self.restore_volume();
+                    }
+                    self.stop();
+                    return;
+                }
+                self.player.playlist.handle_current_track();
+                self.player.start_play();
+                self.restore_last_position();
+                self.broadcast_state();
+            }
+            PlayerMsg::AboutToFinish => {
+                if self.config.gapless {
+                    self.player.enqueue_next();
+                }
+            }
+            PlayerMsg::CurrentTrackUpdated => {
+                self.player.playlist.save().ok();
+                if self.track_finished() {
+                    return;
+                }
+                #[cfg(feature = "mpris")]
+                if let Some(mpris) = &mut self.mpris {
+                    mpris.set_track(self.player.playlist.current_track());
+                }
+                if let Some(scrobbler) = &mut self.scrobbler {
+                    scrobbler.track_started(self.player.playlist.current_track());
+                }
+                self.apply_track_settings();
+                if let Some(track) = self.player.playlist.current_track() {
+                    self.hooks.run(Hook::TrackChange, &hooks::track_info(track));
+                    #[cfg(feature = "notify")]
+                    self.notifier.track_changed(track);
+                }
+                self.broadcast_queue();
+                self.broadcast_state();
+                self.broadcast(&Event::TrackChanged);
+            }
+            PlayerMsg::Progress(position, duration) => {
+                // now and then, for a daemon that gets killed
+                let save = position % 10 == 0 && position != self.position;
+                self.position = position;
+                self.duration = duration;
+                self.about_to_finish();
+                if let Some(scrobbler) = &mut self.scrobbler {
+                    scrobbler.progress(position);
+                }
+                if save {
+                    self.save_session();
+                }
+                #[cfg(feature = "mpris")]
+                if let Some(mpris) = &mut self.mpris {
+                    mpris.set_playback(self.player.playlist.status(), position);
+                    mpris.set_position(position);
+                }
+                self.broadcast(&Event::Progress { position, duration });
+            }
+            PlayerMsg::YoutubeStreamReady(stream) => {
+                if self.player.playlist.is_stopped()
+                    || !self.player.playlist.set_youtube_stream(&stream)
+                {
+                    return;
+                }
+                self.player.start_play();
+                self.restore_last_position();
+            }
+            PlayerMsg::YoutubeStreamFail(video_id, e) => {
+                if self.player.playlist.stale_youtube_stream() != Some(video_id) {
+                    return;
+                }
+                self.stop();
+                self.broadcast(&Event::Error(format!("youtube stream error: {e}")));
+            }
+            #[cfg(not(any(feature = "mpv", feature = "gst")))]
+            PlayerMsg::Duration(duration) => {
+                self.player.player.total_duration = Some(Duration::from_secs(duration));
+            }
+            #[cfg(not(any(feature = "mpv", feature = "gst")))]
+            PlayerMsg::DurationNext(duration) => {
+                self.player
+                    .playlist
+                    .set_next_track_duration(Duration::from_secs(duration));
+            }
+            #[cfg(not(any(feature = "mpv", feature = "gst")))]
+            PlayerMsg::CacheStart(url) => self.broadcast(&Event::CacheStart(url)),
+            #[cfg(not(any(feature = "mpv", feature = "gst")))]
+            PlayerMsg::CacheEnd(url) => self.broadcast(&Event::CacheEnd(url)),
+        }
+    }
+
+    fn handle_request(&mut self, request: Request) {
+        match request {
+            Request::Play => {
+                if self.player.playlist.is_stopped() {
+                    self.player.start_play();
+                    self.restore_last_position();
+                    self.resume_session();
+                } else if self.player.is_paused() {
+                    self.player.resume();
+                }
+            }
+            Request::Pause => self.player.pause(),
+            Request::Resume => self.player.resume(),
+            Request::TogglePause => {
+                if self.player.playlist.is_stopped() {
+                    self.handle_request(Request::Play);
+                } else if self.player.is_paused() {
+                    self.player.resume();
+                } else if self.player.playlist.current_track().is_some() {
+                    self.player.pause();
+                }
+            }
+            Request::Stop => {
+                self.stop();
+                return;
+            }
+            Request::Skip => {
+                self.save_last_position();
+                self.player.skip();
+            }
+            Request::Previous => {
+                if let Loop::Single | Loop::Queue = self.player.playlist.loop_mode() {
+                    return;
+                }
+                if self.player.playlist.is_empty() {
+                    self.stop();
+                    return;
+                }
+                self.save_last_position();
+                self.player.playlist.handle_previous();
+                self.player.skip();
+            }
+            Request::Seek(offset) => self.seek(offset),
+            Request::SeekTo(position) => self.seek_to(position),
+            Request::SetVolume(volume) => {
+                self.end_fade();
+                self.player.set_volume(volume);
+            }
+            Request::VolumeUp => {
+                self.end_fade();
+                self.player.volume_up();
+            }
+            Request::VolumeDown => {
+                self.end_fade();
+                self.player.volume_down();
+            }
+            request @ (Request::SetSpeed(_) | Request::SpeedUp | Request::SpeedDown) => {
+                self.change_speed(&request);
+            }
+            Request::SetSkipSilence(skip_silence) => self.player.set_skip_silence(skip_silence),
+            Request::ToggleGapless => self.config.gapless = self.player.toggle_gapless(),
+            Request::SetLoopMode(loop_mode) => {
+                self.set_loop_mode(loop_mode);
+                return;
+            }
+            Request::Shuffle => {
+                self.player.playlist.shuffle();
+                self.player.playlist.save().ok();
+                self.broadcast_queue();
+                return;
+            }
+            Request::PlayUri(uri) => self.player.add_and_play(&uri),
+            Request::Add(files) => {
+                self.add(&files);
+                return;
+            }
+            Request::SetQueue(queue) => {
+                self.set_queue(queue);
+                return;
+            }
+            Request::SetSleep(sleep) => {
+                self.sleep = sleep;
+                if self.fade_in.is_none() {
+                    self.restore_volume();
+                }
+            }
+            Request::SetAlarm(alarm) => self.alarm = alarm,
+            Request::RunHook(hook, info) => {
+                self.hooks.run(hook, &info);
+                return;
+            }
+            // answered in `run`, where the client is known
+            Request::Status => return,
+            Request::Quit => {
+                self.quit = true;
+                return;
+            }
+        }
+        self.broadcast_state();
+    }
+
+    fn add(&mut self, files: &[String]) {
+        let files = files.iter().map(String::as_str).collect();
+        if let Err(e) = self.player.playlist.add_playlist(files) {
+            eprintln!("error adding to queue: {e}");
+        }
+        self.player.playlist.save().ok();
+        self.broadcast_queue();
+    }
+
+    fn set_queue(&mut self, queue: Queue) {
+        let current = self.queue();
+        // an edit of an older queue would undo what happened since, the sender gets the queue
+        // back to start over
+        if queue.version == current.version {
+            let queue = Queue {
+                current: current.current,
+                ..queue
+            };
+            if let Err(e) = queue.apply(&mut self.player.playlist) {
+                eprintln!("error loading queue: {e}");
+            }
+            self.player.playlist.save().ok();
+        }
+        self.broadcast_queue();
+    }
+
+    fn set_loop_mode(&mut self, loop_mode: Loop) {
+        // cycling keeps the current track in the right place of the queue
+        while self.player.playlist.loop_mode() != loop_mode {
+            self.player.playlist.cycle_loop_mode();
+        }
+        self.player.playlist.save().ok();
+        self.broadcast_queue();
+    }
+
+    fn stop(&mut self) {
+        self.save_last_position();
+        self.player.stop();
+        self.position = 0;
+        #[cfg(feature = "mpris")]
+        if let Some(mpris) = &mut self.mpris {
+            mpris.set_track(None);
+        }
+        if let Some(scrobbler) = &mut self.scrobbler {
+            scrobbler.track_started(None);
+        }
+        self.broadcast_queue();
+        self.broadcast_state();
+        self.broadcast(&Event::Progress {
+            position: 0,
+            duration: 60,
+        });
+        self.broadcast(&Event::TrackChanged);
+    }
+
+    fn seek(&mut self, offset: i64) {
+        #[cfg(feature = "mpris")]
+        if let Some(mpris) = &mut self.mpris {
+            mpris.seeking();
+        }
+        // FIXME: dirty fix for seeking when paused with symphonia,basically set it to play
+        // in rusty sink code, and seek, and then set it back to pause.
+        #[cfg(not(any(feature = "mpv", feature = "gst")))]
+        let (paused, volume) = (self.player.is_paused(), self.player.volume());
+        #[cfg(not(any(feature = "mpv", feature = "gst")))]
+        if paused {
+            self.player.set_volume(0);
+        }
+
+        self.player.seek(offset).ok();
+
+        #[cfg(not(any(feature = "mpv", feature = "gst")))]
+        if paused {
+            std::thread::sleep(Duration::from_millis(50));
+            self.player.pause();
+            self.player.set_volume(volume);
+        }
+    }
+
+    fn seek_to(&mut self, position: u64) {
+        #[cfg(feature = "mpris")]
+        if let Some(mpris) = &mut self.mpris {
+            mpris.seeking();
+        }
+        self.player.seek_to(Duration::from_secs(position));
+    }
+
+    // About to finish signal is a simulation of gstreamer, and used for gapless
+    fn about_to_finish(&mut self) {
+        #[cfg(any(not(feature = "gst"), feature = "mpv"))]
+        if !self.player.playlist.is_empty()
+            && !self.player.playlist.has_next_track()
+            && self.duration > 0
+            && self.position * 2 >= self.duration
+            && self.duration - self.position < 2
+            && self.config.gapless
+        {
+            self.player.message_tx.send(PlayerMsg::AboutToFinish).ok();
+        }
+    }
+
+    fn state(&self) -> State {
+        let track = self.player.playlist.current_track();
+        let tag = |get: fn(&Track) -> Option<&str>| track.and_then(get).map(ToString::to_string);
+        State {
+            status: self.player.playlist.status(),
+            volume: self.fade_volume.unwrap_or_else(|| self.player.volume()),
+            speed: self.player.speed(),
+            gapless: self.config.gapless,
+            skip_silence: self.player.skip_silence(),
+            position: self.position,
+            duration: self.duration,
+            file: tag(Track::file),
+            title: tag(Track::title),
+            artist: tag(Track::artist),
+            album: tag(Track::album),
+            sleep: self.sleep,
+            alarm: self.alarm.clone(),
+        }
+    }
+
+    /// Goes off with the alarm, fades in and out, and acts when the sleep timer runs out.
+    fn tick_timers(&mut self) {
+        let now = Local::now().timestamp();
+        if self.alarm.as_ref().is_some_and(|alarm| alarm.time <= now) {
+            self.wake_up();
+        }
+
+        if let Some(started) = self.fade_in {
+            let elapsed = i64::try_from(started.elapsed().as_secs()).unwrap_or(i64::MAX);
+            if elapsed >= timer::FADE_IN {
+                self.fade_in = None;
+                self.restore_volume();
+            } else {
+                let volume = self.fade_volume.unwrap_or_else(|| self.player.volume());
+                self.fade_to(timer::fade_in(volume, elapsed));
+            }
+        }
+
+        let Some(sleep) = self.sleep else {
+            return;
+        };
+        let left = match sleep.until {
+            SleepUntil::Time(time) => time - now,
+            // the last track fades out towards its end
+            SleepUntil::Tracks(1)
+                if self.duration > 0 && self.player.playlist.status() == Status::Running =>
+            {
+                (self.duration - self.position).max(1)
+            }
+            SleepUntil::Tracks(_) => return,
+        };
+        if left <= 0 {
+            self.fall_asleep();
+        } else if left < timer::FADE_OUT && self.fade_in.is_none() {
+            let volume = self.fade_volume.unwrap_or_else(|| self.player.volume());
+            self.fade_to(timer::fade_out(volume, left));
+        }
+    }
+
+    /// Counts a track change against the sleep timer, true when it stopped playback.
+    fn track_finished(&mut self) -> bool {
+        let Some(Sleep {
+            until: SleepUntil::Tracks(tracks),
+            ..
+        }) = &mut self.sleep
+        else {
+            return false;
+        };
+        // starting to play isn't the end of a track
+        if self.status != Status::Running {
+            return false;
+        }
+        *tracks -= 1;
+        if *tracks > 0 {
+            self.broadcast_state();
+            return false;
+        }
+        self.fall_asleep();
+        self.player.playlist.is_stopped()
+    }
+
+    fn fall_asleep(&mut self) {
+        let Some(sleep) = self.sleep.take() else {
+            return;
+        };
+        match sleep.action {
+            SleepAction::Stop => self.stop(),
+            SleepAction::Pause => {
+                if self.player.playlist.status() == Status::Running {
+                    self.player.pause();
+                }
+            }
+        }
+        self.restore_volume();
+        self.broadcast_state();
+    }
+
+    fn wake_up(&mut self) {
+        let Some(alarm) = self.alarm.take() else {
+            return;
+        };
+        if let Some(path) = &alarm.playlist {
+            match playlist_get_vec(path).and_then(|files| Playlist::load_from(&files)) {
+                Ok(tracks) => {
+                    if !self.player.playlist.is_stopped() {
+                        self.stop();
+                    }
+                    let loop_mode = self.player.playlist.loop_mode();
+                    self.player.playlist.set_queue(None, tracks, loop_mode);
+                    self.player.playlist.save().ok();
+                    self.broadcast_queue();
+                }
+                Err(e) => eprintln!("error loading the playlist of the alarm: {e}"),
+            }
+        } else if self.player.playlist.status() == Status::Running {
+            self.broadcast_state();
+            return;
+        }
+
+        self.fade_to(0);
+        self.fade_in = Some(Instant::now());
+        self.handle_request(Request::Play);
+    }
+
+    /// Sets the volume of a fade, keeping the one to go back to.
+    fn fade_to(&mut self, volume: i32) {
+        let current = self.player.volume();
+        self.fade_volume.get_or_insert(current);
+        if volume != current {
+            self.player.set_volume(volume);
+        }
+    }
+
+    /// Keeps a volume chosen while fading.
+    fn end_fade(&mut self) {
+        self.fade_volume = None;
+        self.fade_in = None;
+    }
+
+    fn restore_volume(&mut self) {
+        if let Some(volume) = self.fade_volume.take() {
+            self.player.set_volume(volume);
+        }
+    }
+
+    fn send_status(&mut self, stream: &UnixStream) {
+        send(stream, &Event::State(self.state())).ok();
+        send(stream, &Event::Queue(self.queue())).ok();
+    }
+
+    /// The queue as it is now, with a new version if it changed since it was last sent.
+    fn queue(&mut self) -> Queue {
+        let mut queue = Queue::new(&self.player.playlist);
+        queue.version = self.queue.version;
+        if queue != self.queue {
+            queue.version += 1;
+            self.queue = queue.clone();
+        }
+        queue
+    }
+
+    fn broadcast_queue(&mut self) {
+        let queue = self.queue();
+        #[cfg(feature = "mpris")]
+        if let Some(mpris) = &self.mpris {
+            mpris.set_loop_mode(queue.loop_mode);
+        }
+        self.broadcast(&Event::Queue(queue));
+    }
+
+    fn broadcast_state(&mut self) {
+        self.run_status_hook();
+        self.save_session();
+        let state = self.state();
+        #[cfg(feature = "mpris")]
+        if let Some(mpris) = &self.mpris {
+            mpris.set_playback(state.status, state.position);
+            mpris.set_volume(state.volume);
+            mpris.set_speed(state.speed);
+        }
+        self.broadcast(&Event::State(state));
+    }
+
+    fn run_status_hook(&mut self) {
+        let status = self.player.playlist.status();
+        let hook = match (self.status, status) {
+            (Status::Running, Status::Paused) => Hook::Pause,
+            (Status::Paused, Status::Running) => Hook::Resume,
+            (Status::Running | Status::Paused, Status::Stopped) => Hook::Stop,
+            _ => {
+                self.status = status;
+                return;
+            }
+        };
+        self.status = status;
+        let mut info = vec![("position", self.position.to_string())];
+        if let Some(track) = self.player.playlist.current_track() {
+            info.extend(hooks::track_info(track));
+        }
+        self.hooks.run(hook, &info);
+    }
+
+    fn broadcast(&mut self, event: &Event) {
+        self.clients
+            .retain(|(_, stream)| send(stream, event).is_ok());
+    }
+
+    fn remembers_position(&self, track: &Track) -> bool {
+        match self.config.remember_last_played_position {
+            LastPosition::Yes => true,
+            LastPosition::No => false,
+            // 10 minutes
+            LastPosition::Auto => track.duration().as_secs() >= 600,
+        }
+    }
+
+    fn change_speed(&mut self, request: &Request) {
+        match request {
+            Request::SetSpeed(speed) => self.player.set_speed(*speed),
+            Request::SpeedUp => self.player.speed_up(),
+            Request::SpeedDown => self.player.speed_down(),
+            _ => return,
+        }
+        // podcasts keep their own speed, which clients store with the feed
+        let is_podcast = self
+            .player
+            .playlist
+            .current_track()
+            .is_some_and(|track| matches!(track.media_type, Some(MediaType::Podcast)));
+        if !is_podcast {
+            self.config.speed = self.player.speed();
+        }
+    }
+
+    /// Applies the speed and silence skipping of the podcast that started, or the global speed
+    /// for everything else, and marks the episode as played.
+    fn apply_track_settings(&mut self) {
+        let episode = self
+            .player
+            .playlist
+            .current_track()
+            .filter(|track| matches!(track.media_type, Some(MediaType::Podcast)))
+            .and_then(Track::file)
+            .map(|url| self.db_podcast.start_episode(url));
+        let (speed, skip_silence) = match episode {
+            Some(Ok(Some((speed, skip_silence)))) => {
+                (speed.unwrap_or(self.config.speed), skip_silence)
+            }
+            Some(Err(e)) => {
+                self.broadcast(&Event::Error(format!(
+                    "error marking episode as played: {e}"
+                )));
+                (self.config.speed, false)
+            }
+            Some(Ok(None)) | None => (self.config.speed, false),
+        };
+        if speed != self.player.speed() {
+            self.player.set_speed(speed);
+        }
+        if skip_silence != self.player.skip_silence() {
+            self.player.set_skip_silence(skip_silence);
+        }
+    }
+
+    fn save_last_position(&mut self) {
+        let Some(track) = self.player.playlist.current_track().cloned() else {
+            return;
+        };
+        if !self.remembers_position(&track) {
+            return;
+        }
+        let position = Duration::from_secs(u64::try_from(self.position).unwrap_or(0));
+        match track.media_type {
+            Some(MediaType::Music) => self.db.set_last_position(&track, position),
+            Some(MediaType::Podcast) => self.db_podcast.set_last_position(&track, position),
+            Some(MediaType::Youtube) | None => {}
+        }
+    }
+
+    fn restore_last_position(&mut self) {
+        let Some(track) = self.player.playlist.current_track().cloned() else {
+            return;
+        };
+        if !self.remembers_position(&track) {
+            return;
+        }
+        let last_pos = match track.media_type {
+            Some(MediaType::Music) => self.db.get_last_position(&track).ok(),
+            Some(MediaType::Podcast) => self.db_podcast.get_last_position(&track).ok(),
+            Some(MediaType::Youtube) | None => None,
+        };
+        if let Some(last_pos) = last_pos {
+            self.player.seek_to(last_pos);
+            self.db.set_last_position(&track, Duration::from_secs(0));
+        }
+    }
+}
@@ -0,0 +1,257 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+mod client;
+mod control;
+mod daemon;
+#[cfg(feature = "mpris")]
+mod mpris;
+#[cfg(feature = "notify")]
+mod notify;
+pub mod timer;
+#[cfg(feature = "web")]
+mod web;
+
+use crate::hooks::Hook;
+use crate::player::{Loop, Playlist, Status};
+use crate::track::Track;
+use crate::utils::get_app_config_path;
+use anyhow::Result;
+pub use client::RemotePlayer;
+pub use control::control;
+pub use daemon::{run, stop};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use timer::{Alarm, Sleep};
+
+/// Commands sent by clients, mostly the methods of `GeneralPlayer`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Request {
+    /// Starts playing when stopped, resumes when paused.
+    Play,
+    Pause,
+    Resume,
+    TogglePause,
+    Stop,
+    /// Skips to the next track in the queue.
+    Skip,
+    /// Goes back to the previous track of the queue.
+    Previous,
+    /// Seeks by the given seconds, backwards when negative.
+    Seek(i64),
+    /// Seeks to the given position in seconds.
+    SeekTo(u64),
+    SetVolume(i32),
+    VolumeUp,
+    VolumeDown,
+    SetSpeed(i32),
+    SpeedUp,
+    SpeedDown,
+    SetSkipSilence(bool),
+    ToggleGapless,
+    SetLoopMode(Loop),
+    /// Shuffles the upcoming tracks once.
+    Shuffle,
+    /// Plays a file or url right away, without touching the queue.
+    PlayUri(String),
+    /// Adds files to the queue, at the front or the back depending on `add_playlist_front`.
+    Add(Vec<String>),
+    /// Replaces the upcoming tracks and loop mode after a client edited them. Ignored when the
+    /// queue changed since the version it was edited from, and the current track is always
+    /// left to the daemon.
+    SetQueue(Queue),
+    /// Sets or cancels the sleep timer.
+    SetSleep(Option<Sleep>),
+    /// Sets or cancels the wake-up alarm.
+    SetAlarm(Option<Alarm>),
+    /// Runs the hook of an event seen by a client, like a finished podcast download.
+    RunHook(Hook, Vec<(String, String)>),
+    /// Asks for the current state and queue.
+    Status,
+    /// Shuts the daemon down.
+    Quit,
+}
+
+/// Messages from the daemon, the counterpart of `PlayerMsg`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Event {
+    Progress {
+        position: i64,
+        duration: i64,
+    },
+    TrackChanged,
+    State(State),
+    Queue(Queue),
+    CacheStart(String),
+    CacheEnd(String),
+    /// Something went wrong that the user should hear about.
+    Error(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct State {
+    pub status: Status,
+    pub volume: i32,
+    pub speed: i32,
+    pub gapless: bool,
+    pub skip_silence: bool,
+    pub position: i64,
+    pub duration: i64,
+    pub file: Option<String>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub sleep: Option<Sleep>,
+    pub alarm: Option<Alarm>,
+}
+
+/// The playlist as a list of entries, in the format of `playlist.log`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Queue {
+    pub current: Option<String>,
+    pub tracks: Vec<String>,
+    pub loop_mode: Loop,
+    /// Counts the changes made by the daemon, so edits of an older queue can be told apart.
+    pub version: u64,
+}
+
+impl Queue {
+    pub fn new(playlist: &Playlist) -> Self {
+        Self {
+            current: playlist
+                .current_track()
+                .and_then(Track::file)
+                .map(ToString::to_string),
+            tracks: playlist
+                .tracks()
+                .iter()
+                .filter_map(Track::file)
+                .map(ToString::to_string)
+                .collect(),
+            loop_mode: playlist.loop_mode(),
+            version: 0,
+        }
+    }
+
+    /// Whether both have the same upcoming tracks and loop mode, whatever the current track.
+    pub fn same_entries(&self, other: &Self) -> bool {
+        self.tracks == other.tracks && self.loop_mode == other.loop_mode
+    }
+
+    /// Whether the playlist still holds exactly these entries.
+    pub fn matches(&self, playlist: &Playlist) -> bool {
+        self.loop_mode == playlist.loop_mode()
+            && self.current.as_deref() == playlist.current_track().and_then(Track::file)
+            && self
+                .tracks
+                .iter()
+                .map(String::as_str)
+                .eq(playlist.tracks().iter().filter_map(Track::file))
+    }
+
+    /// Loads the queue into the playlist. Tracks already in the playlist are reused, so only
+    /// new entries are read from disk.
+    pub fn apply(&self, playlist: &mut Playlist) -> Result<()> {
+        let mut known: HashMap<&str, &Track> = playlist
+            .tracks()
+            .iter()
+            .filter_map(|t| t.file().map(|f| (f, t)))
+            .collect();
+        if let Some(track) = playlist.current_track() {
+            if let Some(file) = track.file() {
+                known.insert(file, track);
+            }
+        }
+
+        let missing: Vec<String> = self
+            .current
+            .iter()
+            .chain(&self.tracks)
+            .filter(|file| !known.contains_key(file.as_str()))
+            .cloned()
+            .collect();
+        let mut loaded: HashMap<String, Track> = HashMap::new();
+        if !missing.is_empty() {
+            for track in Playlist::load_from(&missing)? {
+                if let Some(file) = track.file() {
+                    loaded.insert(file.to_string(), track.clone());
+                }
+            }
+        }
+
+        let lookup = |file: &String| {
+            known
+                .get(file.as_str())
+                .map(|t| (*t).clone())
+                .or_else(|| loaded.get(file).cloned())
+        };
+        let current = self.current.as_ref().and_then(lookup);
+        let tracks: VecDeque<Track> = self.tracks.iter().filter_map(lookup).collect();
+        playlist.set_queue(current, tracks, self.loop_mode);
+        Ok(())
+    }
+}
+
+/// The socket of the daemon, in the config directory so every session of the user shares it.
+pub fn socket_path() -> Result<PathBuf> {
+    let mut path = get_app_config_path()?;
+    path.push("termusic.sock");
+    Ok(path)
+}
+
+/// Writes a message as one line of json.
+fn send<T: Serialize>(mut stream: &UnixStream, message: &T) -> Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    stream.write_all(&line)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_roundtrip() {
+        let request = Request::SetQueue(Queue {
+            current: Some("/music/a.mp3".to_string()),
+            tracks: vec!["/music/b.mp3".to_string()],
+            loop_mode: Loop::Queue,
+            version: 3,
+        });
+        let line = serde_json::to_string(&request).unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(serde_json::from_str::<Request>(&line).unwrap(), request);
+
+        let event = Event::Progress {
+            position: 3,
+            duration: 200,
+        };
+        let line = serde_json::to_string(&event).unwrap();
+        assert_eq!(line, r#"{"Progress":{"position":3,"duration":200}}"#);
+        assert_eq!(serde_json::from_str::<Event>(&line).unwrap(), event);
+    }
+}
@@ -0,0 +1,651 @@
+use super::Request;
+use crate::config::Settings;
+use crate::player::{Loop, Status};
+use crate::track::Track;
+use anyhow::Result;
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged;
+use dbus::blocking::Connection;
+use dbus::channel::{MatchingReceiver, Sender as _};
+use dbus::message::{MatchRule, SignalArgs};
+use dbus::{Message, Path};
+use dbus_crossroads::{Crossroads, IfaceBuilder, MethodErr};
+use lofty::Picture;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+// characters that can't stay as they are in the path of a file url
+const PATH: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'#').add(b'%').add(b'?');
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.termusic";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+/// What the bus gets to see of the player. Written by the daemon, read by the bus thread.
+struct Properties {
+    status: Status,
+    position: i64,
+    volume: i32,
+    speed: i32,
+    loop_mode: Loop,
+    metadata: Metadata,
+}
+
+#[derive(Default)]
+struct Metadata {
+    track_id: Option<String>,
+    url: Option<String>,
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    art_url: Option<String>,
+    track_number: Option<u32>,
+    length: Duration,
+}
+
+impl Metadata {
+    fn to_map(&self) -> PropMap {
+        let mut map = PropMap::new();
+        let mut insert = |key: &str, value: Box<dyn RefArg>| {
+            map.insert(key.to_string(), Variant(value));
+        };
+        let track_id = self.track_id.as_deref().unwrap_or(NO_TRACK);
+        insert("mpris:trackid", Box::new(Path::from(track_id.to_string())));
+        if self.track_id.is_none() {
+            return map;
+        }
+        insert(
+            "mpris:length",
+            Box::new(i64::try_from(self.length.as_micros()).unwrap_or(i64::MAX)),
+        );
+        if let Some(url) = &self.art_url {
+            insert("mpris:artUrl", Box::new(url.clone()));
+        }
+        if let Some(url) = &self.url {
+            insert("xesam:url", Box::new(url.clone()));
+        }
+        if let Some(title) = &self.title {
+            insert("xesam:title", Box::new(title.clone()));
+        }
+        if let Some(artist) = &self.artist {
+            insert("xesam:artist", Box::new(vec![artist.clone()]));
+        }
+        if let Some(album) = &self.album {
+            insert("xesam:album", Box::new(album.clone()));
+        }
+        if let Some(number) = self.track_number.and_then(|n| i32::try_from(n).ok()) {
+            insert("xesam:trackNumber", Box::new(number));
+        }
+        map
+    }
+}
+
+/// The properties that tell the bus when they change. The position only does so when it
+/// jumps, with a signal of its own.
+#[derive(Clone, Copy)]
+enum Changed {
+    PlaybackStatus,
+    LoopStatus,
+    CanGoPrevious,
+    Rate,
+    Volume,
+    Metadata,
+    Seeked,
+}
+
+impl Changed {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::PlaybackStatus => "PlaybackStatus",
+            Self::LoopStatus => "LoopStatus",
+            Self::CanGoPrevious => "CanGoPrevious",
+            Self::Rate => "Rate",
+            Self::Volume => "Volume",
+            Self::Metadata => "Metadata",
+            Self::Seeked => "Position",
+        }
+    }
+
+    fn value(self, properties: &Properties) -> Box<dyn RefArg> {
+        match self {
+            Self::PlaybackStatus => Box::new(playback_status(properties.status).to_string()),
+            Self::LoopStatus => Box::new(loop_status(properties.loop_mode).to_string()),
+            Self::CanGoPrevious => Box::new(can_go_previous(properties.loop_mode)),
+            Self::Rate => Box::new(rate(properties.speed)),
+            Self::Volume => Box::new(volume(properties.volume)),
+            Self::Metadata => Box::new(properties.metadata.to_map()),
+            Self::Seeked => Box::new(properties.position * 1_000_000),
+        }
+    }
+}
+
+const fn playback_status(status: Status) -> &'static str {
+    match status {
+        Status::Running => "Playing",
+        Status::Paused => "Paused",
+        Status::Stopped => "Stopped",
+    }
+}
+
+const fn loop_status(loop_mode: Loop) -> &'static str {
+    match loop_mode {
+        Loop::Single => "Track",
+        Loop::Playlist => "Playlist",
+        Loop::Queue => "None",
+    }
+}
+
+// the daemon ignores Previous when it would not leave the current track
+const fn can_go_previous(loop_mode: Loop) -> bool {
+    matches!(loop_mode, Loop::Playlist)
+}
+
+// termusic seeks in seconds, mpris in microseconds
+const fn seconds(micros: i64) -> i64 {
+    (micros + 500_000).div_euclid(1_000_000)
+}
+
+/// Where playback should be by now, had nobody seeked since `last` was reported.
+#[allow(clippy::cast_possible_truncation)]
+fn expected_position(last: i64, elapsed: Duration, status: Status, speed: i32) -> i64 {
+    match status {
+        Status::Running => last + (elapsed.as_secs_f64() * rate(speed)).round() as i64,
+        Status::Paused | Status::Stopped => last,
+    }
+}
+
+// termusic counts the speed in tenths and the volume in percent, mpris in ratios
+fn rate(speed: i32) -> f64 {
+    f64::from(speed) / 10.0
+}
+
+fn volume(volume: i32) -> f64 {
+    f64::from(volume) / 100.0
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn from_ratio(ratio: f64, scale: f64) -> i32 {
+    (ratio * scale).round().clamp(0.0, 1000.0) as i32
+}
+
+fn lock(properties: &Mutex<Properties>) -> MutexGuard<'_, Properties> {
+    properties.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The player object on the bus.
+struct Bus {
+    properties: Arc<Mutex<Properties>>,
+    requests: Sender<Request>,
+}
+
+impl Bus {
+    fn properties(&self) -> MutexGuard<'_, Properties> {
+        lock(&self.properties)
+    }
+
+    fn request(&self, request: Request) -> Result<(), MethodErr> {
+        self.requests
+            .send(request)
+            .map_err(|_| MethodErr::failed("termusic is shutting down"))
+    }
+}
+
+fn root_interface(b: &mut IfaceBuilder<Bus>) {
+    b.method("Raise", (), (), |_, _, (): ()| Ok(()));
+    b.method("Quit", (), (), |_, _, (): ()| Ok(()));
+    b.property("CanQuit")
+        .get(|_, _| Ok(false))
+        .emits_changed_const();
+    b.property("CanRaise")
+        .get(|_, _| Ok(false))
+        .emits_changed_const();
+    b.property("HasTrackList")
+        .get(|_, _| Ok(false))
+        .emits_changed_const();
+    b.property("Identity")
+        .get(|_, _| Ok("termusic".to_string()))
+        .emits_changed_const();
+    b.property("DesktopEntry")
+        .get(|_, _| Ok("termusic".to_string()))
+        .emits_changed_const();
+    b.property("SupportedUriSchemes")
+        .get(|_, _| Ok(vec!["file".to_string()]))
+        .emits_changed_const();
+    b.property("SupportedMimeTypes")
+        .get(|_, _| Ok(Vec::<String>::new()))
+        .emits_changed_const();
+}
+
+fn player_interface(b: &mut IfaceBuilder<Bus>) {
+    let command = |b: &mut IfaceBuilder<Bus>, name: &'static str, request: Request| {
+        b.method(name, (), (), move |_, bus: &mut Bus, (): ()| {
+            bus.request(request.clone())
+        });
+    };
+    command(b, "Next", Request::Skip);
+    command(b, "Previous", Request::Previous);
+    command(b, "Pause", Request::Pause);
+    command(b, "PlayPause", Request::TogglePause);
+    command(b, "Stop", Request::Stop);
+    command(b, "Play", Request::Play);
+    b.method("Seek", ("Offset",), (), |_, bus, (offset,): (i64,)| {
+        bus.request(Request::Seek(seconds(offset)))
+    });
+    b.method(
+        "SetPosition",
+        ("TrackId", "Position"),
+        (),
+        |_, bus, (track_id, position): (Path<'static>, i64)| {
+            // meant for a track that is gone by now
+            if bus.properties().metadata.track_id.as_deref() != Some(&*track_id) {
+                return Ok(());
+            }
+            match u64::try_from(seconds(position)) {
+                Ok(position) => bus.request(Request::SeekTo(position)),
+                Err(_) => Ok(()),
+            }
+        },
+    );
+    b.method("OpenUri", ("Uri",), (), |_, bus, (uri,): (String,)| {
+        bus.request(Request::PlayUri(match uri.strip_prefix("file://") {
+            Some(path) => percent_decode_str(path).decode_utf8_lossy().to_string(),
+            None => uri,
+        }))
+    });
+
+    b.property("PlaybackStatus")
+        .get(|_, bus| Ok(playback_status(bus.properties().status).to_string()));
+    b.property("LoopStatus")
+        .get(|_, bus| Ok(loop_status(bus.properties().loop_mode).to_string()))
+        .set(|_, bus, status: String| {
+            let loop_mode = match status.as_str() {
+                "None" => Loop::Queue,
+                "Track" => Loop::Single,
+                "Playlist" => Loop::Playlist,
+                _ => return Err(MethodErr::invalid_arg(&status)),
+            };
+            bus.request(Request::SetLoopMode(loop_mode))?;
+            Ok(None)
+        });
+    b.property("Rate")
+        .get(|_, bus| Ok(rate(bus.properties().speed)))
+        .set(|_, bus, rate: f64| {
+            bus.request(Request::SetSpeed(from_ratio(rate, 10.0)))?;
+            Ok(None)
+        });
+    b.property("MinimumRate")
+        .get(|_, _| Ok(rate(1)))
+        .emits_changed_const();
+    b.property("MaximumRate")
+        .get(|_, _| Ok(rate(30)))
+        .emits_changed_const();
+    // termusic shuffles the queue once instead of having a shuffle mode
+    b.property("Shuffle")
+        .get(|_, _| Ok(false))
+        .set(|_, bus, shuffle: bool| {
+            if shuffle {
+                bus.request(Request::Shuffle)?;
+            }
+            Ok(None)
+        });
+    b.property("Metadata")
+        .get(|_, bus| Ok(bus.properties().metadata.to_map()));
+    b.property("Volume")
+        .get(|_, bus| Ok(volume(bus.properties().volume)))
+        .set(|_, bus, volume: f64| {
+            bus.request(Request::SetVolume(from_ratio(volume, 100.0).min(100)))?;
+            Ok(None)
+        });
+    b.property("Position")
+        .get(|_, bus| Ok(bus.properties().position * 1_000_000))
+        .emits_changed_false();
+    b.property("CanGoPrevious")
+        .get(|_, bus| Ok(can_go_previous(bus.properties().loop_mode)));
+    for name in ["CanGoNext", "CanPlay", "CanPause", "CanSeek", "CanControl"] {
+        b.property(name).get(|_, _| Ok(true)).emits_changed_const();
+    }
+}
+
+/// Answers calls from the bus and sends it the changes, until the daemon drops its end.
+fn serve(
+    mut cr: Crossroads,
+    conn: &Connection,
+    properties: &Mutex<Properties>,
+    changes: &Receiver<Changed>,
+) {
+    conn.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(move |msg, conn| {
+            cr.handle_message(msg, conn).ok();
+            true
+        }),
+    );
+    let path = Path::from(OBJECT_PATH);
+    loop {
+        if let Err(e) = conn.process(Duration::from_millis(100)) {
+            eprintln!("mpris error: {e}");
+            return;
+        }
+        let mut signal = PropertiesPropertiesChanged {
+            interface_name: PLAYER_INTERFACE.to_string(),
+            changed_properties: PropMap::new(),
+            invalidated_properties: Vec::new(),
+        };
+        loop {
+            match changes.try_recv() {
+                Ok(Changed::Seeked) => {
+                    let position = lock(properties).position * 1_000_000;
+                    if let Ok(seeked) = Message::new_signal(OBJECT_PATH, PLAYER_INTERFACE, "Seeked")
+                    {
+                        conn.send(seeked.append1(position)).ok();
+                    }
+                }
+                Ok(property) => {
+                    let value = property.value(&lock(properties));
+                    signal
+                        .changed_properties
+                        .insert(property.name().to_string(), Variant(value));
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+        if !signal.changed_properties.is_empty() {
+            conn.send(signal.to_emit_message(&path)).ok();
+        }
+    }
+}
+
+pub struct Mpris {
+    properties: Arc<Mutex<Properties>>,
+    changes: Sender<Changed>,
+    requests: Receiver<Request>,
+    // embedded pictures are handed out as a temporary file
+    cover: Option<PathBuf>,
+    // the last progress update and when it came, to notice when the position jumps
+    progress: Option<(i64, Instant)>,
+    seeking: bool,
+}
+
+impl Drop for Mpris {
+    fn drop(&mut self) {
+        if let Some(cover) = self.cover.take() {
+            std::fs::remove_file(cover).ok();
+        }
+    }
+}
+
+impl Mpris {
+    /// Puts the player on the session bus. Fails when there is no bus to connect to.
+    pub fn new(config: &Settings) -> Result<Self> {
+        let conn = Connection::new_session()?;
+        conn.request_name(BUS_NAME, false, true, false)?;
+
+        let properties = Arc::new(Mutex::new(Properties {
+            status: Status::Stopped,
+            position: 0,
+            volume: config.volume,
+            speed: config.speed,
+            loop_mode: config.loop_mode,
+            metadata: Metadata::default(),
+        }));
+        let (requests_tx, requests) = mpsc::channel();
+        let (changes, changes_rx) = mpsc::channel();
+
+        let mut cr = Crossroads::new();
+        let root = cr.register("org.mpris.MediaPlayer2", root_interface);
+        let player = cr.register(PLAYER_INTERFACE, player_interface);
+        cr.insert(
+            OBJECT_PATH,
+            &[root, player],
+            Bus {
+                properties: properties.clone(),
+                requests: requests_tx,
+            },
+        );
+        let shared = properties.clone();
+        std::thread::spawn(move || serve(cr, &conn, &shared, &changes_rx));
+
+        Ok(Self {
+            properties,
+            changes,
+            requests,
+            cover: None,
+            progress: None,
+            seeking: false,
+        })
+    }
+
+    pub fn try_recv(&self) -> Option<Request> {
+        self.requests.try_recv().ok()
+    }
+
+    fn properties(&self) -> MutexGuard<'_, Properties> {
+        lock(&self.properties)
+    }
+
+    fn changed(&self, changed: Changed) {
+        self.changes.send(changed).ok();
+    }
+
+    pub fn set_track(&mut self, track: Option<&Track>) {
+        let metadata = match track {
+            Some(track) => Metadata {
+                track_id: track
+                    .file()
+                    .map(|file| format!("/org/termusic/track/{:x}", md5::compute(file))),
+                url: track.file().map(|file| {
+                    if file.starts_with("http") {
+                        file.to_string()
+                    } else {
+                        format!("file://{}", utf8_percent_encode(file, PATH))
+                    }
+                }),
+                title: Some(track.title().unwrap_or("Unknown Title").to_string()),
+                artist: Some(track.artist().unwrap_or("Unknown Artist").to_string()),
+                album: track.album().map(ToString::to_string),
+                art_url: self.cover_url(track),
+                track_number: track.track_number(),
+                length: track.duration(),
+            },
+            None => Metadata::default(),
+        };
+        self.properties().metadata = metadata;
+        self.changed(Changed::Metadata);
+        // a new track starting over is no seek
+        self.progress = None;
+        self.seeking = false;
+    }
+
+    pub fn set_playback(&self, status: Status, position: i64) {
+        let mut properties = self.properties();
+        properties.position = position;
+        if properties.status != status {
+            properties.status = status;
+            self.changed(Changed::PlaybackStatus);
+        }
+    }
+
+    /// Called with every progress update of the player. Tells the bus about the new position
+    /// after a seek, or when it jumped for any other reason.
+    pub fn set_position(&mut self, position: i64) {
+        let now = Instant::now();
+        let (status, speed) = {
+            let mut properties = self.properties();
+            properties.position = position;
+            (properties.status, properties.speed)
+        };
+        let jumped = self.progress.is_some_and(|(last, at)| {
+            (position - expected_position(last, now - at, status, speed)).abs() > 2
+        });
+        self.progress = Some((position, now));
+        if self.seeking || jumped {
+            self.seeking = false;
+            self.changed(Changed::Seeked);
+        }
+    }
+
+    /// The next position reported is the result of a seek.
+    pub fn seeking(&mut self) {
+        self.seeking = true;
+    }
+
+    pub fn set_volume(&self, volume: i32) {
+        let mut properties = self.properties();
+        if properties.volume != volume {
+            properties.volume = volume;
+            self.changed(Changed::Volume);
+        }
+    }
+
+    pub fn set_speed(&self, speed: i32) {
+        let mut properties = self.properties();
+        if properties.speed != speed {
+            properties.speed = speed;
+            self.changed(Changed::Rate);
+        }
+    }
+
+    pub fn set_loop_mode(&self, loop_mode: Loop) {
+        let mut properties = self.properties();
+        if properties.loop_mode != loop_mode {
+            properties.loop_mode = loop_mode;
+            self.changed(Changed::LoopStatus);
+            self.changed(Changed::CanGoPrevious);
+        }
+    }
+
+    fn cover_url(&mut self, track: &Track) -> Option<String> {
+        if let Some(picture) = track.picture() {
+            return self.save_cover(picture).ok();
+        }
+        let photo = track.album_photo()?;
+        if photo.starts_with("http") {
+            return Some(photo.to_string());
+        }
+        Some(format!("file://{}", utf8_percent_encode(photo, PATH)))
+    }
+
+    fn save_cover(&mut self, picture: &Picture) -> std::io::Result<String> {
+        let mut path = dirs::cache_dir().unwrap_or_else(std::env::temp_dir);
+        path.push("termusic");
+        std::fs::create_dir_all(&path)?;
+        let ext = picture
+            .mime_type()
+            .as_str()
+            .strip_prefix("image/")
+            .unwrap_or("jpg");
+        // a new name for every picture, so widgets don't keep showing a cached one
+        path.push(format!(
+            "mpris_cover_{:x}.{ext}",
+            md5::compute(picture.data())
+        ));
+        if self.cover.as_ref() != Some(&path) {
+            if let Some(old) = self.cover.take() {
+                std::fs::remove_file(old).ok();
+            }
+            std::fs::write(&path, picture.data())?;
+            self.cover = Some(path.clone());
+        }
+        Ok(format!(
+            "file://{}",
+            utf8_percent_encode(&path.to_string_lossy(), PATH)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_loop_status() {
+        assert_eq!(loop_status(Loop::Single), "Track");
+        assert_eq!(loop_status(Loop::Playlist), "Playlist");
+        assert_eq!(loop_status(Loop::Queue), "None");
+        assert!(can_go_previous(Loop::Playlist));
+        assert!(!can_go_previous(Loop::Single));
+        assert!(!can_go_previous(Loop::Queue));
+    }
+
+    #[test]
+    fn test_from_ratio() {
+        assert_eq!(from_ratio(1.0, 10.0), 10);
+        assert_eq!(from_ratio(1.25, 10.0), 13);
+        assert_eq!(from_ratio(0.504, 100.0), 50);
+        assert_eq!(from_ratio(-1.0, 100.0), 0);
+        assert_eq!(from_ratio(f64::MAX, 100.0), 1000);
+        assert_eq!(from_ratio(rate(15), 10.0), 15);
+    }
+
+    #[test]
+    fn test_seconds() {
+        assert_eq!(seconds(10_000_000), 10);
+        assert_eq!(seconds(400_000), 0);
+        assert_eq!(seconds(600_000), 1);
+        assert_eq!(seconds(-400_000), 0);
+        assert_eq!(seconds(-600_000), -1);
+        assert_eq!(seconds(-5_000_000), -5);
+    }
+
+    #[test]
+    fn test_expected_position() {
+        let elapsed = Duration::from_secs(4);
+        assert_eq!(expected_position(10, elapsed, Status::Running, 10), 14);
+        assert_eq!(expected_position(10, elapsed, Status::Running, 15), 16);
+        assert_eq!(expected_position(10, elapsed, Status::Paused, 10), 10);
+    }
+
+    #[test]
+    fn test_metadata_to_map() {
+        let map = Metadata::default().to_map();
+        assert_eq!(map.len(), 1);
+        assert_eq!(
+            map["mpris:trackid"].0.as_str(),
+            Some("/org/mpris/MediaPlayer2/TrackList/NoTrack")
+        );
+
+        let metadata = Metadata {
+            track_id: Some("/org/termusic/track/1".to_string()),
+            url: Some("file:///music/a%20b.mp3".to_string()),
+            title: Some("Title".to_string()),
+            artist: Some("Artist".to_string()),
+            album: None,
+            art_url: None,
+            track_number: Some(3),
+            length: Duration::from_secs(61),
+        };
+        let map = metadata.to_map();
+        let mut keys: Vec<&str> = map.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            vec![
+                "mpris:length",
+                "mpris:trackid",
+                "xesam:artist",
+                "xesam:title",
+                "xesam:trackNumber",
+                "xesam:url"
+            ]
+        );
+        assert_eq!(
+            map["mpris:trackid"].0.as_str(),
+            Some("/org/termusic/track/1")
+        );
+        assert_eq!(map["mpris:length"].0.as_i64(), Some(61_000_000));
+        assert_eq!(map["xesam:trackNumber"].0.as_i64(), Some(3));
+        let artists: Vec<&str> = map["xesam:artist"]
+            .0
+            .as_iter()
+            .unwrap()
+            .filter_map(|a| a.as_str())
+            .collect();
+        assert_eq!(artists, vec!["Artist"]);
+    }
+}
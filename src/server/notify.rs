@@ -0,0 +1,188 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::Request;
+use crate::config::Settings;
+use crate::track::Track;
+use notify_rust::{Hint, Notification, Timeout};
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Desktop notifications over `org.freedesktop.Notifications`. Each one replaces the one before,
+/// and its Next and Pause buttons come back as requests through `try_recv`.
+pub struct Notifier {
+    summary: String,
+    body: String,
+    tx: Sender<Notice>,
+    rx: Receiver<Request>,
+}
+
+struct Notice {
+    summary: String,
+    body: String,
+    cover: Option<Cover>,
+}
+
+enum Cover {
+    /// The embedded picture with its file extension.
+    Picture(Vec<u8>, String),
+    File(String),
+    Url(String),
+}
+
+impl Notifier {
+    pub fn new(config: &Settings) -> Self {
+        let (tx, notice_rx) = mpsc::channel();
+        let (request_tx, rx) = mpsc::channel();
+        std::thread::spawn(move || worker(&notice_rx, &request_tx));
+        Self {
+            summary: config.notify_summary.clone(),
+            body: config.notify_body.clone(),
+            tx,
+            rx,
+        }
+    }
+
+    pub fn try_recv(&self) -> Option<Request> {
+        self.rx.try_recv().ok()
+    }
+
+    pub fn track_changed(&self, track: &Track) {
+        let cover = if let Some(picture) = track.picture() {
+            let ext = picture
+                .mime_type()
+                .as_str()
+                .strip_prefix("image/")
+                .unwrap_or("jpg")
+                .to_string();
+            Some(Cover::Picture(picture.data().to_vec(), ext))
+        } else {
+            track.album_photo().map(|photo| {
+                if photo.starts_with("http") {
+                    Cover::Url(photo.to_string())
+                } else {
+                    Cover::File(photo.to_string())
+                }
+            })
+        };
+        self.tx
+            .send(Notice {
+                summary: track.fill_template(&self.summary),
+                body: track.fill_template(&self.body),
+                cover,
+            })
+            .ok();
+    }
+}
+
+fn worker(rx: &Receiver<Notice>, request_tx: &Sender<Request>) {
+    let mut id = None;
+    let mut cover_file: Option<PathBuf> = None;
+    // Replaced notifications keep their id, so only the waiter of the latest one may answer.
+    let generation = Arc::new(AtomicUsize::new(0));
+
+    while let Ok(notice) = rx.recv() {
+        let cover = match notice.cover {
+            Some(Cover::File(path)) => Some(path),
+            Some(Cover::Picture(data, ext)) => save_cover(&data, &ext, &mut cover_file),
+            Some(Cover::Url(url)) => {
+                fetch_cover(&url).and_then(|data| save_cover(&data, "jpg", &mut cover_file))
+            }
+            None => None,
+        };
+
+        let mut notification = Notification::new();
+        notification
+            .appname("termusic")
+            .summary(&notice.summary)
+            .body(&notice.body)
+            .icon("audio-x-generic")
+            .action("next", "Next")
+            .action("pause", "Pause")
+            .hint(Hint::Category("x-gnome.music".to_string()))
+            .hint(Hint::Transient(true))
+            .timeout(Timeout::Default);
+        if let Some(cover) = &cover {
+            notification.image_path(cover);
+        }
+        if let Some(id) = id {
+            notification.id(id);
+        }
+        let handle = match notification.show() {
+            Ok(handle) => handle,
+            Err(e) => {
+                eprintln!("error showing notification: {e}");
+                continue;
+            }
+        };
+        id = Some(handle.id());
+
+        let current = generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let generation = Arc::clone(&generation);
+        let request_tx = request_tx.clone();
+        std::thread::spawn(move || {
+            handle.wait_for_action(|action| {
+                if generation.load(Ordering::SeqCst) != current {
+                    return;
+                }
+                let request = match action {
+                    "next" => Request::Skip,
+                    "pause" => Request::TogglePause,
+                    _ => return,
+                };
+                request_tx.send(request).ok();
+            });
+        });
+    }
+
+    if let Some(file) = cover_file {
+        std::fs::remove_file(file).ok();
+    }
+}
+
+/// Writes the cover into the cache, replacing the previous one.
+fn save_cover(data: &[u8], ext: &str, current: &mut Option<PathBuf>) -> Option<String> {
+    let mut path = dirs::cache_dir().unwrap_or_else(std::env::temp_dir);
+    path.push("termusic");
+    std::fs::create_dir_all(&path).ok()?;
+    path.push(format!("notify_cover_{:x}.{ext}", md5::compute(data)));
+    if current.as_ref() != Some(&path) {
+        if let Some(old) = current.take() {
+            std::fs::remove_file(old).ok();
+        }
+        std::fs::write(&path, data).ok()?;
+        *current = Some(path.clone());
+    }
+    Some(path.to_string_lossy().to_string())
+}
+
+fn fetch_cover(url: &str) -> Option<Vec<u8>> {
+    let resp = ureq::get(url).timeout(Duration::from_secs(5)).call().ok()?;
+    let mut data = Vec::new();
+    resp.into_reader().read_to_end(&mut data).ok()?;
+    Some(data)
+}
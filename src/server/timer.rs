@@ -0,0 +1,255 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Seconds the volume takes to fade out before the sleep timer runs out.
+pub const FADE_OUT: i64 = 30;
+/// Seconds the volume takes to come up once the alarm went off.
+pub const FADE_IN: i64 = 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SleepAction {
+    Stop,
+    Pause,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SleepUntil {
+    /// Unix time in seconds.
+    Time(i64),
+    /// Tracks left to finish, counting the current one.
+    Tracks(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Sleep {
+    pub until: SleepUntil,
+    pub action: SleepAction,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Alarm {
+    /// Unix time in seconds.
+    pub time: i64,
+    /// Playlist file replacing the queue, the queue is played as it is without one.
+    pub playlist: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Setting {
+    Sleep(Sleep),
+    Alarm(Alarm),
+    Off,
+}
+
+/// Parses the text of the sleep timer popup:
+/// `30` or `30m` stops after 30 minutes, `track` at the end of the current track and `3t` after
+/// three tracks, any of them followed by `pause` pauses instead. `7:30` plays the queue at 7:30
+/// and `7:30 <playlist>` a playlist. `off` cancels the sleep timer and the alarm.
+pub fn parse(spec: &str) -> Result<Setting> {
+    parse_at(spec, Local::now())
+}
+
+fn parse_at(spec: &str, now: DateTime<Local>) -> Result<Setting> {
+    let spec = spec.trim();
+    let (first, rest) = spec
+        .split_once(char::is_whitespace)
+        .map_or((spec, ""), |(first, rest)| (first, rest.trim()));
+    if first.is_empty() {
+        bail!("nothing to set");
+    }
+    if first == "off" && rest.is_empty() {
+        return Ok(Setting::Off);
+    }
+    if first.contains(':') {
+        return alarm(first, rest, now).map(Setting::Alarm);
+    }
+
+    let mut words: Vec<&str> = spec.split_whitespace().collect();
+    let action = if words.last() == Some(&"pause") {
+        words.pop();
+        SleepAction::Pause
+    } else {
+        SleepAction::Stop
+    };
+    let number = |n: &str| {
+        n.parse::<u32>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| anyhow!("not a number of minutes or tracks: {n}"))
+    };
+    let until = match words.as_slice() {
+        ["track" | "end"] => SleepUntil::Tracks(1),
+        [n, "t" | "track" | "tracks"] => SleepUntil::Tracks(number(n)?),
+        [n, "m" | "min" | "minutes"] => minutes(number(n)?, now),
+        [n] => match n.strip_suffix('t') {
+            Some(n) => SleepUntil::Tracks(number(n)?),
+            None => minutes(
+                number(n.trim_end_matches("min").trim_end_matches('m'))?,
+                now,
+            ),
+        },
+        _ => bail!("unknown sleep timer: {spec}"),
+    };
+    Ok(Setting::Sleep(Sleep { until, action }))
+}
+
+fn minutes(minutes: u32, now: DateTime<Local>) -> SleepUntil {
+    SleepUntil::Time(now.timestamp() + i64::from(minutes) * 60)
+}
+
+/// The alarm goes off at the next time the clock shows `time`.
+fn alarm(time: &str, playlist: &str, now: DateTime<Local>) -> Result<Alarm> {
+    let clock = NaiveTime::parse_from_str(time, "%H:%M")
+        .with_context(|| format!("not a time like 7:30: {time}"))?;
+    let mut day = now.date_naive();
+    let at = loop {
+        if let Some(at) = Local.from_local_datetime(&day.and_time(clock)).earliest() {
+            if at > now {
+                break at;
+            }
+        }
+        day = day
+            .succ_opt()
+            .ok_or_else(|| anyhow!("no next day of {day}"))?;
+    };
+
+    let playlist = if playlist.is_empty() {
+        None
+    } else {
+        let path = shellexpand::tilde(playlist).to_string();
+        let path = Path::new(&path)
+            .canonicalize()
+            .with_context(|| format!("no such playlist: {playlist}"))?;
+        Some(path.to_string_lossy().to_string())
+    };
+    Ok(Alarm {
+        time: at.timestamp(),
+        playlist,
+    })
+}
+
+/// The volume while fading out, with `left` seconds until the timer runs out.
+pub fn fade_out(volume: i32, left: i64) -> i32 {
+    scale(volume, left)
+}
+
+/// The volume while fading in, `elapsed` seconds after the alarm went off.
+pub fn fade_in(volume: i32, elapsed: i64) -> i32 {
+    scale(volume, elapsed * FADE_OUT / FADE_IN)
+}
+
+// `part` out of `FADE_OUT`
+fn scale(volume: i32, part: i64) -> i32 {
+    let part = part.clamp(0, FADE_OUT);
+    i32::try_from(i64::from(volume) * part / FADE_OUT).unwrap_or(volume)
+}
+
+/// What the progress title shows, like `Sleep in 12:05 | Alarm at 07:30`.
+pub fn countdown(sleep: Option<&Sleep>, alarm: Option<&Alarm>) -> Option<String> {
+    let now = Local::now();
+    let mut parts = Vec::new();
+    if let Some(sleep) = sleep {
+        let verb = match sleep.action {
+            SleepAction::Stop => "Stop",
+            SleepAction::Pause => "Pause",
+        };
+        parts.push(match sleep.until {
+            SleepUntil::Time(time) => {
+                let left = (time - now.timestamp()).max(0);
+                format!("{verb} in {}:{:02}", left / 60, left % 60)
+            }
+            SleepUntil::Tracks(1) => format!("{verb} after this track"),
+            SleepUntil::Tracks(n) => format!("{verb} after {n} tracks"),
+        });
+    }
+    if let Some(alarm) = alarm {
+        if let Some(at) = Local.timestamp_opt(alarm.time, 0).single() {
+            parts.push(format!("Alarm at {}", at.format("%H:%M")));
+        }
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts.join(" | "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    #[test]
+    fn test_parse() {
+        let now = Local::now();
+        let sleep = |until, action| Some(Setting::Sleep(Sleep { until, action }));
+        let in_30 = SleepUntil::Time(now.timestamp() + 30 * 60);
+        assert_eq!(parse_at("30", now).ok(), sleep(in_30, SleepAction::Stop));
+        assert_eq!(
+            parse_at("30m pause", now).ok(),
+            sleep(in_30, SleepAction::Pause)
+        );
+        assert_eq!(
+            parse_at("30 min", now).ok(),
+            sleep(in_30, SleepAction::Stop)
+        );
+        assert_eq!(
+            parse_at("track", now).ok(),
+            sleep(SleepUntil::Tracks(1), SleepAction::Stop)
+        );
+        assert_eq!(
+            parse_at("3 tracks pause", now).ok(),
+            sleep(SleepUntil::Tracks(3), SleepAction::Pause)
+        );
+        assert_eq!(
+            parse_at("2t", now).ok(),
+            sleep(SleepUntil::Tracks(2), SleepAction::Stop)
+        );
+        assert_eq!(parse_at("off", now).ok(), Some(Setting::Off));
+        assert!(parse_at("0", now).is_err());
+        assert!(parse_at("soon", now).is_err());
+        assert!(parse_at("25:00", now).is_err());
+
+        let Ok(Setting::Alarm(alarm)) = parse_at("7:30", now) else {
+            panic!("no alarm");
+        };
+        let at = Local.timestamp_opt(alarm.time, 0).unwrap();
+        assert!(at > now && at <= now + chrono::Duration::days(1));
+        assert_eq!((at.hour(), at.minute()), (7, 30));
+        assert_eq!(alarm.playlist, None);
+    }
+
+    #[test]
+    fn test_fade() {
+        assert_eq!(fade_out(60, FADE_OUT), 60);
+        assert_eq!(fade_out(60, FADE_OUT / 2), 30);
+        assert_eq!(fade_out(60, -1), 0);
+        assert_eq!(fade_in(60, 0), 0);
+        assert_eq!(fade_in(60, FADE_IN / 2), 30);
+        assert_eq!(fade_in(60, FADE_IN * 2), 60);
+    }
+}
@@ -0,0 +1,312 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::control::{collect_files, greeting};
+use super::{send, socket_path, Queue, Request, State};
+use crate::config::Settings;
+use crate::player::Loop;
+use crate::sqlite::{DataBase, SearchCriteria, TrackForDB};
+use crate::track::Track;
+use anyhow::{anyhow, bail, Context, Result};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+use tiny_http::{Header, Response, Server};
+
+const PAGE: &str = include_str!("web.html");
+
+/// Serves the http api and the web page for phones in a thread of the daemon. Nothing is
+/// served until a token is configured.
+pub fn spawn(config: &Settings) {
+    if config.web_token.is_empty() {
+        eprintln!("web api disabled, set web_token in the config to enable it");
+        return;
+    }
+    let server = match Server::http(&config.web_address) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("error serving web api on {}: {e}", config.web_address);
+            return;
+        }
+    };
+    let config = config.clone();
+    std::thread::spawn(move || {
+        let mut db = DataBase::new(&config);
+        for request in server.incoming_requests() {
+            handle(&mut db, &config.web_token, request);
+        }
+    });
+}
+
+struct Reply {
+    status: u16,
+    content_type: String,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn json<T: Serialize>(value: &T) -> Result<Self> {
+        Ok(Self {
+            status: 200,
+            content_type: "application/json".to_string(),
+            body: serde_json::to_vec(value)?,
+        })
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json".to_string(),
+            body: serde_json::json!({ "error": message })
+                .to_string()
+                .into_bytes(),
+        }
+    }
+
+    const fn done() -> Self {
+        Self {
+            status: 204,
+            content_type: String::new(),
+            body: Vec::new(),
+        }
+    }
+}
+
+fn handle(db: &mut DataBase, token: &str, mut request: tiny_http::Request) {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let bearer = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .map(|h| h.value.as_str().trim_start_matches("Bearer ").to_string());
+
+    let reply = if path == "/" {
+        // the page holds no data, it asks for the token itself
+        Reply {
+            status: 200,
+            content_type: "text/html; charset=utf-8".to_string(),
+            body: PAGE.as_bytes().to_vec(),
+        }
+    } else if !authorized(token, bearer.as_deref(), query) {
+        Reply::error(401, "missing or wrong token")
+    } else {
+        let method = request.method().to_string();
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body).ok();
+        route(db, &method, path, &body).unwrap_or_else(|e| Reply::error(400, &e.to_string()))
+    };
+
+    let mut response = Response::from_data(reply.body).with_status_code(reply.status);
+    if let Ok(header) = Header::from_bytes("Content-Type", reply.content_type) {
+        response = response.with_header(header);
+    }
+    request.respond(response).ok();
+}
+
+/// The token comes as `Authorization: Bearer <token>`, or as `token=` in the query where no
+/// header can be set, like in the source of an image.
+fn authorized(token: &str, bearer: Option<&str>, query: &str) -> bool {
+    let given = bearer.map(ToString::to_string).or_else(|| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+            .map(|v| percent_decode_str(v).decode_utf8_lossy().to_string())
+    });
+    given.is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+}
+
+/// Compares without returning early, so the time taken doesn't tell how much of a guess was
+/// right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Body {
+    paths: Vec<String>,
+    position: Option<u64>,
+    offset: Option<i64>,
+    volume: Option<i32>,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    file: String,
+    title: String,
+    artist: String,
+    album: String,
+    duration: u64,
+}
+
+#[derive(Serialize)]
+struct QueueView {
+    current: Option<Entry>,
+    tracks: Vec<Entry>,
+    loop_mode: Loop,
+}
+
+fn route(db: &mut DataBase, method: &str, path: &str, body: &str) -> Result<Reply> {
+    let segments: Vec<String> = path
+        .trim_start_matches("/api/")
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| percent_decode_str(s).decode_utf8_lossy().to_string())
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let body: Body = if body.trim().is_empty() {
+        Body::default()
+    } else {
+        serde_json::from_str(body).context("invalid json")?
+    };
+
+    let request = match (method, segments.as_slice()) {
+        ("GET", ["status"]) => return Reply::json(&connect()?.1),
+        ("GET", ["queue"]) => {
+            let (_, _, queue) = connect()?;
+            return Reply::json(&QueueView {
+                current: queue.current.as_ref().map(|file| entry(db, file)),
+                tracks: queue.tracks.iter().map(|file| entry(db, file)).collect(),
+                loop_mode: queue.loop_mode,
+            });
+        }
+        ("GET", ["cover"]) => return Ok(cover(&connect()?.1)),
+        ("GET", ["library", criteria]) => {
+            return Reply::json(&db.get_criterias(&criteria_from(criteria)?));
+        }
+        ("GET", ["library", criteria, value]) => {
+            let tracks = db.get_record_by_criteria(value, &criteria_from(criteria)?)?;
+            let entries: Vec<Entry> = tracks.iter().map(Entry::from).collect();
+            return Reply::json(&entries);
+        }
+        ("POST", ["play"]) => Request::Play,
+        ("POST", ["pause"]) => Request::Pause,
+        ("POST", ["toggle"]) => Request::TogglePause,
+        ("POST", ["stop"]) => Request::Stop,
+        ("POST", ["next"]) => Request::Skip,
+        ("POST", ["previous"]) => Request::Previous,
+        ("POST", ["seek"]) => match (body.position, body.offset) {
+            (Some(position), _) => Request::SeekTo(position),
+            (None, Some(offset)) => Request::Seek(offset),
+            (None, None) => bail!("position or offset is required"),
+        },
+        ("POST", ["volume"]) => {
+            let volume = body.volume.ok_or_else(|| anyhow!("volume is required"))?;
+            Request::SetVolume(volume.clamp(0, 100))
+        }
+        ("POST", ["queue"]) => Request::Add(collect_files(&body.paths)?),
+        ("DELETE", ["queue", index]) => {
+            let (_, _, mut queue) = connect()?;
+            let index: usize = index.parse().context("invalid index")?;
+            if index >= queue.tracks.len() {
+                bail!("no track {index} in the queue");
+            }
+            queue.tracks.remove(index);
+            Request::SetQueue(queue)
+        }
+        _ => return Ok(Reply::error(404, "not found")),
+    };
+    send(&connect()?.0, &request)?;
+    Ok(Reply::done())
+}
+
+fn connect() -> Result<(UnixStream, State, Queue)> {
+    let stream = UnixStream::connect(socket_path()?)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let (state, queue) = greeting(&stream)?;
+    Ok((stream, state, queue))
+}
+
+fn criteria_from(name: &str) -> Result<SearchCriteria> {
+    match name {
+        "artist" => Ok(SearchCriteria::Artist),
+        "album" => Ok(SearchCriteria::Album),
+        "genre" => Ok(SearchCriteria::Genre),
+        "directory" => Ok(SearchCriteria::Directory),
+        _ => bail!("unknown criteria {name}, use artist, album, genre or directory"),
+    }
+}
+
+fn entry(db: &mut DataBase, file: &String) -> Entry {
+    db.get_record_by_path(file).map_or_else(
+        |_| Entry {
+            file: file.clone(),
+            title: Path::new(file)
+                .file_stem()
+                .map_or_else(|| file.clone(), |s| s.to_string_lossy().to_string()),
+            artist: String::new(),
+            album: String::new(),
+            duration: 0,
+        },
+        |record| Entry::from(&record),
+    )
+}
+
+impl From<&TrackForDB> for Entry {
+    fn from(record: &TrackForDB) -> Self {
+        Self {
+            file: record.file.clone(),
+            title: record.title.clone(),
+            artist: record.artist.clone(),
+            album: record.album.clone(),
+            duration: record.duration.as_secs(),
+        }
+    }
+}
+
+/// The picture embedded in the current track.
+fn cover(state: &State) -> Reply {
+    let picture = state
+        .file
+        .as_ref()
+        .filter(|file| Path::new(file).exists())
+        .and_then(|file| Track::read_from_path(file, false).ok())
+        .and_then(|track| track.picture().cloned());
+    let Some(picture) = picture else {
+        return Reply::error(404, "no cover");
+    };
+    Reply {
+        status: 200,
+        content_type: picture.mime_type().as_str().to_string(),
+        body: picture.data().to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorized() {
+        assert!(authorized("a b", Some("a b"), ""));
+        assert!(authorized("a b", None, "x=1&token=a%20b"));
+        assert!(!authorized("a b", Some("a"), "token=a%20b"));
+        assert!(!authorized("a b", None, "x=1"));
+    }
+}
@@ -139,6 +139,7 @@ fn parse_song_info(v: &Value) -> Option<SongTag> {
         lang_ext: Some("kugou".to_string()),
         service_provider: Some(ServiceProvider::Kugou),
         lyric_id: Some(v.get("hash")?.as_str()?.to_owned()),
+        lyric: None,
         url: Some(url),
         album_id: Some(v.get("album_id")?.as_str()?.to_owned()),
     })
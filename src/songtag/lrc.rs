@@ -36,6 +36,8 @@
 // [al:Hits Of The 60's - Vol. 2 – Oldies]
 // [00:12.00]Lyrics beginning ...
 // [00:15.30]Some more lyrics ...
+// Enhanced lrc adds word timing inside a line:
+// [00:12.00]<00:12.00>Lyrics <00:12.50>beginning <00:13.10>...
 use anyhow::Result;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -49,6 +51,7 @@ lazy_static! {
     static ref LINE_STARTS_WITH_RE: Regex =
         Regex::new("^\\[([^\x00-\x08\x0A-\x1F\x7F\\[\\]:]*):([^\x00-\x08\x0A-\x1F\x7F\\[\\]]*)\\]")
             .unwrap();
+    static ref WORD_TIME_RE: Regex = Regex::new(r"<(\d+:\d+\.\d+)>").unwrap();
 }
 
 #[derive(Clone)]
@@ -62,13 +65,19 @@ pub struct Lyric {
 pub struct UnsyncedCaption {
     time_stamp: i64,
     text: String,
+    words: Vec<TimedWord>, // enhanced lrc word timing, empty for plain lines
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimedWord {
+    pub time_stamp: i64,
+    pub text: String,
 }
 
 const EOL: &str = "\n";
 
 impl Lyric {
-    // GetText will fetch lyric by time in seconds
-    pub fn get_text(&self, mut time: i64) -> Option<String> {
+    pub fn get_index(&self, mut time: i64) -> Option<usize> {
         if self.unsynced_captions.is_empty() {
             return None;
         };
@@ -80,42 +89,113 @@ impl Lyric {
             adjusted_time = 0;
         }
 
-        time = adjusted_time;
+        time = adjusted_time.abs();
 
-        let mut text = self.unsynced_captions.get(0)?.text.clone();
-        for v in &self.unsynced_captions {
+        let mut index: usize = 0;
+        for (i, v) in self.unsynced_captions.iter().enumerate() {
             if time >= v.time_stamp {
-                text = v.text.clone();
+                index = i;
             } else {
                 break;
             }
         }
-        Some(text)
+        Some(index)
     }
 
-    pub fn get_index(&self, mut time: i64) -> Option<usize> {
-        if self.unsynced_captions.is_empty() {
+    /// Index and words of the line being sung at `time` in milliseconds, together with the
+    /// index of the word being sung. Unlike `get_index` there is no lead time, as the words are
+    /// highlighted while they are sung. Returns None for lines without word timing.
+    pub fn get_words(&self, time: i64) -> Option<(usize, &[TimedWord], usize)> {
+        let time = (time + self.offset).max(0);
+        let (line, caption) = self
+            .unsynced_captions
+            .iter()
+            .enumerate()
+            .take_while(|(_, c)| time >= c.time_stamp)
+            .last()?;
+        if caption.words.is_empty() {
             return None;
-        };
+        }
+        let index = caption
+            .words
+            .iter()
+            .take_while(|w| time >= w.time_stamp)
+            .count()
+            .saturating_sub(1);
+        Some((line, &caption.words, index))
+    }
 
-        // here we want to show lyric 2 second earlier
-        let mut adjusted_time = time * 1000 + 2000;
-        adjusted_time += self.offset;
-        if adjusted_time < 0 {
-            adjusted_time = 0;
+    pub fn lines(&self) -> Vec<String> {
+        self.unsynced_captions
+            .iter()
+            .map(|c| c.text.clone())
+            .collect()
+    }
+
+    /// Time in milliseconds at which the line at `index` is sung, with the offset applied.
+    pub fn line_time(&self, index: usize) -> Option<i64> {
+        let caption = self.unsynced_captions.get(index)?;
+        Some((caption.time_stamp - self.offset).max(0))
+    }
+
+    pub fn has_word_timing(&self) -> bool {
+        self.unsynced_captions.iter().any(|c| !c.words.is_empty())
+    }
+
+    /// Build from the content of an ID3 SYLT frame, with time stamps in milliseconds. Entries
+    /// starting with a line break begin a new line; without any line break every entry is a
+    /// line of its own.
+    pub fn from_sylt(content: &[(u32, String)]) -> Self {
+        let is_new_line = |text: &str| text.starts_with('\n') || text.starts_with('\r');
+        let word_timing = content.iter().skip(1).any(|(_, text)| is_new_line(text));
+        let mut unsynced_captions: Vec<UnsyncedCaption> = Vec::new();
+        for (time_stamp, text) in content {
+            let time_stamp = i64::from(*time_stamp);
+            let new_line = !word_timing || is_new_line(text);
+            let text = text.trim_start_matches(['\r', '\n']).to_string();
+            match unsynced_captions.last_mut() {
+                Some(caption) if !new_line => {
+                    caption.text += &text;
+                    caption.words.push(TimedWord { time_stamp, text });
+                }
+                _ => unsynced_captions.push(UnsyncedCaption {
+                    time_stamp,
+                    words: if word_timing {
+                        vec![TimedWord {
+                            time_stamp,
+                            text: text.clone(),
+                        }]
+                    } else {
+                        Vec::new()
+                    },
+                    text,
+                }),
+            }
         }
+        unsynced_captions.sort_by_key(|c| c.time_stamp);
 
-        time = adjusted_time.abs();
+        Self {
+            offset: 0,
+            lang_extension: Some(String::new()),
+            unsynced_captions,
+        }
+    }
 
-        let mut index: usize = 0;
-        for (i, v) in self.unsynced_captions.iter().enumerate() {
-            if time >= v.time_stamp {
-                index = i;
-            } else {
-                break;
+    /// Content for an ID3 SYLT frame: one entry per word, or per line for lines without word
+    /// timing, with every line after the first starting with a line break. The offset is
+    /// applied to the time stamps.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn as_sylt(&self) -> Vec<(u32, String)> {
+        let stamp = |time_stamp: i64| (time_stamp - self.offset).max(0) as u32;
+        let mut content = Vec::new();
+        for (i, caption) in self.unsynced_captions.iter().enumerate() {
+            let line_break = if i == 0 { "" } else { "\n" };
+            for (j, word) in caption.timed_words().into_iter().enumerate() {
+                let prefix = if j == 0 { line_break } else { "" };
+                content.push((stamp(word.time_stamp), format!("{prefix}{}", word.text)));
             }
         }
-        Some(index)
+        content
     }
 
     pub fn adjust_offset(&mut self, time: i64, offset: i64) {
@@ -132,6 +212,9 @@ impl Lyric {
                     Ordering::Greater | Ordering::Equal => adjusted_time_stamp,
                     Ordering::Less => 0,
                 };
+                for w in &mut v.words {
+                    w.time_stamp = (w.time_stamp + offset).max(0);
+                }
             }
         };
         // we sort the captions by time_stamp. This is to fix some lyrics downloaded are not sorted
@@ -161,8 +244,17 @@ impl Lyric {
             }
             if let Some(item) = unsynced_captions.get(i - offset) {
                 if v.time_stamp - item.time_stamp < 2000 {
-                    unsynced_captions[i - offset].text += "  ";
-                    unsynced_captions[i - offset].text += v.text.as_ref();
+                    let merged = &mut unsynced_captions[i - offset];
+                    if !merged.words.is_empty() || !v.words.is_empty() {
+                        let mut words = merged.timed_words();
+                        if let Some(last) = words.last_mut() {
+                            last.text += "  ";
+                        }
+                        words.append(&mut v.timed_words());
+                        merged.words = words;
+                    }
+                    merged.text += "  ";
+                    merged.text += v.text.as_ref();
                     unsynced_captions.remove(i - offset + 1);
                     offset += 1;
                 }
@@ -185,12 +277,65 @@ impl UnsyncedCaption {
         let text = line
             .drain(line.find(']').ok_or(())? + 1..)
             .collect::<String>();
+        let time_stamp = time_stamp.try_into().unwrap_or(0);
+        let words = Self::parse_words(&text, time_stamp);
+        let text = if words.is_empty() {
+            text
+        } else {
+            words.iter().map(|w| w.text.as_str()).collect()
+        };
         Ok(Self {
-            time_stamp: time_stamp.try_into().unwrap_or(0),
+            time_stamp,
             text,
+            words,
         })
     }
 
+    // Hello <00:12.50>world<00:13.00>, text before the first stamp starts at the line stamp.
+    fn parse_words(text: &str, line_time_stamp: i64) -> Vec<TimedWord> {
+        let mut words = Vec::new();
+        let mut time_stamp = line_time_stamp;
+        let mut start = 0;
+        for cap in WORD_TIME_RE.captures_iter(text) {
+            let (Some(whole), Some(time)) = (cap.get(0), cap.get(1)) else {
+                continue;
+            };
+            let Ok(time) = Self::parse_time(time.as_str()) else {
+                continue;
+            };
+            let word = &text[start..whole.start()];
+            if !word.is_empty() {
+                words.push(TimedWord {
+                    time_stamp,
+                    text: word.to_string(),
+                });
+            }
+            time_stamp = time.try_into().unwrap_or(0);
+            start = whole.end();
+        }
+        if start == 0 {
+            return words;
+        }
+        if start < text.len() {
+            words.push(TimedWord {
+                time_stamp,
+                text: text[start..].to_string(),
+            });
+        }
+        words
+    }
+
+    // Word timing of the line, a plain line counts as a single word.
+    fn timed_words(&self) -> Vec<TimedWord> {
+        if self.words.is_empty() {
+            return vec![TimedWord {
+                time_stamp: self.time_stamp,
+                text: self.text.clone(),
+            }];
+        }
+        self.words.clone()
+    }
+
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     fn parse_time(string: &str) -> Result<u64, ()> {
         //mm:ss.xx or mm:ss.xxx
@@ -216,11 +361,16 @@ impl UnsyncedCaption {
     }
 
     fn as_lrc(&self) -> String {
-        let line = format!(
-            "[{}]{}",
-            time_lrc(self.time_stamp.try_into().unwrap_or(0)),
-            self.text
-        );
+        let mut line = format!("[{}]", time_lrc(self.time_stamp.try_into().unwrap_or(0)));
+        if self.words.is_empty() {
+            line += &self.text;
+        }
+        for w in &self.words {
+            line.push('<');
+            line += &time_lrc(w.time_stamp.try_into().unwrap_or(0));
+            line.push('>');
+            line += &w.text;
+        }
         line + EOL
     }
 }
@@ -230,9 +380,9 @@ fn time_lrc(time_stamp: u64) -> String {
     let _h = time_duration.as_secs() / 3600;
     let m = (time_duration.as_secs() / 60) % 60;
     let s = time_duration.as_secs() % 60;
-    let ms = time_duration.as_millis() % 60;
+    let cs = time_duration.subsec_millis() / 10;
 
-    let res = format!("{m:02}:{s:02}.{ms:02}");
+    let res = format!("{m:02}:{s:02}.{cs:02}");
     res
 }
 
@@ -292,3 +442,51 @@ impl FromStr for Lyric {
         Ok(lyric)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enhanced_lrc_words() {
+        let lyric =
+            Lyric::from_str("[00:10.00]<00:10.00>Hello <00:10.50>world\n[00:20.00]Plain line")
+                .unwrap();
+        assert!(lyric.has_word_timing());
+        assert_eq!(lyric.unsynced_captions[0].text, "Hello world");
+
+        let (line, words, index) = lyric.get_words(10_600).unwrap();
+        assert_eq!(line, 0);
+        assert_eq!(words.len(), 2);
+        assert_eq!(index, 1);
+        assert_eq!(words[1].time_stamp, 10_500);
+        assert_eq!(lyric.get_words(10_100).unwrap().2, 0);
+        assert!(lyric.get_words(20_100).is_none());
+
+        assert_eq!(
+            lyric.as_lrc_text(),
+            "[00:10.00]<00:10.00>Hello <00:10.50>world\n[00:20.00]Plain line\n"
+        );
+    }
+
+    #[test]
+    fn test_sylt_round_trip() {
+        let content = vec![
+            (1000, "One ".to_string()),
+            (1500, "two".to_string()),
+            (4000, "\nThree".to_string()),
+        ];
+        let lyric = Lyric::from_sylt(&content);
+        assert_eq!(lyric.unsynced_captions.len(), 2);
+        assert_eq!(lyric.unsynced_captions[0].text, "One two");
+        assert_eq!(lyric.get_words(1600).unwrap().2, 1);
+        assert_eq!(lyric.lines(), vec!["One two", "Three"]);
+        assert_eq!(lyric.line_time(1), Some(4000));
+        assert_eq!(lyric.as_sylt(), content);
+
+        // line level SYLT without line breaks
+        let lyric = Lyric::from_sylt(&[(1000, "One".to_string()), (4000, "Two".to_string())]);
+        assert!(!lyric.has_word_timing());
+        assert_eq!(lyric.get_index(2), Some(1));
+    }
+}
@@ -0,0 +1,107 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+mod model;
+
+use anyhow::{anyhow, Result};
+use model::to_song_info;
+use std::time::Duration;
+use ureq::{Agent, AgentBuilder};
+
+/// The track lyrics are searched for. The duration picks the recording among songs with the
+/// same name, zero matches any.
+pub struct Query {
+    pub artist: String,
+    pub title: String,
+    pub duration: Duration,
+}
+
+pub struct Api {
+    client: Agent,
+    base_url: String,
+}
+
+impl Api {
+    pub fn new(base_url: &str) -> Self {
+        let client = AgentBuilder::new().timeout(Duration::from_secs(10)).build();
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // Search results already carry the lyrics, so there is no separate lyric request.
+    pub fn search(&self, query: &Query) -> Result<String> {
+        let mut request = self
+            .client
+            .get(&format!("{}/api/search", self.base_url))
+            .set("User-Agent", "termusic");
+        if query.title.is_empty() {
+            request = request.query("q", &query.artist);
+        } else {
+            request = request.query("track_name", &query.title);
+            if !query.artist.is_empty() {
+                request = request.query("artist_name", &query.artist);
+            }
+        }
+        let result = request.call()?.into_string()?;
+
+        let songtag_vec = to_song_info(&result, query).ok_or_else(|| anyhow!("Search Error"))?;
+        let songtag_string = serde_json::to_string(&songtag_vec)?;
+        Ok(songtag_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::songtag::SongTag;
+    use crate::test_utils::mock_server;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_search_matches_artist_and_duration() {
+        let base_url = mock_server(
+            r#"[
+            {"id":1,"trackName":"Song","artistName":"Band","albumName":"Live","duration":250.0,
+             "instrumental":false,"plainLyrics":"live","syncedLyrics":null},
+            {"id":2,"trackName":"Song","artistName":"Band","albumName":"Album","duration":181.0,
+             "instrumental":false,"plainLyrics":"plain","syncedLyrics":"[00:01.00]synced"},
+            {"id":3,"trackName":"Song","artistName":"Other","albumName":"Album","duration":180.0,
+             "instrumental":false,"plainLyrics":"other","syncedLyrics":null}
+            ]"#,
+        );
+        let query = Query {
+            artist: "band".to_string(),
+            title: "Song".to_string(),
+            duration: Duration::from_mins(3),
+        };
+        let result = Api::new(&format!("{base_url}/")).search(&query).unwrap();
+        let tags: Vec<SongTag> = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].album(), Some("Album"));
+        assert_eq!(tags[0].fetch_lyric().unwrap(), "[00:01.00]synced");
+    }
+}
@@ -0,0 +1,82 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::super::{ServiceProvider, SongTag};
+use super::Query;
+use serde_json::Value;
+
+// Lrclib allows 2 seconds of difference when matching by duration.
+const DURATION_TOLERANCE: f64 = 2.0;
+
+pub fn to_song_info(json: &str, query: &Query) -> Option<Vec<SongTag>> {
+    let value = serde_json::from_str::<Value>(json).ok()?;
+    let array = value.as_array()?;
+    let artist = query.artist.to_lowercase();
+    let duration = query.duration.as_secs_f64();
+    let mut vec: Vec<SongTag> = array
+        .iter()
+        .filter(|v| {
+            query.title.is_empty()
+                || v.get("artistName")
+                    .and_then(Value::as_str)
+                    .is_some_and(|a| a.to_lowercase().contains(&artist))
+        })
+        .filter(|v| {
+            duration == 0.0
+                || v.get("duration")
+                    .and_then(Value::as_f64)
+                    .is_some_and(|d| (d - duration).abs() <= DURATION_TOLERANCE)
+        })
+        .filter_map(parse_song_info)
+        .collect();
+    // synced lyrics first
+    vec.sort_by_key(|s| !s.lyric.as_deref().is_some_and(|l| l.starts_with('[')));
+    Some(vec)
+}
+
+fn parse_song_info(v: &Value) -> Option<SongTag> {
+    let lyric = ["syncedLyrics", "plainLyrics"]
+        .iter()
+        .find_map(|key| v.get(key)?.as_str().filter(|l| !l.is_empty()))?;
+    let id = v.get("id")?.as_u64()?.to_string();
+
+    Some(SongTag {
+        song_id: Some(id.clone()),
+        title: Some(v.get("trackName")?.as_str()?.to_owned()),
+        artist: v
+            .get("artistName")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+        album: v
+            .get("albumName")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+        pic_id: None,
+        lang_ext: Some("lrclib".to_string()),
+        service_provider: Some(ServiceProvider::Lrclib),
+        lyric_id: Some(id),
+        lyric: Some(lyric.to_owned()),
+        url: None,
+        album_id: None,
+    })
+}
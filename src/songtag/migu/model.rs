@@ -102,6 +102,7 @@ fn parse_song_info(v: &Value) -> Option<SongTag> {
         lang_ext: Some("migu".to_string()),
         service_provider: Some(ServiceProvider::Migu),
         lyric_id: Some(v.get("copyrightId")?.as_str()?.to_owned()),
+        lyric: None,
         url: Some(url),
         album_id: Some(album_id),
     })
@@ -24,14 +24,18 @@
 pub mod encrypt;
 mod kugou;
 pub mod lrc;
+pub mod lrclib;
 mod migu;
+pub mod musicbrainz;
 mod netease;
+pub mod pattern;
 
+use crate::config::{AudioCodec, DownloadProfile};
 use crate::ui::{DLMsg, Msg, SearchLyricState};
 use crate::utils::get_parent_folder;
 use anyhow::{anyhow, bail, Result};
 use lofty::id3::v2::{Frame, FrameFlags, FrameValue, ID3v2Tag, LanguageFrame};
-use lofty::{Accessor, Picture, TagExt, TextEncoding};
+use lofty::{Accessor, FileType, ItemKey, ItemValue, Picture, TagExt, TagItem, TextEncoding};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver, Sender};
@@ -48,6 +52,8 @@ pub struct SongTag {
     service_provider: Option<ServiceProvider>,
     song_id: Option<String>,
     lyric_id: Option<String>,
+    /// Lyric included in the search result, so it needs no request of its own.
+    lyric: Option<String>,
     url: Option<String>,
     pic_id: Option<String>,
     album_id: Option<String>,
@@ -60,6 +66,7 @@ pub enum ServiceProvider {
     Netease,
     Kugou,
     Migu,
+    Lrclib,
 }
 
 impl std::fmt::Display for ServiceProvider {
@@ -68,13 +75,19 @@ impl std::fmt::Display for ServiceProvider {
             Self::Netease => "Netease",
             Self::Kugou => "Kugou",
             Self::Migu => "Migu",
+            Self::Lrclib => "LRCLIB",
         };
         write!(f, "{service_provider}")
     }
 }
 
-// Search function of 3 servers. Run in parallel to get results faster.
-pub fn search(search_str: &str, tx_tageditor: Sender<SearchLyricState>) {
+// Search function of 3 servers, and lrclib when a query for it is given. Run in parallel to get
+// results faster.
+pub fn search(
+    search_str: &str,
+    lrclib: Option<(String, lrclib::Query)>,
+    tx_tageditor: Sender<SearchLyricState>,
+) {
     let mut results: Vec<SongTag> = Vec::new();
     let (tx, rx): (Sender<Vec<SongTag>>, Receiver<Vec<SongTag>>) = mpsc::channel();
 
@@ -100,6 +113,18 @@ pub fn search(search_str: &str, tx_tageditor: Sender<SearchLyricState>) {
         Ok(())
     });
 
+    let tx3 = tx.clone();
+    let handle_lrclib = thread::spawn(move || -> Result<()> {
+        if let Some((base_url, query)) = lrclib {
+            let lrclib_api = lrclib::Api::new(&base_url);
+            if let Ok(results) = lrclib_api.search(&query) {
+                let result_new: Vec<SongTag> = serde_json::from_str(&results)?;
+                tx3.send(result_new).ok();
+            }
+        }
+        Ok(())
+    });
+
     let kugou_api = kugou::Api::new();
     let search_str_kugou = search_str.to_string();
     let handle_kugou = thread::spawn(move || -> Result<()> {
@@ -111,6 +136,12 @@ pub fn search(search_str: &str, tx_tageditor: Sender<SearchLyricState>) {
     });
 
     thread::spawn(move || {
+        if handle_lrclib.join().is_ok() {
+            if let Ok(result_new) = rx.try_recv() {
+                results.extend(result_new);
+            }
+        }
+
         if handle_netease.join().is_ok() {
             if let Ok(result_new) = rx.try_recv() {
                 results.extend(result_new);
@@ -201,6 +232,11 @@ impl SongTag {
                     lyric_string = migu_api.song_lyric(lyric_id)?;
                 }
             }
+            Some(ServiceProvider::Lrclib) => {
+                if let Some(lyric) = &self.lyric {
+                    lyric_string.clone_from(lyric);
+                }
+            }
             None => {}
         }
 
@@ -240,6 +276,9 @@ impl SongTag {
                     bail!("song_id is missing for migu")
                 }
             }
+            Some(ServiceProvider::Lrclib) => {
+                bail!("lrclib provides no pictures")
+            }
             None => {
                 bail!("no servie provider given");
             }
@@ -258,8 +297,13 @@ impl SongTag {
     }
 
     #[allow(clippy::too_many_lines)]
-    pub fn download(&self, file: &str, tx_tageditor: &Sender<Msg>) -> Result<()> {
-        let p_parent = get_parent_folder(file);
+    pub fn download(
+        &self,
+        file: &str,
+        profile: &DownloadProfile,
+        tx_tageditor: &Sender<Msg>,
+    ) -> Result<()> {
+        let p_parent = profile.destination_or(PathBuf::from(get_parent_folder(file)));
         let song_id = self
             .song_id
             .as_ref()
@@ -280,14 +324,17 @@ impl SongTag {
 
         let filename = format!("{artist}-{title}.%(ext)s");
 
-        let args = vec![
+        let mut args = vec![
             Arg::new("--quiet"),
             Arg::new_with_arg("--output", filename.as_ref()),
-            Arg::new("--extract-audio"),
-            Arg::new_with_arg("--audio-format", "mp3"),
         ];
+        args.extend(profile.audio_args());
 
-        let p_full = format!("{p_parent}/{artist}-{title}.mp3");
+        let codec = profile.codec;
+        let p_full = p_parent
+            .join(format!("{artist}-{title}.{}", codec.extension()))
+            .to_string_lossy()
+            .to_string();
         if std::fs::remove_file(Path::new(p_full.as_str())).is_err() {}
 
         let mp3_url = self.url.clone().unwrap_or_else(|| String::from("N/A"));
@@ -303,6 +350,9 @@ impl SongTag {
                     url = netease_api.song_url(song_id)?;
                 }
                 ServiceProvider::Migu => {}
+                ServiceProvider::Lrclib => {
+                    bail!("LRCLIB only provides lyrics, please select another item.")
+                }
                 ServiceProvider::Kugou => {
                     let kugou_api = kugou::Api::new();
                     url = kugou_api.song_url(song_id, &album_id)?;
@@ -314,7 +364,7 @@ impl SongTag {
             bail!("url fetch failed, please try another item.");
         }
 
-        let ytd = YoutubeDL::new(&PathBuf::from(p_parent), args, &url)?;
+        let ytd = YoutubeDL::new(&p_parent, args, &url)?;
 
         let tx = tx_tageditor.clone();
         thread::spawn(move || {
@@ -331,36 +381,18 @@ impl SongTag {
                 Ok(_result) => {
                     tx.send(Msg::Download(DLMsg::DownloadSuccess(url.clone())))
                         .ok();
-                    let mut tag = ID3v2Tag::default();
-
-                    tag.set_title(title.clone());
-                    tag.set_artist(artist);
-                    tag.set_album(album);
-
-                    // safe to unwrap these frames, since the ID is valid
-                    if let Ok(l) = lyric {
-                        tag.insert(
-                            Frame::new(
-                                "USLT",
-                                FrameValue::UnSyncText(LanguageFrame {
-                                    encoding: TextEncoding::UTF8,
-                                    language: *b"chi",
-                                    description: String::from("saved by termusic."),
-                                    content: l,
-                                }),
-                                FrameFlags::default(),
-                            )
-                            .unwrap(),
-                        );
-                    }
-
-                    if let Ok(picture) = photo {
-                        tag.insert_picture(picture);
-                    }
-
                     let file = p_full.as_str();
-
-                    if tag.save_to_path(file).is_ok() {
+                    let saved = write_download_tag(
+                        file,
+                        codec,
+                        &title,
+                        &artist,
+                        &album,
+                        lyric.ok(),
+                        photo.ok(),
+                    );
+
+                    if saved.is_ok() {
                         sleep(Duration::from_secs(10));
                         tx.send(Msg::Download(DLMsg::DownloadCompleted(
                             url.clone(),
@@ -394,3 +426,60 @@ impl SongTag {
         Ok(())
     }
 }
+
+// mp3 gets an id3v2 tag with the lyric in a USLT frame, the other codecs get their primary tag
+// with the lyric as a plain item.
+fn write_download_tag(
+    file: &str,
+    codec: AudioCodec,
+    title: &str,
+    artist: &str,
+    album: &str,
+    lyric: Option<String>,
+    picture: Option<Picture>,
+) -> Result<()> {
+    if codec == AudioCodec::Mp3 {
+        let mut tag = ID3v2Tag::default();
+        tag.set_title(title.to_string());
+        tag.set_artist(artist.to_string());
+        tag.set_album(album.to_string());
+
+        // safe to unwrap these frames, since the ID is valid
+        if let Some(l) = lyric {
+            tag.insert(
+                Frame::new(
+                    "USLT",
+                    FrameValue::UnSyncText(LanguageFrame {
+                        encoding: TextEncoding::UTF8,
+                        language: *b"chi",
+                        description: String::from("saved by termusic."),
+                        content: l,
+                    }),
+                    FrameFlags::default(),
+                )
+                .unwrap(),
+            );
+        }
+
+        if let Some(picture) = picture {
+            tag.insert_picture(picture);
+        }
+        tag.save_to_path(file)?;
+        return Ok(());
+    }
+
+    let file_type = FileType::from_ext(codec.extension())
+        .ok_or_else(|| anyhow!("unsupported codec {codec}"))?;
+    let mut tag = lofty::Tag::new(file_type.primary_tag_type());
+    tag.set_title(title.to_string());
+    tag.set_artist(artist.to_string());
+    tag.set_album(album.to_string());
+    if let Some(l) = lyric {
+        tag.push_item(TagItem::new(ItemKey::Lyrics, ItemValue::Text(l)));
+    }
+    if let Some(picture) = picture {
+        tag.push_picture(picture);
+    }
+    tag.save_to_path(file)?;
+    Ok(())
+}
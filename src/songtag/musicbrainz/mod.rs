@@ -0,0 +1,275 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+mod model;
+
+use crate::track::MusicBrainzIds;
+use anyhow::{anyhow, bail, Result};
+use lofty::{Picture, PictureType};
+use model::{to_recording_ids, to_releases};
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+use std::thread::sleep;
+use std::time::Duration;
+use ureq::{Agent, AgentBuilder};
+
+const USER_AGENT: &str = concat!(
+    "termusic/",
+    env!("CARGO_PKG_VERSION"),
+    " ( https://github.com/tramhao/termusic )"
+);
+// Recordings looked up for a fingerprint, as MusicBrainz allows one request per second.
+const MAX_FINGERPRINT_RECORDINGS: usize = 3;
+// Milliseconds the recording length may differ from the track.
+const DURATION_TOLERANCE: u128 = 3000;
+
+/// What the track is looked up by. Empty fields and a zero duration are left out of the query.
+pub struct Query {
+    pub artist: String,
+    pub title: String,
+    pub album: String,
+    pub duration: Duration,
+}
+
+/// A recording on one of its releases, the candidate offered in the tag editor.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Release {
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub year: Option<u32>,
+    pub track_number: Option<u32>,
+    /// Match score from 0 to 100.
+    pub score: u8,
+    pub ids: MusicBrainzIds,
+}
+
+/// Endpoints of the musicbrainz web service, acoustid and the Cover Art Archive.
+pub struct Api {
+    client: Agent,
+    base_url: String,
+    cover_art_url: String,
+    acoustid_url: String,
+    acoustid_key: String,
+}
+
+impl Api {
+    pub fn new(
+        base_url: &str,
+        cover_art_url: &str,
+        acoustid_url: &str,
+        acoustid_key: &str,
+    ) -> Self {
+        let client = AgentBuilder::new()
+            .timeout(Duration::from_secs(10))
+            .user_agent(USER_AGENT)
+            .build();
+
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            cover_art_url: cover_art_url.trim_end_matches('/').to_string(),
+            acoustid_url: acoustid_url.trim_end_matches('/').to_string(),
+            acoustid_key: acoustid_key.to_string(),
+        }
+    }
+
+    /// Search recordings by the tags of the query, releases of the best matches first.
+    pub fn search(&self, query: &Query) -> Result<Vec<Release>> {
+        let mut terms = Vec::new();
+        for (field, value) in [
+            ("recording", &query.title),
+            ("artist", &query.artist),
+            ("release", &query.album),
+        ] {
+            if !value.trim().is_empty() {
+                terms.push(format!("{field}:\"{}\"", escape(value.trim())));
+            }
+        }
+        let duration = query.duration.as_millis();
+        if duration > 0 {
+            terms.push(format!(
+                "dur:[{} TO {}]",
+                duration.saturating_sub(DURATION_TOLERANCE),
+                duration + DURATION_TOLERANCE
+            ));
+        }
+        if terms.is_empty() {
+            bail!("nothing to look up, fill in artist or title");
+        }
+
+        let result = self
+            .client
+            .get(&format!("{}/ws/2/recording", self.base_url))
+            .query("query", &terms.join(" AND "))
+            .query("fmt", "json")
+            .query("limit", "25")
+            .call()?
+            .into_string()?;
+        let value: Value = serde_json::from_str(&result)?;
+        let recordings = value
+            .get("recordings")
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("Search Error"))?;
+        Ok(recordings.iter().flat_map(to_releases).collect())
+    }
+
+    /// Identify the file by its Chromaprint fingerprint through acoustid. Needs an acoustid
+    /// api key and `fpcalc` in the path.
+    pub fn search_fingerprint(&self, file: &Path) -> Result<Vec<Release>> {
+        if self.acoustid_key.is_empty() {
+            bail!("no AcoustID api key configured");
+        }
+        let (duration, fingerprint) = fingerprint(file)?;
+        let result = self
+            .client
+            .post(&format!("{}/v2/lookup", self.acoustid_url))
+            .send_form(&[
+                ("client", &self.acoustid_key),
+                ("meta", "recordingids"),
+                ("duration", &duration.to_string()),
+                ("fingerprint", &fingerprint),
+            ])?
+            .into_string()?;
+
+        let mut releases = Vec::new();
+        let ids = to_recording_ids(&result).ok_or_else(|| anyhow!("AcoustID lookup error"))?;
+        for (i, (id, score)) in ids.iter().take(MAX_FINGERPRINT_RECORDINGS).enumerate() {
+            if i > 0 {
+                sleep(Duration::from_secs(1));
+            }
+            let result = self
+                .client
+                .get(&format!("{}/ws/2/recording/{id}", self.base_url))
+                .query("inc", "artists+releases+release-groups+media")
+                .query("fmt", "json")
+                .call()?
+                .into_string()?;
+            let value: Value = serde_json::from_str(&result)?;
+            releases.extend(to_releases(&value).into_iter().map(|mut release| {
+                release.score = *score;
+                release
+            }));
+        }
+        Ok(releases)
+    }
+
+    /// Front cover of the release, or of its release group when the release has none.
+    pub fn cover_art(&self, ids: &MusicBrainzIds) -> Result<Picture> {
+        let urls = [
+            ("release", &ids.release),
+            ("release-group", &ids.release_group),
+        ];
+        for (entity, id) in urls {
+            let Some(id) = id else {
+                continue;
+            };
+            let url = format!("{}/{entity}/{id}/front-500", self.cover_art_url);
+            if let Ok(response) = self.client.get(&url).call() {
+                let mut picture = Picture::from_reader(&mut response.into_reader())?;
+                picture.set_pic_type(PictureType::CoverFront);
+                return Ok(picture);
+            }
+        }
+        bail!("no cover art found")
+    }
+}
+
+// Quote marks and backslashes end a quoted lucene term.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Duration in seconds and Chromaprint fingerprint, as calculated by fpcalc.
+fn fingerprint(file: &Path) -> Result<(u64, String)> {
+    let output = Command::new("fpcalc")
+        .arg("-json")
+        .arg(file)
+        .output()
+        .map_err(|e| anyhow!("fpcalc is needed for fingerprints: {e}"))?;
+    if !output.status.success() {
+        bail!("fpcalc failed: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    let value: Value = serde_json::from_slice(&output.stdout)?;
+    let duration = value
+        .get("duration")
+        .and_then(Value::as_f64)
+        .ok_or_else(|| anyhow!("no duration from fpcalc"))?;
+    let fingerprint = value
+        .get("fingerprint")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("no fingerprint from fpcalc"))?;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Ok((duration.round() as u64, fingerprint.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mock_server;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_search_releases() {
+        let base_url = mock_server(
+            r#"{"recordings":[{"id":"rec-1","score":98,"title":"Song","length":181000,
+            "artist-credit":[{"name":"Band","joinphrase":" & ","artist":{"id":"art-1","name":"Band"}},
+                             {"name":"Guest","artist":{"id":"art-2","name":"Guest"}}],
+            "releases":[{"id":"rel-1","title":"Album","date":"2001-05-01",
+                "artist-credit":[{"name":"Band","artist":{"id":"art-1","name":"Band"}}],
+                "release-group":{"id":"rg-1"},
+                "media":[{"position":1,"track":[{"number":"3","position":3}]}]},
+                {"id":"rel-2","title":"Best Of","date":"","release-group":{"id":"rg-2"},
+                "media":[{"position":1,"track":[{"number":"A2","position":7}]}]}]}]}"#,
+        );
+        let api = Api::new(&base_url, "", "", "");
+        let query = Query {
+            artist: "Band".to_string(),
+            title: "Song".to_string(),
+            album: String::new(),
+            duration: Duration::from_mins(3),
+        };
+        let releases = api.search(&query).unwrap();
+
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].artist.as_deref(), Some("Band & Guest"));
+        assert_eq!(releases[0].album_artist.as_deref(), Some("Band"));
+        assert_eq!(releases[0].year, Some(2001));
+        assert_eq!(releases[0].track_number, Some(3));
+        assert_eq!(releases[0].score, 98);
+        assert_eq!(
+            releases[0].ids,
+            MusicBrainzIds {
+                recording: Some("rec-1".to_string()),
+                release: Some("rel-1".to_string()),
+                release_group: Some("rg-1".to_string()),
+                artist: Some("art-1".to_string()),
+            }
+        );
+        assert_eq!(releases[1].album_artist.as_deref(), Some("Band & Guest"));
+        assert_eq!(releases[1].year, None);
+        assert_eq!(releases[1].track_number, Some(7));
+    }
+}
@@ -0,0 +1,141 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::Release;
+use crate::track::MusicBrainzIds;
+use serde_json::Value;
+
+// Artist credit joined as credited, like `Band & Guest`, and the id of the first artist.
+fn artist_credit(value: &Value) -> Option<(String, Option<String>)> {
+    let credits = value.get("artist-credit")?.as_array()?;
+    let mut name = String::new();
+    for credit in credits {
+        name += credit
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        name += credit
+            .get("joinphrase")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+    }
+    let id = credits
+        .first()
+        .and_then(|c| c.get("artist")?.get("id")?.as_str())
+        .map(str::to_owned);
+    Some((name, id)).filter(|(name, _)| !name.is_empty())
+}
+
+// Search results list the track as `track`, lookups as `tracks`.
+fn track_number(release: &Value) -> Option<u32> {
+    let medium = release.get("media")?.as_array()?.first()?;
+    let track = ["track", "tracks"]
+        .iter()
+        .find_map(|key| medium.get(key)?.as_array()?.first())?;
+    track
+        .get("number")
+        .and_then(Value::as_str)
+        .and_then(|n| n.parse().ok())
+        .or_else(|| track.get("position")?.as_u64()?.try_into().ok())
+}
+
+/// One candidate for each release of the recording.
+pub fn to_releases(recording: &Value) -> Vec<Release> {
+    let Some(recording_id) = recording.get("id").and_then(Value::as_str) else {
+        return Vec::new();
+    };
+    let (artist, artist_id) = artist_credit(recording).unzip();
+    let score = recording
+        .get("score")
+        .and_then(Value::as_u64)
+        .and_then(|s| u8::try_from(s).ok())
+        .unwrap_or(100);
+    let base = Release {
+        artist: artist.clone(),
+        title: recording
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+        score,
+        ids: MusicBrainzIds {
+            recording: Some(recording_id.to_owned()),
+            artist: artist_id.flatten(),
+            ..MusicBrainzIds::default()
+        },
+        ..Release::default()
+    };
+
+    let Some(releases) = recording.get("releases").and_then(Value::as_array) else {
+        return vec![base];
+    };
+    releases
+        .iter()
+        .map(|release| {
+            let album_artist = artist_credit(release).map(|(name, _)| name);
+            Release {
+                album: release
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .map(str::to_owned),
+                album_artist: album_artist.or_else(|| artist.clone()),
+                year: release
+                    .get("date")
+                    .and_then(Value::as_str)
+                    .and_then(|d| d.get(..4)?.parse().ok()),
+                track_number: track_number(release),
+                ids: MusicBrainzIds {
+                    release: release.get("id").and_then(Value::as_str).map(str::to_owned),
+                    release_group: release
+                        .get("release-group")
+                        .and_then(|g| g.get("id")?.as_str())
+                        .map(str::to_owned),
+                    ..base.ids.clone()
+                },
+                ..base.clone()
+            }
+        })
+        .collect()
+}
+
+/// Recording ids of an acoustid lookup with the score of their fingerprint match, best first.
+pub fn to_recording_ids(json: &str) -> Option<Vec<(String, u8)>> {
+    let value = serde_json::from_str::<Value>(json).ok()?;
+    if value.get("status")?.as_str()? != "ok" {
+        return None;
+    }
+    let mut ids: Vec<(String, u8)> = Vec::new();
+    for result in value.get("results")?.as_array()? {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let score = (result.get("score").and_then(Value::as_f64).unwrap_or(0.0) * 100.0) as u8;
+        let Some(recordings) = result.get("recordings").and_then(Value::as_array) else {
+            continue;
+        };
+        for id in recordings.iter().filter_map(|r| r.get("id")?.as_str()) {
+            if !ids.iter().any(|(i, _)| i == id) {
+                ids.push((id.to_owned(), score));
+            }
+        }
+    }
+    ids.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    Some(ids)
+}
@@ -165,6 +165,7 @@ fn parse_song_info(v: &Value) -> Option<SongTag> {
         ),
         lang_ext: Some(String::from("netease")),
         lyric_id: Some(v.get("id")?.as_u64()?.to_string()),
+        lyric: None,
         song_id: Some(v.get("id")?.as_u64()?.to_string()),
         service_provider: Some(ServiceProvider::Netease),
         url: Some(
@@ -0,0 +1,257 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use crate::track::Track;
+use anyhow::{anyhow, bail, Result};
+use regex::Regex;
+use std::fmt::Write as _;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Artist,
+    AlbumArtist,
+    Album,
+    Title,
+    Genre,
+    Year,
+    Track,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "artist" => Some(Self::Artist),
+            "albumartist" | "album_artist" => Some(Self::AlbumArtist),
+            "album" => Some(Self::Album),
+            "title" => Some(Self::Title),
+            "genre" => Some(Self::Genre),
+            "year" => Some(Self::Year),
+            "track" => Some(Self::Track),
+            _ => None,
+        }
+    }
+
+    fn value(self, track: &Track) -> Option<String> {
+        match self {
+            Self::Artist => track.artist().map(str::to_string),
+            Self::AlbumArtist => track.album_artist().map(str::to_string),
+            Self::Album => track.album().map(str::to_string),
+            Self::Title => track.title().map(str::to_string),
+            Self::Genre => track.genre().map(str::to_string),
+            Self::Year => track.year().map(|y| y.to_string()),
+            Self::Track => track.track_number().map(|t| t.to_string()),
+        }
+    }
+
+    fn set(self, track: &mut Track, value: &str) -> Result<()> {
+        match self {
+            Self::Artist => track.set_artist(value),
+            Self::AlbumArtist => track.set_album_artist(value),
+            Self::Album => track.set_album(value),
+            Self::Title => track.set_title(value),
+            Self::Genre => track.set_genre(value),
+            Self::Year => track.set_year(value.parse()?),
+            Self::Track => track.set_track_number(value.parse()?),
+        }
+        Ok(())
+    }
+
+    const fn is_number(self) -> bool {
+        matches!(self, Self::Year | Self::Track)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Literal(String),
+    /// A field, with the width numbers are padded to with zeros.
+    Field(Field, usize),
+}
+
+/// A pattern naming files after their tags, like `{track:02} - {title}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let mut tokens = Vec::new();
+        let mut rest = pattern;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                tokens.push(Token::Literal(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow!("missing }} in pattern: {pattern}"))?
+                + start;
+            let spec = &rest[start + 1..end];
+            let (name, width) = spec.split_once(':').unwrap_or((spec, "0"));
+            let field = Field::from_name(name.trim())
+                .ok_or_else(|| anyhow!("unknown field {{{name}}} in pattern"))?;
+            let width = width
+                .parse()
+                .map_err(|_| anyhow!("bad width in {{{spec}}}, use a number like {{track:02}}"))?;
+            tokens.push(Token::Field(field, width));
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            tokens.push(Token::Literal(rest.to_string()));
+        }
+        if tokens.is_empty() {
+            bail!("empty pattern");
+        }
+        Ok(Self { tokens })
+    }
+
+    /// File name for `track`, without extension. Fails when a field of the pattern is not set.
+    pub fn format(&self, track: &Track) -> Result<String> {
+        let mut name = String::new();
+        for token in &self.tokens {
+            match token {
+                Token::Literal(text) => name += text,
+                Token::Field(field, width) => {
+                    let value = field
+                        .value(track)
+                        .filter(|v| !v.is_empty())
+                        .ok_or_else(|| anyhow!("{field:?} is not set"))?;
+                    match value.parse::<u32>() {
+                        Ok(number) if *width > 0 => write!(name, "{number:0width$}")?,
+                        _ => name += &value.replace(['/', '\\'], "_"),
+                    }
+                }
+            }
+        }
+        Ok(name)
+    }
+
+    /// Whether the pattern places files into directories, like `{artist}/{album}/{title}`.
+    pub fn has_directories(&self) -> bool {
+        self.tokens
+            .iter()
+            .any(|token| matches!(token, Token::Literal(text) if text.contains('/')))
+    }
+
+    /// Read the fields of the pattern from the end of `path` into `track`. The extension is
+    /// ignored, and a pattern with directories matches as many parent directories.
+    pub fn parse(&self, path: &Path, track: &mut Track) -> Result<()> {
+        let mut re = String::from("^");
+        let mut fields = Vec::new();
+        for token in &self.tokens {
+            match token {
+                Token::Literal(text) => re += &regex::escape(text),
+                Token::Field(field, _) if field.is_number() => {
+                    re += r"(\d+)";
+                    fields.push(*field);
+                }
+                Token::Field(field, _) => {
+                    re += "([^/]+?)";
+                    fields.push(*field);
+                }
+            }
+        }
+        re += "$";
+        let re = Regex::new(&re)?;
+
+        let depth = self
+            .tokens
+            .iter()
+            .map(|token| match token {
+                Token::Literal(text) => text.matches('/').count(),
+                Token::Field(..) => 0,
+            })
+            .sum::<usize>();
+        let mut parts: Vec<String> = path
+            .with_extension("")
+            .components()
+            .rev()
+            .take(depth + 1)
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        parts.reverse();
+        let tail = parts.join("/");
+
+        let captures = re
+            .captures(&tail)
+            .ok_or_else(|| anyhow!("{tail} does not match the pattern"))?;
+        for (field, value) in fields.iter().zip(captures.iter().skip(1)) {
+            if let Some(value) = value {
+                field.set(track, value.as_str().trim())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invidious::YoutubeStream;
+
+    fn track() -> Track {
+        let mut track = Track::from_youtube(&YoutubeStream {
+            video_id: "id".to_string(),
+            title: "Song/Part 1".to_string(),
+            author: "Artist".to_string(),
+            length_seconds: 180,
+            thumbnail: None,
+            url: String::new(),
+        });
+        track.set_track_number(3);
+        track
+    }
+
+    #[test]
+    fn test_pattern_format() {
+        let pattern = Pattern::new("{track:02} - {artist} - {title}").unwrap();
+        assert_eq!(
+            pattern.format(&track()).unwrap(),
+            "03 - Artist - Song_Part 1"
+        );
+        assert!(Pattern::new("{year}").unwrap().format(&track()).is_err());
+        assert!(Pattern::new("{unknown}").is_err());
+        assert!(Pattern::new("{title").is_err());
+    }
+
+    #[test]
+    fn test_pattern_parse() {
+        let pattern = Pattern::new("{artist}/{album}/{track} - {title}").unwrap();
+        assert!(pattern.has_directories());
+        let mut track = track();
+        pattern
+            .parse(
+                Path::new("/music/Some Band/Best Of/07 - A - B.mp3"),
+                &mut track,
+            )
+            .unwrap();
+        assert_eq!(track.artist(), Some("Some Band"));
+        assert_eq!(track.album(), Some("Best Of"));
+        assert_eq!(track.track_number(), Some(7));
+        assert_eq!(track.title(), Some("A - B"));
+        assert!(pattern
+            .parse(Path::new("/music/Best Of/A - B.mp3"), &mut track)
+            .is_err());
+    }
+}
@@ -26,6 +26,7 @@ use crate::config::Settings;
 use crate::track::Track;
 use crate::utils::{filetype_supported, get_app_config_path, get_pin_yin};
 use rusqlite::{params, Connection, Error, Result, Row};
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, UNIX_EPOCH};
@@ -53,7 +54,7 @@ pub struct TrackForDB {
     pub last_position: Duration,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum SearchCriteria {
     Artist,
     Album,
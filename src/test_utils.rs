@@ -0,0 +1,58 @@
+//! Fixtures shared by the unit tests.
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver};
+
+/// Serves `body` once on a random local port and returns the base url.
+pub fn mock_server(body: &'static str) -> String {
+    mock_server_sequence(vec![body]).0
+}
+
+/// Answers one request with each of `bodies` in turn, then stops listening. Every request is
+/// passed on as its request line followed by the body, e.g. `"POST /path\n{..}"`.
+pub fn mock_server_sequence(bodies: Vec<&'static str>) -> (String, Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for body in bodies {
+            let Ok((mut stream, _)) = listener.accept() else {
+                return;
+            };
+            // read the request headers before answering
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            reader.read_line(&mut request).ok();
+            let mut length = 0;
+            let mut line = String::new();
+            while matches!(reader.read_line(&mut line), Ok(n) if n > 2) {
+                if let Some((key, value)) = line.split_once(':') {
+                    if key.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+                line.clear();
+            }
+            let mut content = vec![0; length];
+            reader.read_exact(&mut content).ok();
+            let request_line = request.rsplitn(2, ' ').last().unwrap_or_default();
+            tx.send(format!(
+                "{request_line}\n{}",
+                String::from_utf8_lossy(&content)
+            ))
+            .ok();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .ok();
+        }
+    });
+    (format!("http://{addr}"), rx)
+}
+
+/// A base url that refuses every connection, port 0 can't be connected to.
+pub fn dead_server() -> String {
+    "http://127.0.0.1:0".to_string()
+}
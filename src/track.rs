@@ -1,4 +1,3 @@
-use crate::podcast::Episode;
 /**
  * MIT License
  *
@@ -22,21 +21,26 @@ use crate::podcast::Episode;
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE US OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+use crate::invidious::{video_id_from_url, video_url, YoutubeStream};
+use crate::podcast::Episode;
 use crate::songtag::lrc::Lyric;
 use crate::utils::get_parent_folder;
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use id3::frame::Lyrics;
-use lofty::id3::v2::{Frame, FrameFlags, FrameValue, ID3v2Tag, LanguageFrame};
+use lofty::id3::v2::{
+    EncodedTextFrame, Frame, FrameFlags, FrameValue, ID3v2Tag, LanguageFrame, SyncTextContentType,
+    SyncTextInformation, SynchronizedText, TimestampFormat,
+};
 use lofty::{
     mpeg::MPEGFile, Accessor, AudioFile, FileType, ItemKey, ItemValue, Picture, PictureType,
     TagExt, TagItem, TaggedFileExt, TextEncoding,
 };
 use std::convert::From;
 use std::ffi::OsStr;
-use std::fs::rename;
 use std::fs::File;
-use std::io::BufReader;
-use std::path::Path;
+use std::fs::{copy, create_dir_all, remove_file, rename};
+use std::io::{BufReader, ErrorKind};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::{Duration, SystemTime};
 
@@ -58,28 +62,123 @@ pub struct Track {
     ext: Option<String>,
     directory: Option<String>,
     pub last_modified: SystemTime,
-    /// USLT lyrics
+    /// USLT lyrics, SYLT lyrics are kept as enhanced lrc text
     lyric_frames: Vec<Lyrics>,
     lyric_selected_index: usize,
     parsed_lyric: Option<Lyric>,
     picture: Option<Picture>,
     album_photo: Option<String>,
     file_type: Option<FileType>,
-    // Date
-    // Track
+    album_artist: Option<String>,
+    year: Option<u32>,
+    number: Option<u32>,
     genre: Option<String>,
+    musicbrainz: MusicBrainzIds,
     // Composer
     // Performer
     // Disc
     // Comment
     pub media_type: Option<MediaType>,
     pub podcast_localfile: Option<String>,
+    /// Audio stream of a youtube track, `file` holds the video url.
+    stream_url: Option<String>,
+}
+
+/// Musicbrainz identifiers of a track, stored under the names other taggers use.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MusicBrainzIds {
+    pub recording: Option<String>,
+    pub release: Option<String>,
+    pub release_group: Option<String>,
+    pub artist: Option<String>,
+}
+
+impl MusicBrainzIds {
+    // ID3v2 TXXX description and vorbis comment key of each id.
+    const KEYS: [(&'static str, &'static str); 4] = [
+        ("MusicBrainz Track Id", "MUSICBRAINZ_TRACKID"),
+        ("MusicBrainz Album Id", "MUSICBRAINZ_ALBUMID"),
+        ("MusicBrainz Release Group Id", "MUSICBRAINZ_RELEASEGROUPID"),
+        ("MusicBrainz Artist Id", "MUSICBRAINZ_ARTISTID"),
+    ];
+
+    fn ids_mut(&mut self) -> [&mut Option<String>; 4] {
+        [
+            &mut self.recording,
+            &mut self.release,
+            &mut self.release_group,
+            &mut self.artist,
+        ]
+    }
+
+    fn ids(&self) -> [&Option<String>; 4] {
+        [
+            &self.recording,
+            &self.release,
+            &self.release_group,
+            &self.artist,
+        ]
+    }
+
+    fn read_id3(tag: &ID3v2Tag) -> Self {
+        let mut ids = Self::default();
+        for frame in tag.iter().filter(|f| f.id_str() == "TXXX") {
+            let FrameValue::UserText(text) = frame.content() else {
+                continue;
+            };
+            for ((description, _), id) in Self::KEYS.iter().zip(ids.ids_mut()) {
+                if text.description.eq_ignore_ascii_case(description) {
+                    *id = Some(text.content.clone());
+                }
+            }
+        }
+        ids
+    }
+
+    fn read(tag: &lofty::Tag) -> Self {
+        let mut ids = Self::default();
+        for ((_, key), id) in Self::KEYS.iter().zip(ids.ids_mut()) {
+            *id = tag
+                .get_string(&ItemKey::Unknown((*key).to_string()))
+                .map(str::to_string);
+        }
+        ids
+    }
+
+    fn write_id3(&self, tag: &mut ID3v2Tag) {
+        for ((description, _), id) in Self::KEYS.iter().zip(self.ids()) {
+            let Some(id) = id else {
+                continue;
+            };
+            if let Ok(frame) = Frame::new(
+                "TXXX",
+                FrameValue::UserText(EncodedTextFrame {
+                    encoding: TextEncoding::UTF8,
+                    description: (*description).to_string(),
+                    content: id.clone(),
+                }),
+                FrameFlags::default(),
+            ) {
+                tag.insert(frame);
+            }
+        }
+    }
+
+    fn write(&self, tag: &mut lofty::Tag) {
+        for ((_, key), id) in Self::KEYS.iter().zip(self.ids()) {
+            if let Some(id) = id {
+                tag.insert_text(ItemKey::Unknown((*key).to_string()), id.clone());
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
 pub enum MediaType {
     Music,
     Podcast,
+    /// Streamed from youtube through an invidious instance.
+    Youtube,
 }
 
 impl Track {
@@ -110,12 +209,61 @@ impl Track {
             picture: None,
             album_photo: ep.image_url.clone(),
             file_type: None,
+            album_artist: None,
+            year: None,
+            number: None,
+            musicbrainz: MusicBrainzIds::default(),
             genre: None,
             media_type: Some(MediaType::Podcast),
             podcast_localfile,
+            stream_url: None,
         }
     }
 
+    pub fn from_youtube(stream: &YoutubeStream) -> Self {
+        Self {
+            artist: Some(stream.author.clone()),
+            album: Some("YouTube".to_string()),
+            title: Some(stream.title.clone()),
+            file: Some(video_url(&stream.video_id)),
+            duration: Duration::from_secs(stream.length_seconds),
+            name: None,
+            ext: None,
+            directory: None,
+            last_modified: SystemTime::now(),
+            lyric_frames: Vec::new(),
+            lyric_selected_index: 0,
+            parsed_lyric: None,
+            picture: None,
+            album_photo: stream.thumbnail.clone(),
+            file_type: None,
+            album_artist: None,
+            year: None,
+            number: None,
+            musicbrainz: MusicBrainzIds::default(),
+            genre: None,
+            media_type: Some(MediaType::Youtube),
+            podcast_localfile: None,
+            stream_url: Some(stream.url.clone()),
+        }
+    }
+
+    /// A youtube track restored from the playlist file. The stream and the details are fetched
+    /// once it is played.
+    pub fn from_youtube_url(url: &str) -> Option<Self> {
+        let video_id = video_id_from_url(url)?;
+        let mut track = Self::from_youtube(&YoutubeStream {
+            video_id: video_id.to_string(),
+            title: url.to_string(),
+            author: "YouTube".to_string(),
+            length_seconds: 0,
+            thumbnail: None,
+            url: String::new(),
+        });
+        track.stream_url = None;
+        Some(track)
+    }
+
     pub fn read_from_path<P: AsRef<Path>>(path: P, for_db: bool) -> Result<Self> {
         let path = path.as_ref();
 
@@ -139,12 +287,18 @@ impl Track {
                 song.album = tag.album().map(std::borrow::Cow::into_owned);
                 song.title = tag.title().map(std::borrow::Cow::into_owned);
                 song.genre = tag.genre().map(std::borrow::Cow::into_owned);
+                song.album_artist = tag.get_string(&ItemKey::AlbumArtist).map(str::to_string);
+                song.year = tag.year();
+                song.number = tag.track();
                 song.media_type = Some(MediaType::Music);
 
                 if for_db {
                     return Ok(song);
                 }
 
+                // ID3v2 keeps the ids in TXXX frames, read below
+                song.musicbrainz = MusicBrainzIds::read(tag);
+
                 // Get all of the lyrics tags
                 let mut lyric_frames: Vec<Lyrics> = Vec::new();
                 match file_type {
@@ -166,6 +320,8 @@ impl Track {
                                     text: lyrics_frame.content.clone(),
                                 });
                             }
+                            lyric_frames.append(&mut sync_lyrics(id3v2_tag));
+                            song.musicbrainz = MusicBrainzIds::read_id3(id3v2_tag);
                         }
                     }
                     _ => {
@@ -243,9 +399,14 @@ impl Track {
             picture,
             album_photo,
             last_modified,
+            album_artist: None,
+            year: None,
+            number: None,
+            musicbrainz: MusicBrainzIds::default(),
             genre,
             media_type: Some(MediaType::Music),
             podcast_localfile: None,
+            stream_url: None,
         }
     }
 
@@ -279,6 +440,62 @@ impl Track {
         bail!("cycle lyrics error")
     }
 
+    /// Add the lyrics of `.lrc` and `.txt` files named after the track, either by file name or
+    /// as `artist - title`, found next to the track or in `lyric_dir`. Returns whether any new
+    /// lyric was found.
+    pub fn load_lyric_files(&mut self, lyric_dir: &str) -> bool {
+        let Some(path) = self.file().map(PathBuf::from) else {
+            return false;
+        };
+        let Some(stem) = path.file_stem() else {
+            return false;
+        };
+        let mut names = vec![stem.to_string_lossy().to_string()];
+        if let (Some(artist), Some(title)) = (self.artist(), self.title()) {
+            names.push(format!("{artist} - {title}"));
+        }
+        let mut dirs: Vec<PathBuf> = path.parent().map(Path::to_path_buf).into_iter().collect();
+        if !lyric_dir.is_empty() {
+            dirs.push(PathBuf::from(shellexpand::tilde(lyric_dir).as_ref()));
+        }
+
+        let mut found = false;
+        for dir in &dirs {
+            for name in &names {
+                for ext in ["lrc", "txt"] {
+                    let lyric_path = dir.join(format!("{name}.{ext}"));
+                    let Ok(text) = std::fs::read_to_string(&lyric_path) else {
+                        continue;
+                    };
+                    let description = lyric_path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    if self
+                        .lyric_frames
+                        .iter()
+                        .any(|f| f.description == description && f.text == text)
+                    {
+                        continue;
+                    }
+                    self.lyric_frames.push(Lyrics {
+                        lang: "eng".to_string(),
+                        description,
+                        text,
+                    });
+                    found = true;
+                }
+            }
+        }
+        if found && self.parsed_lyric.is_none() {
+            self.parsed_lyric = self
+                .lyric_frames
+                .get(self.lyric_selected_index)
+                .and_then(|f| Lyric::from_str(&f.text).ok());
+        }
+        found
+    }
+
     pub const fn parsed_lyric(&self) -> Option<&Lyric> {
         self.parsed_lyric.as_ref()
     }
@@ -363,6 +580,34 @@ impl Track {
         self.genre = Some(genre.to_string());
     }
 
+    pub fn album_artist(&self) -> Option<&str> {
+        self.album_artist.as_deref()
+    }
+
+    pub fn set_album_artist(&mut self, album_artist: &str) {
+        self.album_artist = Some(album_artist.to_string());
+    }
+
+    pub const fn year(&self) -> Option<u32> {
+        self.year
+    }
+
+    pub fn set_year(&mut self, year: u32) {
+        self.year = Some(year);
+    }
+
+    pub const fn track_number(&self) -> Option<u32> {
+        self.number
+    }
+
+    pub fn set_track_number(&mut self, track_number: u32) {
+        self.number = Some(track_number);
+    }
+
+    pub fn set_musicbrainz(&mut self, ids: MusicBrainzIds) {
+        self.musicbrainz = ids;
+    }
+
     /// Optionally return the title of the song
     /// If `None` it wasn't able to read the tags
     pub fn title(&self) -> Option<&str> {
@@ -377,6 +622,10 @@ impl Track {
         self.file.as_deref()
     }
 
+    pub fn stream_url(&self) -> Option<&str> {
+        self.stream_url.as_deref()
+    }
+
     pub fn directory(&self) -> Option<&str> {
         self.directory.as_deref()
     }
@@ -409,13 +658,76 @@ impl Track {
         self.name.as_deref()
     }
 
+    /// Fill `{title}`, `{artist}`, `{album}`, `{genre}` and `{duration}` of `template`. Missing
+    /// tags become empty, other text in braces is kept as it is.
+    #[allow(unused)]
+    pub fn fill_template(&self, template: &str) -> String {
+        let mut text = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            text.push_str(&rest[..start]);
+            let name = &rest[start + 1..start + len];
+            match name {
+                "title" => text.push_str(self.title().unwrap_or_default()),
+                "artist" => text.push_str(self.artist().unwrap_or_default()),
+                "album" => text.push_str(self.album().unwrap_or_default()),
+                "genre" => text.push_str(self.genre().unwrap_or_default()),
+                "duration" => text.push_str(&self.duration_formatted()),
+                _ => text.push_str(&rest[start..=start + len]),
+            }
+            rest = &rest[start + len + 1..];
+        }
+        text.push_str(rest);
+        text
+    }
+
+    /// Write the tag and rename the file by artist and title.
     pub fn save_tag(&mut self) -> Result<()> {
+        self.write_tag()?;
+        self.rename_by_tag()?;
+        Ok(())
+    }
+
+    /// Write the tag and move the file to `target`, a path without extension. None keeps the
+    /// file where it is.
+    pub fn save_tag_as(&mut self, target: Option<&Path>) -> Result<()> {
+        self.write_tag()?;
+        if let Some(target) = target {
+            self.move_to(target)?;
+        }
+        Ok(())
+    }
+
+    // Frames termusic doesn't know, like the disc number or comments, are kept as they are in
+    // the file.
+    fn write_tag(&self) -> Result<()> {
         match self.file_type {
             Some(FileType::MPEG) => {
                 if let Some(file_path) = self.file() {
-                    let mut tag = ID3v2Tag::default();
+                    let mut reader = BufReader::new(File::open(file_path)?);
+                    let file = MPEGFile::read_from(&mut reader, lofty::ParseOptions::new())?;
+                    let mut tag = file.id3v2().cloned().unwrap_or_default();
                     self.update_tag(&mut tag);
+                    if let Some(album_artist) = self.album_artist() {
+                        if let Ok(frame) = Frame::new(
+                            "TPE2",
+                            FrameValue::Text {
+                                encoding: TextEncoding::UTF8,
+                                value: album_artist.to_string(),
+                            },
+                            FrameFlags::default(),
+                        ) {
+                            tag.insert(frame);
+                        }
+                    }
+                    self.musicbrainz.write_id3(&mut tag);
 
+                    // the lyric frames were all read from the file
+                    tag.remove("USLT");
+                    tag.remove("SYLT");
                     if !self.lyric_frames_is_empty() {
                         if let Some(lyric_frames) = self.lyric_frames() {
                             for l in lyric_frames {
@@ -432,6 +744,10 @@ impl Track {
                                 //     language = *b"eng";
                                 // }
 
+                                if let Some(s_frame) = sync_lyrics_frame(&l) {
+                                    tag.insert(s_frame);
+                                    continue;
+                                }
                                 if let Ok(l_frame) = Frame::new(
                                     "USLT",
                                     FrameValue::UnSyncText(LanguageFrame {
@@ -451,6 +767,7 @@ impl Track {
                     }
 
                     if let Some(any_picture) = self.picture().cloned() {
+                        tag.remove_picture_type(any_picture.pic_type());
                         tag.insert_picture(any_picture);
                     }
 
@@ -465,9 +782,17 @@ impl Track {
                         None => return Ok(()),
                     };
 
-                    let mut tag = lofty::Tag::new(tag_type);
+                    let mut tag = lofty::read_from_path(file_path)?
+                        .primary_tag()
+                        .cloned()
+                        .unwrap_or_else(|| lofty::Tag::new(tag_type));
                     self.update_tag(&mut tag);
+                    if let Some(album_artist) = self.album_artist() {
+                        tag.insert_text(ItemKey::AlbumArtist, album_artist.to_string());
+                    }
+                    self.musicbrainz.write(&mut tag);
 
+                    tag.remove_key(&ItemKey::Lyrics);
                     if !self.lyric_frames_is_empty() {
                         if let Some(lyric_frames) = self.lyric_frames() {
                             for l in lyric_frames {
@@ -480,6 +805,7 @@ impl Track {
                     }
 
                     if let Some(any_picture) = self.picture().cloned() {
+                        tag.remove_picture_type(any_picture.pic_type());
                         tag.push_picture(any_picture);
                     }
 
@@ -487,29 +813,48 @@ impl Track {
                 }
             }
         }
-
-        self.rename_by_tag()?;
         Ok(())
     }
 
     fn rename_by_tag(&mut self) -> Result<()> {
-        if let Some(ext) = self.ext() {
-            let new_name = format!(
-                "{}-{}.{}",
-                self.artist().unwrap_or("Unknown Artist"),
-                self.title().unwrap_or("Unknown Title"),
-                ext,
-            );
-
-            let new_name_path: &Path = Path::new(new_name.as_str());
-            if let Some(file) = self.file() {
-                let p_old: &Path = Path::new(file);
-                if let Some(p_prefix) = p_old.parent() {
-                    let p_new = p_prefix.join(new_name_path);
-                    rename(p_old, &p_new)?;
-                    self.file = Some(String::from(p_new.to_string_lossy()));
+        let new_name = format!(
+            "{}-{}",
+            self.artist().unwrap_or("Unknown Artist"),
+            self.title().unwrap_or("Unknown Title"),
+        );
+        self.rename_to(&new_name)
+    }
+
+    fn rename_to(&mut self, file_name: &str) -> Result<()> {
+        if let Some(p_prefix) = self.file().and_then(|file| Path::new(file).parent()) {
+            let target = p_prefix.join(file_name);
+            self.move_to(&target)?;
+        }
+        Ok(())
+    }
+
+    // Move the file to `target`, a path without extension, creating its directory if needed.
+    fn move_to(&mut self, target: &Path) -> Result<()> {
+        if let (Some(ext), Some(file)) = (self.ext(), self.file()) {
+            let file_name = target
+                .file_name()
+                .ok_or_else(|| anyhow!("no file name in {}", target.display()))?;
+            let new_name = format!("{}.{ext}", file_name.to_string_lossy());
+            let p_new = target.with_file_name(&new_name);
+            if let Some(dir) = p_new.parent() {
+                create_dir_all(dir)?;
+            }
+            match rename(file, &p_new) {
+                // a rename can't leave the file system, so the file is copied over instead
+                Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+                    copy(file, &p_new)?;
+                    remove_file(file)?;
                 }
+                result => result?,
             }
+            self.directory = p_new.parent().map(|dir| dir.to_string_lossy().to_string());
+            self.file = Some(String::from(p_new.to_string_lossy()));
+            self.name = Some(new_name);
         }
 
         Ok(())
@@ -556,6 +901,12 @@ impl Track {
 
         tag.set_album(self.album().map_or_else(String::new, str::to_string));
         tag.set_genre(self.genre().map_or_else(String::new, str::to_string));
+        if let Some(year) = self.year {
+            tag.set_year(year);
+        }
+        if let Some(track_number) = self.number {
+            tag.set_track(track_number);
+        }
     }
 }
 
@@ -572,3 +923,130 @@ fn create_lyrics(tag: &mut lofty::Tag, lyric_frames: &mut Vec<Lyrics>) {
         }
     }
 }
+
+// SYLT frames with millisecond time stamps, as enhanced lrc text.
+fn sync_lyrics(tag: &ID3v2Tag) -> Vec<Lyrics> {
+    let mut lyric_frames = Vec::new();
+    for frame in tag.iter().filter(|f| f.id_str() == "SYLT") {
+        let FrameValue::Binary(bytes) = frame.content() else {
+            continue;
+        };
+        let Ok(sylt) = SynchronizedText::parse(bytes) else {
+            continue;
+        };
+        if sylt.information.timestamp_format != TimestampFormat::MS
+            || sylt.information.content_type != SyncTextContentType::Lyrics
+        {
+            continue;
+        }
+        let mut lang = sylt.information.language.clone();
+        if lang.len() < 3 {
+            lang = "eng".to_string();
+        }
+        lyric_frames.push(Lyrics {
+            lang,
+            description: sylt.information.description.clone().unwrap_or_default(),
+            text: Lyric::from_sylt(&sylt.content).as_lrc_text(),
+        });
+    }
+    lyric_frames
+}
+
+// Lyrics with word timing are written as SYLT, as USLT would keep them only as text.
+fn sync_lyrics_frame(lyrics: &Lyrics) -> Option<Frame> {
+    let lyric = Lyric::from_str(&lyrics.text).ok()?;
+    if !lyric.has_word_timing() {
+        return None;
+    }
+    let language = if lyrics.lang.len() == 3 && lyrics.lang.bytes().all(|c| c.is_ascii_lowercase())
+    {
+        lyrics.lang.clone()
+    } else {
+        "eng".to_string()
+    };
+    let sylt = SynchronizedText {
+        information: SyncTextInformation {
+            encoding: TextEncoding::UTF8,
+            language,
+            timestamp_format: TimestampFormat::MS,
+            content_type: SyncTextContentType::Lyrics,
+            description: Some(lyrics.description.clone()).filter(|d| !d.is_empty()),
+        },
+        content: lyric.as_sylt(),
+    };
+    Frame::new(
+        "SYLT",
+        FrameValue::Binary(sylt.as_bytes().ok()?),
+        FrameFlags::default(),
+    )
+    .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invidious::YoutubeStream;
+
+    #[test]
+    fn test_fill_template() {
+        let track = Track::from_youtube(&YoutubeStream {
+            video_id: "id".to_string(),
+            title: "Song".to_string(),
+            author: "Band".to_string(),
+            length_seconds: 75,
+            thumbnail: None,
+            url: String::new(),
+        });
+        assert_eq!(
+            track.fill_template("{title} by {artist}{genre} ({duration}) {other} {"),
+            "Song by Band (01:15) {other} {"
+        );
+    }
+
+    #[test]
+    fn test_load_lyric_files() {
+        let dir = std::env::temp_dir().join(format!("termusic-lyric-{}", std::process::id()));
+        let (music, lyrics) = (dir.join("music"), dir.join("lyrics"));
+        std::fs::create_dir_all(&music).unwrap();
+        std::fs::create_dir_all(&lyrics).unwrap();
+        std::fs::write(music.join("song.mp3"), b"").unwrap();
+        std::fs::write(music.join("song.lrc"), "[00:01.00]next to the track").unwrap();
+        std::fs::write(lyrics.join("Band - Tune.lrc"), "[00:01.00]in the lyric dir").unwrap();
+        // not looked up, the track is neither called like this nor is it in a lyric dir
+        std::fs::write(music.join("other.lrc"), "[00:01.00]unrelated").unwrap();
+
+        let mut track = Track::read_from_path(music.join("song.mp3"), false).unwrap();
+        track.set_artist("Band");
+        track.set_title("Tune");
+        let found = track.load_lyric_files(&lyrics.to_string_lossy());
+        let reloaded = track.load_lyric_files(&lyrics.to_string_lossy());
+        let without_dir = Track::read_from_path(music.join("song.mp3"), false)
+            .unwrap()
+            .load_lyric_files("");
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(found);
+        let frames: Vec<(String, String)> = track
+            .lyric_frames()
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.description, f.text))
+            .collect();
+        assert_eq!(
+            frames,
+            vec![
+                (
+                    "song.lrc".to_string(),
+                    "[00:01.00]next to the track".to_string()
+                ),
+                (
+                    "Band - Tune.lrc".to_string(),
+                    "[00:01.00]in the lyric dir".to_string()
+                ),
+            ]
+        );
+        assert!(track.parsed_lyric().is_some());
+        assert!(!reloaded);
+        assert!(without_dir);
+    }
+}
@@ -253,6 +253,7 @@ impl CEColorSelect {
             IdConfigEditor::LyricForeground => style_color_symbol.lyric_foreground.as_usize(),
             IdConfigEditor::LyricBackground => style_color_symbol.lyric_background.as_usize(),
             IdConfigEditor::LyricBorder => style_color_symbol.lyric_border.as_usize(),
+            IdConfigEditor::LyricHighlight => style_color_symbol.lyric_highlight.as_usize(),
             _ => 0,
         }
     }
@@ -853,6 +854,35 @@ impl Component<Msg, NoUserEvent> for ConfigLyricBorder {
     }
 }
 
+#[derive(MockComponent)]
+pub struct ConfigLyricHighlight {
+    component: CEColorSelect,
+}
+
+impl ConfigLyricHighlight {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: CEColorSelect::new(
+                " Highlight ",
+                IdConfigEditor::LyricHighlight,
+                config
+                    .style_color_symbol
+                    .lyric_highlight()
+                    .unwrap_or(Color::LightYellow),
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::LyricHighlightBlurDown),
+                Msg::ConfigEditor(ConfigEditorMsg::LyricHighlightBlurUp),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigLyricHighlight {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
 #[derive(MockComponent)]
 pub struct ConfigInputHighlight {
     component: Input,
@@ -0,0 +1,342 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (C) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::general::handle_input_ev;
+use crate::config::Settings;
+use crate::invidious::{check_instances, InstanceCheck, InstanceSettings};
+use crate::ui::{ConfigEditorMsg, Id, IdConfigEditor, Model, Msg};
+use std::thread;
+use tui_realm_stdlib::{Input, Table};
+use tuirealm::props::{
+    Alignment, AttrValue, Attribute, BorderType, Borders, Color, InputType, Style, TableBuilder,
+    TextSpan,
+};
+use tuirealm::{
+    command::{Cmd, Direction, Position},
+    event::{Key, KeyEvent, KeyModifiers, NoUserEvent},
+    Component, Event, MockComponent, State, StateValue,
+};
+
+#[derive(MockComponent)]
+pub struct InvidiousInstances {
+    component: Input,
+    config: Settings,
+}
+
+impl InvidiousInstances {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Input::default()
+                .borders(
+                    Borders::default()
+                        .color(
+                            config
+                                .style_color_symbol
+                                .library_border()
+                                .unwrap_or(Color::LightGreen),
+                        )
+                        .modifiers(BorderType::Rounded),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightGreen),
+                )
+                .input_type(InputType::Text)
+                .placeholder(
+                    "empty to use the public list from api.invidious.io",
+                    Style::default().fg(Color::Rgb(128, 128, 128)),
+                )
+                .title(" Invidious Instances:(use ; to separate) ", Alignment::Left)
+                .value(config.invidious_instances.join(";")),
+            config: config.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for InvidiousInstances {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        handle_input_ev(
+            self,
+            ev,
+            &config,
+            Msg::ConfigEditor(ConfigEditorMsg::InvidiousInstancesBlurDown),
+            Msg::ConfigEditor(ConfigEditorMsg::InvidiousInstancesBlurUp),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct InvidiousPreferred {
+    component: Input,
+    config: Settings,
+}
+
+impl InvidiousPreferred {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Input::default()
+                .borders(
+                    Borders::default()
+                        .color(
+                            config
+                                .style_color_symbol
+                                .library_border()
+                                .unwrap_or(Color::LightYellow),
+                        )
+                        .modifiers(BorderType::Rounded),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightYellow),
+                )
+                .input_type(InputType::Text)
+                .placeholder(
+                    "https://vid.puffyan.us",
+                    Style::default().fg(Color::Rgb(128, 128, 128)),
+                )
+                .title(" Preferred Instance: ", Alignment::Left)
+                .value(&config.invidious_preferred_instance),
+            config: config.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for InvidiousPreferred {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        handle_input_ev(
+            self,
+            ev,
+            &config,
+            Msg::ConfigEditor(ConfigEditorMsg::InvidiousPreferredBlurDown),
+            Msg::ConfigEditor(ConfigEditorMsg::InvidiousPreferredBlurUp),
+        )
+    }
+}
+
+#[derive(MockComponent)]
+pub struct InvidiousTest {
+    component: Table,
+    config: Settings,
+}
+
+impl InvidiousTest {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Table::default()
+                .borders(
+                    Borders::default().modifiers(BorderType::Rounded).color(
+                        config
+                            .style_color_symbol
+                            .library_border()
+                            .unwrap_or(Color::Blue),
+                    ),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Yellow),
+                )
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .title(" Instance Test: <Enter> to test ", Alignment::Left)
+                .scroll(true)
+                .highlighted_color(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightBlue),
+                )
+                .highlighted_str(&config.style_color_symbol.library_highlight_symbol)
+                .rewind(true)
+                .step(4)
+                .row_height(1)
+                .headers(&["Instance", "Status", "Latency"])
+                .column_spacing(1)
+                .widths(&[50, 35, 15])
+                .table(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from("Not tested yet"))
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from(""))
+                        .build(),
+                ),
+            config: config.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for InvidiousTest {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            // Global Hotkeys
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.config_save.key_event() => {
+                return Some(Msg::ConfigEditor(ConfigEditorMsg::CloseOk));
+            }
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.global_esc.key_event() => {
+                return Some(Msg::ConfigEditor(ConfigEditorMsg::CloseCancel));
+            }
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.global_quit.key_event() => {
+                return Some(Msg::ConfigEditor(ConfigEditorMsg::CloseCancel));
+            }
+
+            // Local Hotkeys
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.global_down.key_event() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.global_up.key_event() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(keyevent)
+                if keyevent == self.config.keys.global_goto_top.key_event() =>
+            {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.config.keys.global_goto_bottom.key_event() =>
+            {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
+                return Some(Msg::ConfigEditor(ConfigEditorMsg::ChangeLayout));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::BackTab,
+                modifiers: KeyModifiers::SHIFT,
+            }) => {
+                return Some(Msg::ConfigEditor(ConfigEditorMsg::InvidiousTestBlurUp));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                return Some(Msg::ConfigEditor(ConfigEditorMsg::InvidiousTestRun));
+            }
+            _ => tuirealm::command::CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+impl Model {
+    /// Instance settings as currently typed in the editor, which may not be saved yet.
+    fn invidious_editor_settings(&self) -> InstanceSettings {
+        let mut config = self.config.clone();
+        if let Ok(State::One(StateValue::String(instances))) = self
+            .app
+            .state(&Id::ConfigEditor(IdConfigEditor::InvidiousInstances))
+        {
+            config.invidious_instances = instances
+                .split(';')
+                .map(std::string::ToString::to_string)
+                .collect();
+        }
+        if let Ok(State::One(StateValue::String(preferred))) = self
+            .app
+            .state(&Id::ConfigEditor(IdConfigEditor::InvidiousPreferred))
+        {
+            config.invidious_preferred_instance = preferred;
+        }
+        InstanceSettings::new(&config)
+    }
+
+    pub fn invidious_test_run(&mut self) {
+        let settings = self.invidious_editor_settings();
+        let tx = self.tx_to_main.clone();
+        let table = TableBuilder::default()
+            .add_col(TextSpan::from("Testing..."))
+            .add_col(TextSpan::from(""))
+            .add_col(TextSpan::from(""))
+            .build();
+        self.app
+            .attr(
+                &Id::ConfigEditor(IdConfigEditor::InvidiousTest),
+                Attribute::Content,
+                AttrValue::Table(table),
+            )
+            .ok();
+        thread::spawn(move || {
+            let checks = check_instances(&settings);
+            tx.send(Msg::ConfigEditor(ConfigEditorMsg::InvidiousTestResult(
+                checks,
+            )))
+            .ok();
+        });
+    }
+
+    /// Shows the working instances fastest first, then the failed ones.
+    pub fn invidious_test_sync(&mut self, checks: &[InstanceCheck]) {
+        let mut checks = checks.to_vec();
+        checks.sort_by_key(|check| check.result.clone().unwrap_or(std::time::Duration::MAX));
+        let mut table = TableBuilder::default();
+        for (idx, check) in checks.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            table.add_col(TextSpan::new(&check.domain));
+            match &check.result {
+                Ok(latency) => table
+                    .add_col(TextSpan::new("ok").fg(Color::Green))
+                    .add_col(TextSpan::new(format!("{} ms", latency.as_millis()))),
+                Err(e) => table
+                    .add_col(TextSpan::new(e).fg(Color::Red))
+                    .add_col(TextSpan::from("-")),
+            };
+        }
+        if checks.is_empty() {
+            table
+                .add_col(TextSpan::from("No instances found"))
+                .add_col(TextSpan::from(""))
+                .add_col(TextSpan::from(""));
+        }
+        self.app
+            .attr(
+                &Id::ConfigEditor(IdConfigEditor::InvidiousTest),
+                Attribute::Content,
+                AttrValue::Table(table.build()),
+            )
+            .ok();
+    }
+}
@@ -1171,6 +1171,8 @@ impl KEModifierSelect {
             IdKey::GlobalXywhZoomIn => keys.global_xywh_zoom_in.mod_key(),
             IdKey::GlobalXywhZoomOut => keys.global_xywh_zoom_out.mod_key(),
             IdKey::GlobalXywhHide => keys.global_xywh_hide.mod_key(),
+            IdKey::GlobalSleepTimer => keys.global_sleep_timer.mod_key(),
+            IdKey::GlobalSleepTimerCancel => keys.global_sleep_timer_cancel.mod_key(),
             IdKey::PodcastMarkPlayed => keys.podcast_mark_played.mod_key(),
             IdKey::PodcastMarkAllPlayed => keys.podcast_mark_all_played.mod_key(),
             IdKey::PodcastEpDownload => keys.podcast_episode_download.mod_key(),
@@ -1178,6 +1180,14 @@ impl KEModifierSelect {
             IdKey::PodcastDeleteFeed => keys.podcast_delete_feed.mod_key(),
             IdKey::PodcastDeleteAllFeeds => keys.podcast_delete_all_feeds.mod_key(),
             IdKey::PodcastSearchAddFeed => keys.podcast_search_add_feed.mod_key(),
+            IdKey::PodcastToggleSkipSilence => keys.podcast_toggle_skip_silence.mod_key(),
+            IdKey::PodcastSyncNow => keys.podcast_sync_now.mod_key(),
+            IdKey::PodcastEpisodeShowNotes => keys.podcast_episode_show_notes.mod_key(),
+            IdKey::PlaylistDownload => keys.playlist_download.mod_key(),
+            IdKey::TagEditorLookup => keys.tag_editor_lookup.mod_key(),
+            IdKey::YoutubeSearchMark => keys.youtube_search_mark.mod_key(),
+            IdKey::YoutubeSearchMarkPage => keys.youtube_search_mark_page.mod_key(),
+            IdKey::YoutubeSearchPlay => keys.youtube_search_play.mod_key(),
             IdKey::PodcastRefreshFeed => keys.podcast_refresh_feed.mod_key(),
             IdKey::PodcastRefreshAllFeeds => keys.podcast_refresh_all_feeds.mod_key(),
         }
@@ -2780,6 +2790,60 @@ impl Component<Msg, NoUserEvent> for ConfigGlobalXywhHide {
     }
 }
 
+#[derive(MockComponent)]
+pub struct ConfigGlobalSleepTimer {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalSleepTimer {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Sleep timer ",
+                IdKey::GlobalSleepTimer,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::GlobalSleepTimerBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::GlobalSleepTimerBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalSleepTimer {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalSleepTimerCancel {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalSleepTimerCancel {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Sleep timer cancel ",
+                IdKey::GlobalSleepTimerCancel,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalSleepTimerCancelBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalSleepTimerCancelBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalSleepTimerCancel {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
 #[derive(MockComponent)]
 pub struct ConfigPodcastMarkPlayed {
     component: KEModifierSelect,
@@ -3018,3 +3082,215 @@ impl Component<Msg, NoUserEvent> for ConfigPodcastRefreshAllFeeds {
         self.component.on(ev)
     }
 }
+
+#[derive(MockComponent)]
+pub struct ConfigPodcastToggleSkipSilence {
+    component: KEModifierSelect,
+}
+
+impl ConfigPodcastToggleSkipSilence {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Toggle skip silence ",
+                IdKey::PodcastToggleSkipSilence,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::PodcastToggleSkipSilenceBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::PodcastToggleSkipSilenceBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigPodcastToggleSkipSilence {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigPodcastSyncNow {
+    component: KEModifierSelect,
+}
+
+impl ConfigPodcastSyncNow {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Sync with gpodder ",
+                IdKey::PodcastSyncNow,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::PodcastSyncNowBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::PodcastSyncNowBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigPodcastSyncNow {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigPodcastEpisodeShowNotes {
+    component: KEModifierSelect,
+}
+
+impl ConfigPodcastEpisodeShowNotes {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Episode show notes ",
+                IdKey::PodcastEpisodeShowNotes,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::PodcastEpisodeShowNotesBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::PodcastEpisodeShowNotesBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigPodcastEpisodeShowNotes {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigPlaylistDownload {
+    component: KEModifierSelect,
+}
+
+impl ConfigPlaylistDownload {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Download youtube ",
+                IdKey::PlaylistDownload,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::PlaylistDownloadBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::PlaylistDownloadBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigPlaylistDownload {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigTagEditorLookup {
+    component: KEModifierSelect,
+}
+
+impl ConfigTagEditorLookup {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Tag editor lookup ",
+                IdKey::TagEditorLookup,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::TagEditorLookupBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::TagEditorLookupBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigTagEditorLookup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigYoutubeSearchMark {
+    component: KEModifierSelect,
+}
+
+impl ConfigYoutubeSearchMark {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Youtube: mark ",
+                IdKey::YoutubeSearchMark,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::YoutubeSearchMarkBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::YoutubeSearchMarkBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigYoutubeSearchMark {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigYoutubeSearchMarkPage {
+    component: KEModifierSelect,
+}
+
+impl ConfigYoutubeSearchMarkPage {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Youtube: mark page ",
+                IdKey::YoutubeSearchMarkPage,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::YoutubeSearchMarkPageBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::YoutubeSearchMarkPageBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigYoutubeSearchMarkPage {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigYoutubeSearchPlay {
+    component: KEModifierSelect,
+}
+
+impl ConfigYoutubeSearchPlay {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Youtube: play ",
+                IdKey::YoutubeSearchPlay,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::YoutubeSearchPlayBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::YoutubeSearchPlayBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigYoutubeSearchPlay {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
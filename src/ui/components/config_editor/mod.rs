@@ -23,6 +23,7 @@
  */
 mod color;
 mod general;
+mod invidious;
 mod key_combo;
 mod update;
 mod view;
@@ -32,6 +33,7 @@ use crate::ui::model::ConfigEditorLayout;
 use crate::ui::{ConfigEditorMsg, Msg};
 pub use color::*;
 pub use general::*;
+pub use invidious::*;
 pub use key_combo::*;
 
 use tui_realm_stdlib::{Radio, Span};
@@ -61,6 +63,7 @@ impl CEHeader {
                     "Themes and Colors",
                     "Keys Global",
                     "Keys Other",
+                    "Invidious",
                 ])
                 .foreground(
                     config
@@ -79,6 +82,7 @@ impl CEHeader {
                     ConfigEditorLayout::Color => 1,
                     ConfigEditorLayout::Key1 => 2,
                     ConfigEditorLayout::Key2 => 3,
+                    ConfigEditorLayout::Invidious => 4,
                 }),
         }
     }
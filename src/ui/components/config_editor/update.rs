@@ -113,6 +113,26 @@ impl Model {
                     .ok();
             }
 
+            // Focus of invidious page
+            ConfigEditorMsg::InvidiousTestBlurUp | ConfigEditorMsg::InvidiousInstancesBlurDown => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::InvidiousPreferred))
+                    .ok();
+            }
+            ConfigEditorMsg::InvidiousPreferredBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::InvidiousInstances))
+                    .ok();
+            }
+            ConfigEditorMsg::InvidiousPreferredBlurDown
+            | ConfigEditorMsg::InvidiousInstancesBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::InvidiousTest))
+                    .ok();
+            }
+            ConfigEditorMsg::InvidiousTestRun => self.invidious_test_run(),
+            ConfigEditorMsg::InvidiousTestResult(checks) => self.invidious_test_sync(checks),
+
             ConfigEditorMsg::ConfigSaveOk => {
                 self.app
                     .umount(&Id::ConfigEditor(IdConfigEditor::ConfigSavePopup))
@@ -138,7 +158,7 @@ impl Model {
                     .active(&Id::ConfigEditor(IdConfigEditor::LibraryForeground))
                     .ok();
             }
-            ConfigEditorMsg::LibraryForegroundBlurUp | ConfigEditorMsg::LyricBorderBlurDown => {
+            ConfigEditorMsg::LibraryForegroundBlurUp | ConfigEditorMsg::LyricHighlightBlurDown => {
                 self.app
                     .active(&Id::ConfigEditor(IdConfigEditor::CEThemeSelect))
                     .ok();
@@ -222,11 +242,16 @@ impl Model {
                     .active(&Id::ConfigEditor(IdConfigEditor::LyricBackground))
                     .ok();
             }
-            ConfigEditorMsg::LyricBackgroundBlurDown | ConfigEditorMsg::ThemeSelectBlurUp => {
+            ConfigEditorMsg::LyricBackgroundBlurDown | ConfigEditorMsg::LyricHighlightBlurUp => {
                 self.app
                     .active(&Id::ConfigEditor(IdConfigEditor::LyricBorder))
                     .ok();
             }
+            ConfigEditorMsg::LyricBorderBlurDown | ConfigEditorMsg::ThemeSelectBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::LyricHighlight))
+                    .ok();
+            }
             ConfigEditorMsg::ThemeSelectLoad(index) => {
                 if let Some(t) = self.ce_themes.get(*index) {
                     let path = PathBuf::from(t);
@@ -271,7 +296,7 @@ impl Model {
     fn update_key_focus(&mut self, msg: &KFMsg) {
         match msg {
             // Focus of key global page
-            KFMsg::GlobalXywhHideBlurDown | KFMsg::GlobalLeftBlurUp => {
+            KFMsg::GlobalSleepTimerCancelBlurDown | KFMsg::GlobalLeftBlurUp => {
                 self.app
                     .active(&Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalQuit)))
                     .ok();
@@ -494,16 +519,30 @@ impl Model {
                     )))
                     .ok();
             }
-            KFMsg::GlobalXywhZoomOutBlurDown | KFMsg::GlobalQuitBlurUp => {
+            KFMsg::GlobalXywhZoomOutBlurDown | KFMsg::GlobalSleepTimerBlurUp => {
                 self.app
                     .active(&Id::ConfigEditor(IdConfigEditor::Key(
                         IdKey::GlobalXywhHide,
                     )))
                     .ok();
             }
+            KFMsg::GlobalXywhHideBlurDown | KFMsg::GlobalSleepTimerCancelBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalSleepTimer,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalSleepTimerBlurDown | KFMsg::GlobalQuitBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalSleepTimerCancel,
+                    )))
+                    .ok();
+            }
 
             // Focus of key 2 page
-            KFMsg::PodcastSearchAddFeedBlurDown | KFMsg::LibraryDeleteBlurUp => {
+            KFMsg::YoutubeSearchPlayBlurDown | KFMsg::LibraryDeleteBlurUp => {
                 self.app
                     .active(&Id::ConfigEditor(IdConfigEditor::Key(
                         IdKey::LibraryTagEditor,
@@ -726,16 +765,73 @@ impl Model {
                     )))
                     .ok();
             }
-            KFMsg::PodcastRefreshAllFeedsBlurDown | KFMsg::LibraryTagEditorBlurUp => {
+            KFMsg::PodcastRefreshAllFeedsBlurDown | KFMsg::PodcastToggleSkipSilenceBlurUp => {
                 self.app
                     .active(&Id::ConfigEditor(IdConfigEditor::Key(
                         IdKey::PodcastSearchAddFeed,
                     )))
                     .ok();
             }
+            KFMsg::PodcastSearchAddFeedBlurDown | KFMsg::PodcastSyncNowBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::PodcastToggleSkipSilence,
+                    )))
+                    .ok();
+            }
+            KFMsg::PodcastToggleSkipSilenceBlurDown | KFMsg::PodcastEpisodeShowNotesBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::PodcastSyncNow,
+                    )))
+                    .ok();
+            }
+            KFMsg::PodcastSyncNowBlurDown | KFMsg::PlaylistDownloadBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::PodcastEpisodeShowNotes,
+                    )))
+                    .ok();
+            }
+            KFMsg::PodcastEpisodeShowNotesBlurDown | KFMsg::TagEditorLookupBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::PlaylistDownload,
+                    )))
+                    .ok();
+            }
+            KFMsg::PlaylistDownloadBlurDown | KFMsg::YoutubeSearchMarkBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::TagEditorLookup,
+                    )))
+                    .ok();
+            }
+            KFMsg::TagEditorLookupBlurDown | KFMsg::YoutubeSearchMarkPageBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::YoutubeSearchMark,
+                    )))
+                    .ok();
+            }
+            KFMsg::YoutubeSearchMarkBlurDown | KFMsg::YoutubeSearchPlayBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::YoutubeSearchMarkPage,
+                    )))
+                    .ok();
+            }
+            KFMsg::YoutubeSearchMarkPageBlurDown | KFMsg::LibraryTagEditorBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::YoutubeSearchPlay,
+                    )))
+                    .ok();
+            }
         }
     }
 
+    #[allow(clippy::too_many_lines)]
     fn update_key(&mut self, id: &IdKey, binding: &BindingForEvent) {
         self.config_changed = true;
         match id {
@@ -806,6 +902,10 @@ impl Model {
             IdKey::GlobalXywhZoomIn => self.ke_key_config.global_xywh_zoom_in = *binding,
             IdKey::GlobalXywhZoomOut => self.ke_key_config.global_xywh_zoom_out = *binding,
             IdKey::GlobalXywhHide => self.ke_key_config.global_xywh_hide = *binding,
+            IdKey::GlobalSleepTimer => self.ke_key_config.global_sleep_timer = *binding,
+            IdKey::GlobalSleepTimerCancel => {
+                self.ke_key_config.global_sleep_timer_cancel = *binding;
+            }
             IdKey::PodcastMarkPlayed => self.ke_key_config.podcast_mark_played = *binding,
             IdKey::PodcastMarkAllPlayed => self.ke_key_config.podcast_mark_all_played = *binding,
             IdKey::PodcastEpDownload => self.ke_key_config.podcast_episode_download = *binding,
@@ -817,6 +917,30 @@ impl Model {
             IdKey::PodcastRefreshAllFeeds => {
                 self.ke_key_config.podcast_refresh_all_feeds = *binding;
             }
+            IdKey::PodcastToggleSkipSilence => {
+                self.ke_key_config.podcast_toggle_skip_silence = *binding;
+            }
+            IdKey::PodcastSyncNow => {
+                self.ke_key_config.podcast_sync_now = *binding;
+            }
+            IdKey::PodcastEpisodeShowNotes => {
+                self.ke_key_config.podcast_episode_show_notes = *binding;
+            }
+            IdKey::PlaylistDownload => {
+                self.ke_key_config.playlist_download = *binding;
+            }
+            IdKey::TagEditorLookup => {
+                self.ke_key_config.tag_editor_lookup = *binding;
+            }
+            IdKey::YoutubeSearchMark => {
+                self.ke_key_config.youtube_search_mark = *binding;
+            }
+            IdKey::YoutubeSearchMarkPage => {
+                self.ke_key_config.youtube_search_mark_page = *binding;
+            }
+            IdKey::YoutubeSearchPlay => {
+                self.ke_key_config.youtube_search_play = *binding;
+            }
         }
     }
 
@@ -868,6 +992,9 @@ impl Model {
             IdConfigEditor::LyricBorder => {
                 self.ce_style_color_symbol.lyric_border = color_config;
             }
+            IdConfigEditor::LyricHighlight => {
+                self.ce_style_color_symbol.lyric_highlight = color_config;
+            }
 
             _ => {}
         }
@@ -22,6 +22,7 @@
  * SOFTWARE.
  */
 use crate::config::{LastPosition, SeekStep, Settings};
+use crate::invidious::InstanceSettings;
 use crate::ui::components::{
     AlbumPhotoAlign, CEHeader, CEThemeSelectTable, ConfigDatabaseAddAll, ConfigGlobalConfig,
     ConfigGlobalDown, ConfigGlobalGotoBottom, ConfigGlobalGotoTop, ConfigGlobalHelp,
@@ -30,27 +31,31 @@ use crate::ui::components::{
     ConfigGlobalLyricCycle, ConfigGlobalPlayerNext, ConfigGlobalPlayerPrevious,
     ConfigGlobalPlayerSeekBackward, ConfigGlobalPlayerSeekForward, ConfigGlobalPlayerSpeedDown,
     ConfigGlobalPlayerSpeedUp, ConfigGlobalPlayerToggleGapless, ConfigGlobalPlayerTogglePause,
-    ConfigGlobalQuit, ConfigGlobalRight, ConfigGlobalSavePlaylist, ConfigGlobalUp,
-    ConfigGlobalVolumeDown, ConfigGlobalVolumeUp, ConfigGlobalXywhHide, ConfigGlobalXywhMoveDown,
-    ConfigGlobalXywhMoveLeft, ConfigGlobalXywhMoveRight, ConfigGlobalXywhMoveUp,
-    ConfigGlobalXywhZoomIn, ConfigGlobalXywhZoomOut, ConfigLibraryAddRoot, ConfigLibraryBackground,
-    ConfigLibraryBorder, ConfigLibraryDelete, ConfigLibraryForeground, ConfigLibraryHighlight,
+    ConfigGlobalQuit, ConfigGlobalRight, ConfigGlobalSavePlaylist, ConfigGlobalSleepTimer,
+    ConfigGlobalSleepTimerCancel, ConfigGlobalUp, ConfigGlobalVolumeDown, ConfigGlobalVolumeUp,
+    ConfigGlobalXywhHide, ConfigGlobalXywhMoveDown, ConfigGlobalXywhMoveLeft,
+    ConfigGlobalXywhMoveRight, ConfigGlobalXywhMoveUp, ConfigGlobalXywhZoomIn,
+    ConfigGlobalXywhZoomOut, ConfigLibraryAddRoot, ConfigLibraryBackground, ConfigLibraryBorder,
+    ConfigLibraryDelete, ConfigLibraryForeground, ConfigLibraryHighlight,
     ConfigLibraryHighlightSymbol, ConfigLibraryLoadDir, ConfigLibraryPaste,
     ConfigLibraryRemoveRoot, ConfigLibrarySearch, ConfigLibrarySearchYoutube,
     ConfigLibrarySwitchRoot, ConfigLibraryTagEditor, ConfigLibraryTitle, ConfigLibraryYank,
-    ConfigLyricBackground, ConfigLyricBorder, ConfigLyricForeground, ConfigLyricTitle,
-    ConfigPlaylistAddFront, ConfigPlaylistBackground, ConfigPlaylistBorder, ConfigPlaylistDelete,
-    ConfigPlaylistDeleteAll, ConfigPlaylistForeground, ConfigPlaylistHighlight,
-    ConfigPlaylistHighlightSymbol, ConfigPlaylistLqueue, ConfigPlaylistModeCycle,
-    ConfigPlaylistPlaySelected, ConfigPlaylistSearch, ConfigPlaylistShuffle,
-    ConfigPlaylistSwapDown, ConfigPlaylistSwapUp, ConfigPlaylistTitle, ConfigPlaylistTqueue,
-    ConfigPodcastDeleteAllFeeds, ConfigPodcastDeleteFeed, ConfigPodcastEpDeleteFile,
-    ConfigPodcastEpDownload, ConfigPodcastMarkAllPlayed, ConfigPodcastMarkPlayed,
+    ConfigLyricBackground, ConfigLyricBorder, ConfigLyricForeground, ConfigLyricHighlight,
+    ConfigLyricTitle, ConfigPlaylistAddFront, ConfigPlaylistBackground, ConfigPlaylistBorder,
+    ConfigPlaylistDelete, ConfigPlaylistDeleteAll, ConfigPlaylistDownload,
+    ConfigPlaylistForeground, ConfigPlaylistHighlight, ConfigPlaylistHighlightSymbol,
+    ConfigPlaylistLqueue, ConfigPlaylistModeCycle, ConfigPlaylistPlaySelected,
+    ConfigPlaylistSearch, ConfigPlaylistShuffle, ConfigPlaylistSwapDown, ConfigPlaylistSwapUp,
+    ConfigPlaylistTitle, ConfigPlaylistTqueue, ConfigPodcastDeleteAllFeeds,
+    ConfigPodcastDeleteFeed, ConfigPodcastEpDeleteFile, ConfigPodcastEpDownload,
+    ConfigPodcastEpisodeShowNotes, ConfigPodcastMarkAllPlayed, ConfigPodcastMarkPlayed,
     ConfigPodcastRefreshAllFeeds, ConfigPodcastRefreshFeed, ConfigPodcastSearchAddFeed,
-    ConfigProgressBackground, ConfigProgressBorder, ConfigProgressForeground, ConfigProgressTitle,
-    ConfigSavePopup, ConfigSeekStep, ExitConfirmation, Footer, GlobalListener, MusicDir,
-    PlaylistDisplaySymbol, PlaylistRandomAlbum, PlaylistRandomTrack, PodcastDir, PodcastMaxRetries,
-    PodcastSimulDownload, SaveLastPosition,
+    ConfigPodcastSyncNow, ConfigPodcastToggleSkipSilence, ConfigProgressBackground,
+    ConfigProgressBorder, ConfigProgressForeground, ConfigProgressTitle, ConfigSavePopup,
+    ConfigSeekStep, ConfigTagEditorLookup, ConfigYoutubeSearchMark, ConfigYoutubeSearchMarkPage,
+    ConfigYoutubeSearchPlay, ExitConfirmation, Footer, GlobalListener, InvidiousInstances,
+    InvidiousPreferred, InvidiousTest, MusicDir, PlaylistDisplaySymbol, PlaylistRandomAlbum,
+    PlaylistRandomTrack, PodcastDir, PodcastMaxRetries, PodcastSimulDownload, SaveLastPosition,
 };
 use crate::utils::draw_area_in_absolute;
 
@@ -196,6 +201,51 @@ impl Model {
             .is_ok());
     }
 
+    pub fn view_config_editor_invidious(&mut self) {
+        assert!(self
+            .terminal
+            .raw_mut()
+            .draw(|f| {
+                let chunks_main = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Length(3),
+                            Constraint::Length(3),
+                            Constraint::Length(3),
+                            Constraint::Min(3),
+                            Constraint::Length(1),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(f.size());
+
+                self.app
+                    .view(&Id::ConfigEditor(IdConfigEditor::Header), f, chunks_main[0]);
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::InvidiousInstances),
+                    f,
+                    chunks_main[1],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::InvidiousPreferred),
+                    f,
+                    chunks_main[2],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::InvidiousTest),
+                    f,
+                    chunks_main[3],
+                );
+                self.app
+                    .view(&Id::ConfigEditor(IdConfigEditor::Footer), f, chunks_main[4]);
+
+                Self::view_config_editor_commons(f, &mut self.app);
+            })
+            .is_ok());
+    }
+
     fn view_config_editor_commons(f: &mut Frame<'_>, app: &mut Application<Id, Msg, NoUserEvent>) {
         // -- popups
         if app.mounted(&Id::ConfigEditor(IdConfigEditor::ConfigSavePopup)) {
@@ -311,6 +361,13 @@ impl Model {
             Ok(State::One(_)) => 3,
             _ => 8,
         };
+        let select_lyric_highlight_len = match self
+            .app
+            .state(&Id::ConfigEditor(IdConfigEditor::LyricHighlight))
+        {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
         assert!(self
             .terminal
             .raw_mut()
@@ -405,6 +462,7 @@ impl Model {
                             Constraint::Length(select_lyric_foreground_len),
                             Constraint::Length(select_lyric_background_len),
                             Constraint::Length(select_lyric_border_len),
+                            Constraint::Length(select_lyric_highlight_len),
                             Constraint::Min(3),
                         ]
                         .as_ref(),
@@ -525,6 +583,11 @@ impl Model {
                     f,
                     chunks_lyric[3],
                 );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::LyricHighlight),
+                    f,
+                    chunks_lyric[4],
+                );
                 Self::view_config_editor_commons(f, &mut self.app);
             })
             .is_ok());
@@ -750,6 +813,18 @@ impl Model {
             Ok(State::One(_)) => 3,
             _ => 8,
         };
+        let select_global_sleep_timer = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalSleepTimer),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_sleep_timer_cancel = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalSleepTimerCancel),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
 
         assert!(self
             .terminal
@@ -852,8 +927,8 @@ impl Model {
                             Constraint::Length(select_global_xywh_zoom_in),
                             Constraint::Length(select_global_xywh_zoom_out),
                             Constraint::Length(select_global_xywh_hide),
-                            // Constraint::Length(select_global_xywh_hide),
-                            // Constraint::Length(select_global_xywh_hide),
+                            Constraint::Length(select_global_sleep_timer),
+                            Constraint::Length(select_global_sleep_timer_cancel),
                             // Constraint::Length(select_global_xywh_hide),
                             Constraint::Min(0),
                         ]
@@ -1035,6 +1110,16 @@ impl Model {
                     f,
                     chunks_middle_column4[5],
                 );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalSleepTimer)),
+                    f,
+                    chunks_middle_column4[6],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalSleepTimerCancel)),
+                    f,
+                    chunks_middle_column4[7],
+                );
                 Self::view_config_editor_commons(f, &mut self.app);
             })
             .is_ok());
@@ -1252,6 +1337,62 @@ impl Model {
             _ => 8,
         };
 
+        let podcast_toggle_skip_silence_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::PodcastToggleSkipSilence),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+
+        let podcast_sync_now_len = match self.app.state(&Id::ConfigEditor(IdConfigEditor::Key(
+            IdKey::PodcastSyncNow,
+        ))) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+
+        let podcast_episode_show_notes_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::PodcastEpisodeShowNotes),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+
+        let playlist_download_len = match self.app.state(&Id::ConfigEditor(IdConfigEditor::Key(
+            IdKey::PlaylistDownload,
+        ))) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+
+        let tag_editor_lookup_len = match self.app.state(&Id::ConfigEditor(IdConfigEditor::Key(
+            IdKey::TagEditorLookup,
+        ))) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+
+        let youtube_search_mark_len = match self.app.state(&Id::ConfigEditor(IdConfigEditor::Key(
+            IdKey::YoutubeSearchMark,
+        ))) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+
+        let youtube_search_mark_page_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::YoutubeSearchMarkPage),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+
+        let youtube_search_play_len = match self.app.state(&Id::ConfigEditor(IdConfigEditor::Key(
+            IdKey::YoutubeSearchPlay,
+        ))) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+
         assert!(self
             .terminal
             .raw_mut()
@@ -1274,10 +1415,11 @@ impl Model {
                     .margin(0)
                     .constraints(
                         [
-                            Constraint::Ratio(1, 4),
-                            Constraint::Ratio(1, 4),
-                            Constraint::Ratio(1, 4),
-                            Constraint::Ratio(1, 4),
+                            Constraint::Ratio(1, 5),
+                            Constraint::Ratio(1, 5),
+                            Constraint::Ratio(1, 5),
+                            Constraint::Ratio(1, 5),
+                            Constraint::Ratio(1, 5),
                         ]
                         .as_ref(),
                     )
@@ -1356,11 +1498,30 @@ impl Model {
                             // Constraint::Length(podcast_ep_download_len),
                             // Constraint::Length(podcast_ep_delete_file_len),
                             // Constraint::Length(podcast_delete_feed_len),
+                            Constraint::Length(podcast_toggle_skip_silence_len),
+                            Constraint::Length(podcast_sync_now_len),
+                            Constraint::Length(podcast_episode_show_notes_len),
+                            Constraint::Length(playlist_download_len),
+                            Constraint::Length(tag_editor_lookup_len),
                             Constraint::Min(0),
                         ]
                         .as_ref(),
                     )
                     .split(chunks_middle[3]);
+
+                let chunks_middle_column5 = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Length(youtube_search_mark_len),
+                            Constraint::Length(youtube_search_mark_page_len),
+                            Constraint::Length(youtube_search_play_len),
+                            Constraint::Min(0),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(chunks_middle[4]);
                 self.app
                     .view(&Id::ConfigEditor(IdConfigEditor::Header), f, chunks_main[0]);
                 self.app
@@ -1527,6 +1688,46 @@ impl Model {
                     f,
                     chunks_middle_column4[3],
                 );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::PodcastToggleSkipSilence)),
+                    f,
+                    chunks_middle_column4[4],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::PodcastSyncNow)),
+                    f,
+                    chunks_middle_column4[5],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::PodcastEpisodeShowNotes)),
+                    f,
+                    chunks_middle_column4[6],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::PlaylistDownload)),
+                    f,
+                    chunks_middle_column4[7],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::TagEditorLookup)),
+                    f,
+                    chunks_middle_column4[8],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::YoutubeSearchMark)),
+                    f,
+                    chunks_middle_column5[0],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::YoutubeSearchMarkPage)),
+                    f,
+                    chunks_middle_column5[1],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::YoutubeSearchPlay)),
+                    f,
+                    chunks_middle_column5[2],
+                );
                 Self::view_config_editor_commons(f, &mut self.app);
             })
             .is_ok());
@@ -1654,6 +1855,32 @@ impl Model {
             )
             .is_ok());
 
+        // Mount invidious page
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::InvidiousInstances),
+                Box::new(InvidiousInstances::new(&self.config)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::InvidiousPreferred),
+                Box::new(InvidiousPreferred::new(&self.config)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::InvidiousTest),
+                Box::new(InvidiousTest::new(&self.config)),
+                vec![]
+            )
+            .is_ok());
+
         let config = self.config.clone();
         self.remount_config_color(&config);
 
@@ -1845,6 +2072,15 @@ impl Model {
             )
             .is_ok());
 
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::LyricHighlight),
+                Box::new(ConfigLyricHighlight::new(config)),
+                vec![]
+            )
+            .is_ok());
+
         assert!(self
             .app
             .remount(
@@ -2323,6 +2559,22 @@ impl Model {
                 vec![],
             )
             .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalSleepTimer)),
+                Box::new(ConfigGlobalSleepTimer::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalSleepTimerCancel)),
+                Box::new(ConfigGlobalSleepTimerCancel::new(config)),
+                vec![],
+            )
+            .is_ok());
         assert!(self
             .app
             .remount(
@@ -2395,6 +2647,70 @@ impl Model {
                 vec![],
             )
             .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::PodcastToggleSkipSilence)),
+                Box::new(ConfigPodcastToggleSkipSilence::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::PodcastSyncNow)),
+                Box::new(ConfigPodcastSyncNow::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::PodcastEpisodeShowNotes)),
+                Box::new(ConfigPodcastEpisodeShowNotes::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::PlaylistDownload)),
+                Box::new(ConfigPlaylistDownload::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::TagEditorLookup)),
+                Box::new(ConfigTagEditorLookup::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::YoutubeSearchMark)),
+                Box::new(ConfigYoutubeSearchMark::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::YoutubeSearchMarkPage)),
+                Box::new(ConfigYoutubeSearchMarkPage::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::YoutubeSearchPlay)),
+                Box::new(ConfigYoutubeSearchPlay::new(config)),
+                vec![],
+            )
+            .is_ok());
         self.theme_select_sync();
     }
 
@@ -2468,6 +2784,19 @@ impl Model {
             .umount(&Id::ConfigEditor(IdConfigEditor::SeekStep))
             .is_ok());
 
+        assert!(self
+            .app
+            .umount(&Id::ConfigEditor(IdConfigEditor::InvidiousInstances))
+            .is_ok());
+        assert!(self
+            .app
+            .umount(&Id::ConfigEditor(IdConfigEditor::InvidiousPreferred))
+            .is_ok());
+        assert!(self
+            .app
+            .umount(&Id::ConfigEditor(IdConfigEditor::InvidiousTest))
+            .is_ok());
+
         assert!(self
             .app
             .umount(&Id::ConfigEditor(IdConfigEditor::CEThemeSelect))
@@ -2554,6 +2883,10 @@ impl Model {
             .app
             .umount(&Id::ConfigEditor(IdConfigEditor::LyricBorder))
             .is_ok());
+        assert!(self
+            .app
+            .umount(&Id::ConfigEditor(IdConfigEditor::LyricHighlight))
+            .is_ok());
         assert!(self
             .app
             .umount(&Id::ConfigEditor(IdConfigEditor::LibraryHighlightSymbol))
@@ -2825,6 +3158,16 @@ impl Model {
                 IdKey::GlobalXywhHide,
             )))
             .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalSleepTimer,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalSleepTimerCancel,
+            )))
+            .ok();
 
         self.app
             .umount(&Id::ConfigEditor(IdConfigEditor::Key(
@@ -2871,6 +3214,46 @@ impl Model {
                 IdKey::PodcastSearchAddFeed,
             )))
             .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::PodcastToggleSkipSilence,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::PodcastSyncNow,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::PodcastEpisodeShowNotes,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::PlaylistDownload,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::TagEditorLookup,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::YoutubeSearchMark,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::YoutubeSearchMarkPage,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::YoutubeSearchPlay,
+            )))
+            .ok();
         assert!(self
             .app
             .remount(
@@ -2891,7 +3274,8 @@ impl Model {
 
             ConfigEditorLayout::Color => self.config_layout = ConfigEditorLayout::Key1,
             ConfigEditorLayout::Key1 => self.config_layout = ConfigEditorLayout::Key2,
-            ConfigEditorLayout::Key2 => self.config_layout = ConfigEditorLayout::General,
+            ConfigEditorLayout::Key2 => self.config_layout = ConfigEditorLayout::Invidious,
+            ConfigEditorLayout::Invidious => self.config_layout = ConfigEditorLayout::General,
         }
 
         let layout = self.config_layout.clone();
@@ -2922,6 +3306,10 @@ impl Model {
                     IdKey::LibraryTagEditor,
                 )))
                 .ok(),
+            ConfigEditorLayout::Invidious => self
+                .app
+                .active(&Id::ConfigEditor(IdConfigEditor::InvidiousInstances))
+                .ok(),
         };
     }
 
@@ -3069,6 +3457,27 @@ impl Model {
             };
             self.config.seek_step = seek_step;
         }
+
+        if let Ok(State::One(StateValue::String(instances))) = self
+            .app
+            .state(&Id::ConfigEditor(IdConfigEditor::InvidiousInstances))
+        {
+            self.config.invidious_instances = instances
+                .split(';')
+                .map(str::trim)
+                .filter(|i| !i.is_empty())
+                .map(std::string::ToString::to_string)
+                .collect();
+        }
+        if let Ok(State::One(StateValue::String(preferred))) = self
+            .app
+            .state(&Id::ConfigEditor(IdConfigEditor::InvidiousPreferred))
+        {
+            self.config.invidious_preferred_instance = preferred.trim().to_string();
+        }
+        self.player
+            .playlist
+            .set_invidious(InstanceSettings::new(&self.config));
         Ok(())
     }
 }
@@ -304,6 +304,14 @@ impl Component<Msg, NoUserEvent> for DBListSearchResult {
             Event::Keyboard(keyevent) if keyevent == self.keys.library_search.key_event() => {
                 return Some(Msg::GeneralSearch(crate::ui::GSMsg::PopupShowDatabase))
             }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.library_tag_editor_open.key_event() =>
+            {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::DataBase(DBMsg::TagEditorBatch(index)));
+                }
+                CmdResult::None
+            }
 
             _ => CmdResult::None,
         };
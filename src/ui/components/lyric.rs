@@ -1,36 +1,217 @@
 use crate::config::Settings;
+use crate::podcast::show_notes::ShowNotes;
 use crate::podcast::Episode;
+use crate::songtag::lrc::TimedWord;
 use crate::track::MediaType;
 use crate::ui::{model::TermusicLayout, Id, LyricMsg, Model, Msg};
 
 use anyhow::{anyhow, Result};
-use lazy_static::lazy_static;
-use regex::Regex;
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+use tui_realm_stdlib::utils::get_block;
 use tui_realm_stdlib::Textarea;
 // use tui_realm_textarea::TextArea;
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
 use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
 use tuirealm::props::{
-    Alignment, AttrValue, Attribute, BorderType, Borders, Color, PropPayload, PropValue, TextSpan,
+    Alignment, AttrValue, Attribute, BorderType, Borders, Color, PropPayload, PropValue, Style,
+    TextModifiers, TextSpan,
 };
-use tuirealm::{Component, Event, MockComponent, State, StateValue};
+use tuirealm::tui::layout::Rect;
+use tuirealm::tui::text::{Span, Spans};
+use tuirealm::tui::widgets::Paragraph;
+use tuirealm::{Component, Event, Frame, MockComponent, State, StateValue};
+
+/// Lines of synced lyrics, shown centered on the current line.
+const ATTR_LINES: &str = "lines";
+/// Index of the line being sung.
+const ATTR_CURRENT: &str = "current";
+/// Words of the current line, styled by how far they are sung.
+const ATTR_KARAOKE: &str = "karaoke";
 
-lazy_static! {
-    /// Regex for finding <br/> tags -- also captures any surrounding
-    /// line breaks
-    static ref RE_BR_TAGS: Regex = Regex::new(r"((\r\n)|\r|\n)*<br */?>((\r\n)|\r|\n)*").expect("Regex error");
+pub struct Lyric {
+    component: Textarea,
+    keys: crate::config::Keys,
+    /// Set for synced lyrics, and drawn in place of the text rows.
+    lines: Vec<String>,
+    current: usize,
+    /// Set with lyrics with word timing, replaces the current line.
+    karaoke: Option<Vec<TextSpan>>,
+    /// Line picked by scrolling, the view follows the song again after `scroll_timeout`.
+    selected: Option<(usize, Instant)>,
+    context_lines: usize,
+    scroll_timeout: Duration,
+    highlight: Color,
+}
 
-    /// Regex for finding HTML tags
-    static ref RE_HTML_TAGS: Regex = Regex::new(r"<[^<>]*>").expect("Regex error");
+/// The part of the synced lyrics that fits in the pane.
+#[derive(Debug, PartialEq, Eq)]
+struct LineWindow {
+    /// Line picked by scrolling, until it times out.
+    selected: Option<usize>,
+    /// Blank rows above the first line, so the centered line stays in the middle of the pane.
+    padding: usize,
+    lines: Range<usize>,
+}
 
-    /// Regex for finding more than two line breaks
-    static ref RE_MULT_LINE_BREAKS: Regex = Regex::new(r"((\r\n)|\r|\n){3,}").expect("Regex error");
+/// Centers the window on the selected line, or on `current` once `scroll_timeout` passed since
+/// the selection. `selected` comes with the time since it was picked. None without any lines,
+/// when the lyrics have no time stamps.
+fn line_window(
+    len: usize,
+    current: usize,
+    selected: Option<(usize, Duration)>,
+    scroll_timeout: Duration,
+    context_lines: usize,
+    height: usize,
+) -> Option<LineWindow> {
+    let last = len.checked_sub(1)?;
+    let selected = selected
+        .filter(|(_, elapsed)| *elapsed < scroll_timeout)
+        .map(|(index, _)| index.min(last));
+    let center = selected.unwrap_or(current).min(last);
+    let context = context_lines.min(height.saturating_sub(1) / 2);
+    let first = center.saturating_sub(context);
+    Some(LineWindow {
+        selected,
+        padding: height / 2 - (center - first),
+        lines: first..(center + context + 1).min(len),
+    })
 }
 
-#[derive(MockComponent)]
-pub struct Lyric {
-    component: Textarea,
-    keys: crate::config::Keys,
+impl MockComponent for Lyric {
+    fn view(&mut self, render: &mut Frame<'_>, area: Rect) {
+        let Some(window) = line_window(
+            self.lines.len(),
+            self.current,
+            self.selected.map(|(index, at)| (index, at.elapsed())),
+            self.scroll_timeout,
+            self.context_lines,
+            usize::from(area.height.saturating_sub(2)),
+        ) else {
+            self.component.view(render, area);
+            return;
+        };
+        if window.selected.is_none() {
+            self.selected = None;
+        }
+        let foreground = self
+            .query(Attribute::Foreground)
+            .map_or(Color::Reset, AttrValue::unwrap_color);
+        let background = self
+            .query(Attribute::Background)
+            .map_or(Color::Reset, AttrValue::unwrap_color);
+        let title = self
+            .query(Attribute::Title)
+            .map_or((String::new(), Alignment::Left), AttrValue::unwrap_title);
+        let borders = self
+            .query(Attribute::Borders)
+            .map_or_else(Borders::default, AttrValue::unwrap_borders);
+        let focus = self
+            .query(Attribute::Focus)
+            .is_some_and(AttrValue::unwrap_flag);
+
+        let mut rows: Vec<Spans<'_>> = vec![Spans::from(""); window.padding];
+        for (index, line) in self
+            .lines
+            .iter()
+            .enumerate()
+            .take(window.lines.end)
+            .skip(window.lines.start)
+        {
+            let mut spans = match &self.karaoke {
+                Some(words) if index == self.current => words
+                    .iter()
+                    .map(|w| {
+                        let style = match w.fg {
+                            Color::Reset => Style::default(),
+                            fg => Style::default().fg(fg),
+                        };
+                        Span::styled(w.content.as_str(), style.add_modifier(w.modifiers))
+                    })
+                    .collect(),
+                _ if index == self.current => vec![Span::styled(
+                    line.as_str(),
+                    Style::default()
+                        .fg(self.highlight)
+                        .add_modifier(TextModifiers::BOLD),
+                )],
+                _ => vec![Span::raw(line.as_str())],
+            };
+            if window.selected == Some(index) {
+                for span in &mut spans {
+                    span.style = span.style.add_modifier(TextModifiers::REVERSED);
+                }
+            }
+            rows.push(Spans::from(spans));
+        }
+
+        render.render_widget(
+            Paragraph::new(rows)
+                .block(get_block(borders, Some(title), focus, None))
+                .style(Style::default().fg(foreground).bg(background))
+                .alignment(Alignment::Center),
+            area,
+        );
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.component.query(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        match (attr, value) {
+            (Attribute::Custom(ATTR_LINES), AttrValue::Payload(PropPayload::Vec(lines))) => {
+                self.lines = lines.into_iter().map(PropValue::unwrap_str).collect();
+                self.current = 0;
+                self.karaoke = None;
+                self.selected = None;
+            }
+            (Attribute::Custom(ATTR_CURRENT), AttrValue::Length(current)) => {
+                self.current = current;
+                self.karaoke = None;
+            }
+            (Attribute::Custom(ATTR_KARAOKE), AttrValue::Payload(PropPayload::Vec(words))) => {
+                self.karaoke = Some(words.into_iter().map(PropValue::unwrap_text_span).collect());
+            }
+            (attr, value) => {
+                if attr == Attribute::Text {
+                    self.lines.clear();
+                    self.karaoke = None;
+                    self.selected = None;
+                }
+                self.component.attr(attr, value);
+            }
+        }
+    }
+
+    fn state(&self) -> State {
+        self.component.state()
+    }
+
+    fn perform(&mut self, cmd: Cmd) -> CmdResult {
+        if self.lines.is_empty() {
+            return self.component.perform(cmd);
+        }
+        let last = self.lines.len() - 1;
+        let selected = self.selected.map_or(self.current, |(index, _)| index);
+        let selected = match cmd {
+            Cmd::Move(Direction::Down) => (selected + 1).min(last),
+            Cmd::Move(Direction::Up) => selected.saturating_sub(1),
+            Cmd::Scroll(Direction::Down) => (selected + 4).min(last),
+            Cmd::Scroll(Direction::Up) => selected.saturating_sub(4),
+            Cmd::GoTo(Position::Begin) => 0,
+            Cmd::GoTo(Position::End) => last,
+            Cmd::Submit => {
+                self.selected = None;
+                return CmdResult::Submit(State::One(StateValue::Usize(selected)));
+            }
+            _ => return CmdResult::None,
+        };
+        self.selected = Some((selected, Instant::now()));
+        CmdResult::Changed(State::One(StateValue::Usize(selected)))
+    }
 }
 
 impl Lyric {
@@ -68,6 +249,16 @@ impl Lyric {
                     crate::player::Status::Stopped
                 ))]),
             keys: config.keys.clone(),
+            lines: Vec::new(),
+            current: 0,
+            karaoke: None,
+            selected: None,
+            context_lines: config.lyric_context_lines,
+            scroll_timeout: Duration::from_secs(config.lyric_scroll_timeout),
+            highlight: config
+                .style_color_symbol
+                .lyric_highlight()
+                .unwrap_or(Color::LightYellow),
         }
     }
 }
@@ -99,6 +290,15 @@ impl Component<Msg, NoUserEvent> for Lyric {
                 code: Key::End,
                 modifiers: KeyModifiers::NONE,
             }) => self.perform(Cmd::GoTo(Position::End)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter,
+                modifiers: KeyModifiers::NONE,
+            }) => match self.perform(Cmd::Submit) {
+                CmdResult::Submit(State::One(StateValue::Usize(index))) => {
+                    return Some(Msg::LyricMessage(LyricMsg::SeekToLine(index)))
+                }
+                _ => CmdResult::None,
+            },
             Event::Keyboard(KeyEvent {
                 code: Key::Tab,
                 modifiers: KeyModifiers::NONE,
@@ -189,27 +389,14 @@ impl Model {
     }
 
     pub fn lyric_update_for_episode_after(&mut self, po_title: &str, ep: &Episode) {
-        // convert <br/> tags to a single line break
-        let br_to_lb = RE_BR_TAGS.replace_all(&ep.description, "\n");
-
-        // strip all HTML tags
-        let stripped_tags = RE_HTML_TAGS.replace_all(&br_to_lb, "");
-
-        // convert HTML entities (e.g., &amp;)
-        let decoded = match escaper::decode_html(&stripped_tags) {
-            Err(_) => stripped_tags.to_string(),
-            Ok(s) => s,
-        };
-
-        // remove anything more than two line breaks (i.e., one blank line)
-        let no_line_breaks = RE_MULT_LINE_BREAKS.replace_all(&decoded, "\n\n");
+        // html show notes as plain lines, with links numbered as footnotes
+        let notes = ShowNotes::parse(&ep.description).plain_lines();
 
         let (term_width, _) = viuer::terminal_size();
         let term_width = usize::from(term_width);
         let lyric_width = term_width * 3 / 5;
-        let lines_vec: Vec<_> = no_line_breaks.split('\n').collect();
         let mut short_string_vec: Vec<_> = Vec::new();
-        for l in lines_vec {
+        for l in notes.iter().map(String::as_str) {
             let unicode_width = unicode_width::UnicodeWidthStr::width(l);
             if unicode_width > lyric_width {
                 let mut string_tmp = textwrap::wrap(l, lyric_width);
@@ -243,6 +430,7 @@ impl Model {
         final_vec.push(PropValue::TextSpan(TextSpan::from("Description:").bold()));
         final_vec.append(&mut lines_textspan);
 
+        self.lyric_clear_lines();
         self.app
             .attr(
                 &Id::Lyric,
@@ -263,39 +451,151 @@ impl Model {
             self.lyric_set_lyric("Stopped.");
             return;
         }
+        let position = self.lyric_position_ms();
         if let Some(song) = self.player.playlist.current_track() {
             if song.lyric_frames_is_empty() {
                 self.lyric_set_lyric("No lyrics available.");
                 return;
             }
 
-            let mut line = String::new();
-            if let Some(l) = song.parsed_lyric() {
-                if l.unsynced_captions.is_empty() {
-                    return;
-                }
-                if let Some(l) = l.get_text(self.time_pos) {
-                    line = l;
+            let Some(l) = song.parsed_lyric() else {
+                self.lyric_set_lyric("");
+                return;
+            };
+            if l.unsynced_captions.is_empty() {
+                // lyrics without time stamps are shown as they are
+                if let Some(text) = song.lyric_selected().map(|f| f.text.clone()) {
+                    self.lyric_set_lyric(&text);
                 }
-            }
-            if self.lyric_line == line {
                 return;
             }
-            self.lyric_set_lyric(&line);
-            self.lyric_line = line;
+            let lines = l.lines();
+            let (current, words) = match l.get_words(position) {
+                Some((current, words, index)) => (current, Some((words.to_vec(), index))),
+                None => (l.get_index(self.time_pos).unwrap_or(0), None),
+            };
+            self.lyric_set_lines(lines, current);
+            if let Some((words, index)) = words {
+                self.lyric_set_karaoke(&words, index);
+            }
+        }
+    }
+
+    // Progress is reported once a second, so follow the clock in between.
+    #[allow(clippy::cast_possible_truncation)]
+    fn lyric_position_ms(&self) -> i64 {
+        let mut ms = self.time_pos * 1000;
+        if !self.player.playlist.is_paused() {
+            ms += self.time_pos_at.elapsed().as_millis().min(999) as i64;
+        }
+        ms
+    }
+
+    fn lyric_set_lines(&mut self, lines: Vec<String>, current: usize) {
+        if self.lyric_lines != lines {
+            let payload = lines.iter().cloned().map(PropValue::Str).collect();
+            self.app
+                .attr(
+                    &Id::Lyric,
+                    Attribute::Custom(ATTR_LINES),
+                    AttrValue::Payload(PropPayload::Vec(payload)),
+                )
+                .ok();
+            self.lyric_lines = lines;
+            self.lyric_current = None;
+        }
+        if self.lyric_current == Some(current) {
+            return;
         }
+        self.app
+            .attr(
+                &Id::Lyric,
+                Attribute::Custom(ATTR_CURRENT),
+                AttrValue::Length(current),
+            )
+            .ok();
+        self.lyric_current = Some(current);
+        self.lyric_word = None;
+        self.force_redraw();
+    }
+
+    fn lyric_set_karaoke(&mut self, words: &[TimedWord], index: usize) {
+        if self.lyric_word == Some(index) {
+            return;
+        }
+        let highlight = self
+            .config
+            .style_color_symbol
+            .lyric_highlight()
+            .unwrap_or(Color::LightYellow);
+        let spans = words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                let span = TextSpan::new(&w.text);
+                let span = match i.cmp(&index) {
+                    Ordering::Less => span.fg(highlight),
+                    Ordering::Equal => span.fg(highlight).bold(),
+                    Ordering::Greater => span,
+                };
+                PropValue::TextSpan(span)
+            })
+            .collect();
+        self.app
+            .attr(
+                &Id::Lyric,
+                Attribute::Custom(ATTR_KARAOKE),
+                AttrValue::Payload(PropPayload::Vec(spans)),
+            )
+            .ok();
+        self.lyric_word = Some(index);
+        self.force_redraw();
+    }
+
+    // Setting the text drops the lines from the view, so they are pushed again on next update.
+    fn lyric_clear_lines(&mut self) {
+        self.lyric_lines.clear();
+        self.lyric_current = None;
+        self.lyric_word = None;
     }
 
     fn lyric_set_lyric(&mut self, text: &str) {
+        self.lyric_clear_lines();
         self.app
             .attr(
                 &Id::Lyric,
                 Attribute::Text,
-                AttrValue::Payload(PropPayload::Vec(vec![PropValue::TextSpan(TextSpan::from(
-                    text,
-                ))])),
+                AttrValue::Payload(PropPayload::Vec(
+                    text.lines()
+                        .map(|l| PropValue::TextSpan(TextSpan::from(l)))
+                        .collect(),
+                )),
             )
             .ok();
+        self.lyric_line = text.to_string();
+    }
+
+    pub fn lyric_seek_to_line(&mut self, index: usize) {
+        let Some(time) = self
+            .player
+            .playlist
+            .current_track()
+            .and_then(crate::track::Track::parsed_lyric)
+            .and_then(|l| l.line_time(index))
+        else {
+            return;
+        };
+        self.player_seek(time / 1000 - self.time_pos);
+    }
+
+    pub fn lyric_load_files(&mut self) {
+        if let Some(mut song) = self.player.playlist.current_track_as_mut() {
+            if matches!(song.media_type, Some(MediaType::Music))
+                && song.load_lyric_files(&self.config.lyric_dir)
+            {
+                self.player.playlist.set_current_track(Some(&song));
+            }
+        }
     }
 
     pub fn lyric_cycle(&mut self) {
@@ -323,7 +623,7 @@ impl Model {
         let mut lyric_title = " No track is playing ".to_string();
         if let Some(song) = self.player.playlist.current_track() {
             match song.media_type {
-                Some(MediaType::Music) => {
+                Some(MediaType::Music | MediaType::Youtube) => {
                     let artist = song.artist().unwrap_or("Unknown Artist");
                     let title = song.title().unwrap_or("Unknown Title");
                     lyric_title = format!(" Lyrics of {artist:^.20} - {title:^.20} ");
@@ -347,3 +647,73 @@ impl Model {
             .ok();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const TIMEOUT: Duration = Duration::from_secs(3);
+
+    #[test]
+    fn test_line_window_first_line() {
+        assert_eq!(
+            line_window(10, 0, None, TIMEOUT, 3, 9),
+            Some(LineWindow {
+                selected: None,
+                padding: 4,
+                lines: 0..4,
+            })
+        );
+    }
+
+    #[test]
+    fn test_line_window_last_line() {
+        let window = Some(LineWindow {
+            selected: None,
+            padding: 1,
+            lines: 6..10,
+        });
+        assert_eq!(line_window(10, 9, None, TIMEOUT, 3, 9), window);
+        // an index past the end, left over from longer lyrics, sticks to the last line
+        assert_eq!(line_window(10, 12, None, TIMEOUT, 3, 9), window);
+        // fewer rows than context lines
+        assert_eq!(
+            line_window(10, 9, None, TIMEOUT, 3, 4),
+            Some(LineWindow {
+                selected: None,
+                padding: 1,
+                lines: 8..10,
+            })
+        );
+    }
+
+    #[test]
+    fn test_line_window_no_timestamps() {
+        assert_eq!(line_window(0, 0, None, TIMEOUT, 3, 9), None);
+        assert_eq!(
+            line_window(0, 0, Some((2, Duration::ZERO)), TIMEOUT, 3, 9),
+            None
+        );
+    }
+
+    #[test]
+    fn test_line_window_scroll_timeout() {
+        assert_eq!(
+            line_window(10, 1, Some((5, Duration::from_secs(1))), TIMEOUT, 1, 9),
+            Some(LineWindow {
+                selected: Some(5),
+                padding: 3,
+                lines: 4..7,
+            })
+        );
+        assert_eq!(
+            line_window(10, 1, Some((5, TIMEOUT)), TIMEOUT, 1, 9),
+            Some(LineWindow {
+                selected: None,
+                padding: 3,
+                lines: 0..3,
+            })
+        );
+    }
+}
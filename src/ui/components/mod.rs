@@ -32,6 +32,7 @@ mod playlist;
 mod podcast;
 mod popups;
 mod progress;
+mod show_notes;
 #[allow(
     clippy::match_bool,
     clippy::redundant_closure_for_method_calls,
@@ -54,7 +55,7 @@ pub use podcast::{EpisodeList, FeedsList};
 pub use popups::{
     DeleteConfirmInputPopup, DeleteConfirmRadioPopup, ErrorPopup, FeedDeleteConfirmInputPopup,
     FeedDeleteConfirmRadioPopup, HelpPopup, MessagePopup, PodcastAddPopup, QuitPopup,
-    SavePlaylistConfirm, SavePlaylistPopup,
+    SavePlaylistConfirm, SavePlaylistPopup, SleepTimerPopup,
 };
 pub use progress::Progress;
 pub use youtube_search::{YSInputPopup, YSTablePopup};
@@ -227,6 +228,14 @@ impl Component<Msg, NoUserEvent> for GlobalListener {
             Event::Keyboard(keyevent) if keyevent == self.keys.global_xywh_hide.key_event() => {
                 Some(Msg::Xywh(XYWHMsg::Hide))
             }
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_sleep_timer.key_event() => {
+                Some(Msg::SleepTimerPopupShow)
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_sleep_timer_cancel.key_event() =>
+            {
+                Some(Msg::SleepTimerCancel)
+            }
             _ => None,
         }
     }
@@ -329,6 +338,14 @@ impl Model {
                 SubEventClause::Keyboard(keys.global_layout_podcast.key_event()),
                 Self::no_popup_mounted_clause(),
             ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_sleep_timer.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_sleep_timer_cancel.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
             Sub::new(
                 SubEventClause::Keyboard(keys.global_xywh_move_left.key_event()),
                 SubClause::Always,
@@ -371,7 +388,13 @@ impl Model {
             Box::new(SubClause::IsMounted(Id::FeedDeleteConfirmRadioPopup)),
             Box::new(SubClause::Or(
                 Box::new(SubClause::IsMounted(Id::FeedDeleteConfirmInputPopup)),
-                Box::new(SubClause::IsMounted(Id::PodcastSearchTablePopup)),
+                Box::new(SubClause::Or(
+                    Box::new(SubClause::IsMounted(Id::PodcastSearchTablePopup)),
+                    Box::new(SubClause::Or(
+                        Box::new(SubClause::IsMounted(Id::ShowNotesPopup)),
+                        Box::new(SubClause::IsMounted(Id::SleepTimerPopup)),
+                    )),
+                )),
             )),
         )))
     }
@@ -1,10 +1,10 @@
 use crate::{
     config::{Keys, Settings},
-    track::Track,
+    invidious::YoutubeStream,
+    track::{MediaType, Track},
     ui::{GSMsg, Id, Model, Msg, PLMsg},
 };
 
-use crate::player::PlayerTrait;
 use crate::sqlite::TrackForDB;
 use crate::ui::model::TermusicLayout;
 use crate::utils::{filetype_supported, get_parent_folder, is_playlist, playlist_get_vec};
@@ -139,6 +139,14 @@ impl Component<Msg, NoUserEvent> for Playlist {
                     _ => return Some(Msg::None),
                 }
             }
+            Event::Keyboard(key) if key == self.keys.playlist_download.key_event() => {
+                match self.component.state() {
+                    State::One(StateValue::Usize(index_selected)) => {
+                        return Some(Msg::Playlist(PLMsg::Download(index_selected)))
+                    }
+                    _ => return Some(Msg::None),
+                }
+            }
             Event::Keyboard(key) if key == self.keys.playlist_delete_all.key_event() => {
                 return Some(Msg::Playlist(PLMsg::DeleteAll))
             }
@@ -482,14 +490,38 @@ impl Model {
             .ok();
     }
     pub fn playlist_play_selected(&mut self, index: usize) {
-        self.player_save_last_position();
         if let Some(song) = self.player.playlist.remove(index) {
             self.player.playlist.push_front(&song);
             self.playlist_sync();
-            self.player.stop();
-            // self.status = Some(Status::Stopped);
-            // self.player_next();
+            self.player.play_now();
+        }
+    }
+
+    /// Plays a youtube stream right away, the same way as selecting a track in the playlist.
+    pub fn playlist_play_youtube(&mut self, stream: &YoutubeStream) {
+        self.player
+            .playlist
+            .push_front(&Track::from_youtube(stream));
+        self.playlist_sync();
+        self.player.play_now();
+    }
+
+    /// Downloads a streamed youtube track with the selected download profile.
+    pub fn playlist_download(&mut self, index: usize) -> Result<()> {
+        let track = self
+            .player
+            .playlist
+            .tracks()
+            .get(index)
+            .ok_or_else(|| anyhow!("get track selected failed."))?;
+        if !matches!(track.media_type, Some(MediaType::Youtube)) {
+            bail!("only youtube tracks can be downloaded");
         }
+        let url = track
+            .file()
+            .ok_or_else(|| anyhow!("youtube track without url"))?
+            .to_string();
+        self.youtube_dl(&url)
     }
 
     pub fn playlist_update_search(&mut self, input: &str) {
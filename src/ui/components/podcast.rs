@@ -1,12 +1,14 @@
 use crate::config::{Keys, Settings};
+use crate::hooks::Hook;
+use crate::podcast::db::Database;
+use crate::podcast::sync::{SyncClient, SyncOutcome};
 use crate::podcast::{download_list, EpData, PodcastFeed, PodcastNoId};
 use crate::track::MediaType;
 use crate::ui::{Id, Model, Msg, PCMsg};
+use crate::utils::get_app_config_path;
+use crate::utils::StringUtils;
 use anyhow::{anyhow, bail, Result};
-use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use sanitize_filename::{sanitize_with_options, Options};
-use serde_json::Value;
-use std::time::Duration;
 use tui_realm_stdlib::List;
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
 use tuirealm::props::{Alignment, BorderType, TableBuilder, TextSpan};
@@ -18,6 +20,9 @@ use tuirealm::{
 // left for debug
 // use std::io::Write;
 
+/// Longer category names are cut in the feeds list.
+const CATEGORY_MAX_LENGTH: usize = 16;
+
 #[derive(MockComponent)]
 pub struct FeedsList {
     component: List,
@@ -185,6 +190,17 @@ impl Component<Msg, NoUserEvent> for FeedsList {
             {
                 return Some(Msg::Podcast(PCMsg::FeedsDeleteShow));
             }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.podcast_toggle_skip_silence.key_event() =>
+            {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Podcast(PCMsg::FeedToggleSkipSilence(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent) if keyevent == self.keys.podcast_sync_now.key_event() => {
+                return Some(Msg::Podcast(PCMsg::RemoteSyncStart));
+            }
             _ => CmdResult::None,
         };
         Some(Msg::None)
@@ -362,6 +378,15 @@ impl Component<Msg, NoUserEvent> for EpisodeList {
                 }
                 CmdResult::None
             }
+
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.podcast_episode_show_notes.key_event() =>
+            {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Podcast(PCMsg::ShowNotesPopupShow(index)));
+                }
+                CmdResult::None
+            }
             _ => CmdResult::None,
         };
         Some(Msg::None)
@@ -369,72 +394,86 @@ impl Component<Msg, NoUserEvent> for EpisodeList {
 }
 
 impl Model {
-    pub fn podcast_search_itunes(&self, search_str: &str) {
-        let encoded: String = utf8_percent_encode(search_str, NON_ALPHANUMERIC).to_string();
-        let url = format!(
-            "https://itunes.apple.com/search?media=podcast&entity=podcast&term={}",
-            encoded
-        );
-        let agent = ureq::builder()
-            .timeout_connect(Duration::from_secs(5))
-            .timeout_read(Duration::from_secs(20))
-            .build();
-        // let result = agent.get(&url).call()?;
-
-        let mut max_retries = self.config.podcast_max_retries;
-
+    pub fn podcast_search(&self, search_str: &str) {
+        let providers = crate::podcast::search::providers(&self.config);
+        let max_retries = self.config.podcast_max_retries;
+        let search_str = search_str.to_string();
         let tx = self.tx_to_main.clone();
 
         std::thread::spawn(move || {
-            let request: Result<ureq::Response> = loop {
-                let response = agent.get(&url).call();
-                if let Ok(resp) = response {
-                    break Ok(resp);
-                }
-                max_retries -= 1;
-                if max_retries == 0 {
-                    break Err(anyhow!("No response from feed"));
-                }
+            match crate::podcast::search::search_all(&providers, &search_str, max_retries) {
+                Ok(vec) => tx.send(Msg::Podcast(PCMsg::SearchSuccess(vec))).ok(),
+                Err(e) => tx
+                    .send(Msg::Podcast(PCMsg::SearchError(e.to_string())))
+                    .ok(),
             };
-            // below two lines are left for debug purpose
-            // let mut file = std::fs::File::create("data.txt").expect("create failed");
-            // file.write_all(result.into_string()?.as_bytes())
-            //     .expect("write failed");
-            match request {
-                Ok(result) => match result.status() {
-                    200 => match result.into_string() {
-                        Ok(text) => {
-                            if let Some(vec) = parse_itunes_results(&text) {
-                                tx.send(Msg::Podcast(PCMsg::SearchSuccess(vec))).ok();
-                            } else {
-                                tx.send(Msg::Podcast(PCMsg::SearchError(
-                                    "Error parsing result".to_string(),
-                                )))
-                                .ok();
-                            }
-                        }
-                        Err(_) => {
-                            tx.send(Msg::Podcast(PCMsg::SearchError(
-                                "Error in into_string".to_string(),
-                            )))
-                            .ok();
-                        }
-                    },
-                    _ => {
-                        tx.send(Msg::Podcast(PCMsg::SearchError(
-                            "Error result status code".to_string(),
-                        )))
-                        .ok();
-                    }
-                },
-                Err(e) => {
-                    tx.send(Msg::Podcast(PCMsg::SearchError(e.to_string())))
-                        .ok();
-                }
+        });
+    }
+
+    pub fn podcast_remote_sync(&mut self) {
+        let Some(client) = SyncClient::from_config(&self.config) else {
+            self.mount_error_popup("No podcast sync server set in config.");
+            return;
+        };
+        self.show_message_timeout_label_help("Syncing podcasts with server...", None, None, None);
+
+        let tx = self.tx_to_main.clone();
+        std::thread::spawn(move || {
+            let result = get_app_config_path()
+                .and_then(|path| Database::connect(&path))
+                .and_then(|db| client.sync(&db));
+            match result {
+                Ok(outcome) => tx.send(Msg::Podcast(PCMsg::RemoteSyncSuccess(outcome))),
+                Err(e) => tx.send(Msg::Podcast(PCMsg::RemoteSyncError(e.to_string()))),
             }
+            .ok();
         });
     }
 
+    /// Sends local changes to the sync server before quitting. This blocks, but only for as
+    /// long as the request timeouts allow.
+    pub fn podcast_remote_sync_push(&self) -> Result<()> {
+        if let Some(client) = SyncClient::from_config(&self.config) {
+            client.push(&self.db_podcast)?;
+        }
+        Ok(())
+    }
+
+    pub fn podcast_remote_sync_complete(&mut self, outcome: &SyncOutcome) -> Result<()> {
+        // changes from the server are applied without queueing them to be sent back
+        for url in &outcome.removed_feeds {
+            if let Some(index) = self.podcasts.iter().position(|pod| &pod.url == url) {
+                // downloaded episodes stay on disk, only the feed goes
+                let pod = self.podcasts.remove(index);
+                self.db_podcast.remove_podcast(pod.id)?;
+                self.db_podcast
+                    .clear_pending_subscriptions(&[], &[pod.url])?;
+            }
+        }
+        for url in &outcome.new_feeds {
+            self.podcast_synced_feeds.insert(url.clone());
+            self.podcast_add(url);
+        }
+
+        self.podcasts = self.db_podcast.get_podcasts()?;
+        self.podcasts_index = self
+            .podcasts_index
+            .min(self.podcasts.len().saturating_sub(1));
+        self.podcast_sync_feeds_and_episodes();
+        self.show_message_timeout_label_help(
+            format!(
+                "Podcast sync finished: {} new feeds, {} removed feeds (downloads kept), {} episodes updated.",
+                outcome.new_feeds.len(),
+                outcome.removed_feeds.len(),
+                outcome.episodes_updated
+            ),
+            None,
+            None,
+            None,
+        );
+        Ok(())
+    }
+
     pub fn podcast_add(&mut self, url: &str) {
         let feed = PodcastFeed::new(None, url, None);
 
@@ -448,18 +487,53 @@ impl Model {
     pub fn podcast_sync_feeds_and_episodes(&mut self) {
         let mut table: TableBuilder = TableBuilder::default();
 
+        // feeds are sorted by category, which is shown once at the start of each group
+        let category_width = self
+            .podcasts
+            .iter()
+            .filter_map(|pod| pod.category.as_ref())
+            .map(StringUtils::grapheme_len)
+            .max()
+            .unwrap_or(0)
+            .min(CATEGORY_MAX_LENGTH);
+        let category_color = self
+            .config
+            .style_color_symbol
+            .library_highlight()
+            .unwrap_or(Color::LightBlue);
+
         for (idx, record) in self.podcasts.iter().enumerate() {
             if idx > 0 {
                 table.add_row();
             }
+            if category_width > 0 {
+                let first_of_group = idx == 0 || self.podcasts[idx - 1].category != record.category;
+                let label = match &record.category {
+                    Some(category) if first_of_group => category.substr(0, category_width),
+                    _ => String::new(),
+                };
+                let padding = category_width - label.grapheme_len();
+                table.add_col(
+                    TextSpan::new(format!("{label}{} │ ", " ".repeat(padding)))
+                        .bold()
+                        .fg(category_color),
+                );
+            }
             let new = record.num_unplayed();
             let total = record.episodes.len();
+            let mut title = format!("{} ({new}/{total})", record.title);
+            if let Some(speed) = record.speed {
+                title = format!("{title} [{:.1}x]", f64::from(speed) / 10.0);
+            }
+            if record.skip_silence {
+                title.push_str(" [skip silence]");
+            }
             if new > 0 {
-                table.add_col(TextSpan::new(format!("{} ({new}/{total})", record.title)).bold());
+                table.add_col(TextSpan::new(title).bold());
                 continue;
             }
 
-            table.add_col(TextSpan::new(format!("{} ({new}/{total})", record.title)));
+            table.add_col(TextSpan::new(title));
         }
         if self.podcasts.is_empty() {
             table.add_col(TextSpan::from("empty feeds list"));
@@ -597,7 +671,23 @@ impl Model {
             db_result = self.db_podcast.insert_podcast(pod);
         }
         match db_result {
-            Ok(_result) => {
+            Ok(result) => {
+                if pod_id.is_none() && self.podcast_synced_feeds.remove(&pod.url) {
+                    self.db_podcast
+                        .clear_pending_subscriptions(std::slice::from_ref(&pod.url), &[])?;
+                }
+                // a newly added feed brings all its episodes, only the ones found by a sync are new
+                if pod_id.is_some() {
+                    for ep in &result.added {
+                        self.player.run_hook(
+                            Hook::NewEpisode,
+                            &[
+                                ("podcast", ep.pod_title.clone()),
+                                ("title", ep.title.clone()),
+                            ],
+                        );
+                    }
+                }
                 {
                     self.podcasts = self.db_podcast.get_podcasts()?;
                     self.podcast_sync_feeds_and_episodes();
@@ -820,6 +910,22 @@ impl Model {
         let podcasts = self.db_podcast.get_podcasts()?;
         self.podcasts = podcasts;
 
+        let podcast = self
+            .podcasts
+            .iter()
+            .find(|pod| pod.id == ep_data.pod_id)
+            .map(|pod| pod.title.clone())
+            .unwrap_or_default();
+        self.player.run_hook(
+            Hook::DownloadComplete,
+            &[
+                ("podcast", podcast),
+                ("title", ep_data.title),
+                ("url", ep_data.url),
+                ("file", file_path.to_string_lossy().to_string()),
+            ],
+        );
+
         self.podcast_sync_feeds_and_episodes();
         self.episode_update_playlist();
         Ok(())
@@ -946,9 +1052,10 @@ impl Model {
         Err(anyhow!("cannot get feed index"))
     }
 
-    pub fn podcast_mark_current_track_played(&mut self) -> Result<()> {
+    /// Shows the episode being played as played, the daemon stores it.
+    pub fn podcast_mark_current_track_played(&mut self) {
         if self.podcasts.is_empty() {
-            return Ok(());
+            return;
         }
         if let Some(track) = self.player.playlist.current_track() {
             if let Some(MediaType::Podcast) = track.media_type {
@@ -956,10 +1063,7 @@ impl Model {
                     'outer: for pod in &mut self.podcasts {
                         for ep in &mut pod.episodes {
                             if ep.url == url {
-                                if !ep.played {
-                                    ep.played = true;
-                                    self.db_podcast.set_played_status(ep.id, ep.played)?;
-                                }
+                                ep.played = true;
                                 break 'outer;
                             }
                         }
@@ -969,7 +1073,33 @@ impl Model {
         }
 
         self.podcast_sync_feeds_and_episodes();
+    }
+
+    /// Returns the index of the feed the current track belongs to, if it is a podcast.
+    pub fn podcast_get_current_feed_index(&self) -> Option<usize> {
+        let track = self.player.playlist.current_track()?;
+        if !matches!(track.media_type, Some(MediaType::Podcast)) {
+            return None;
+        }
+        let url = track.file()?;
+        self.podcasts
+            .iter()
+            .position(|pod| pod.episodes.iter().any(|ep| ep.url == url))
+    }
+
+    pub fn podcast_toggle_skip_silence(&mut self, index: usize) -> Result<()> {
+        let pod = self
+            .podcasts
+            .get_mut(index)
+            .ok_or_else(|| anyhow!("cannot find feed at index {index}"))?;
+        pod.skip_silence = !pod.skip_silence;
+        self.db_podcast
+            .set_podcast_skip_silence(pod.id, pod.skip_silence)?;
 
+        if self.podcast_get_current_feed_index() == Some(index) {
+            self.player_restore_speed();
+        }
+        self.podcast_sync_feeds_and_episodes();
         Ok(())
     }
 
@@ -1003,31 +1133,3 @@ impl Model {
         None
     }
 }
-
-fn parse_itunes_results(data: &str) -> Option<Vec<PodcastFeed>> {
-    if let Ok(value) = serde_json::from_str::<Value>(data) {
-        // below two lines are left for debug purpose
-        // let mut file = std::fs::File::create("data.txt").expect("create failed");
-        // file.write_all(data.as_bytes()).expect("write failed");
-
-        let mut vec: Vec<PodcastFeed> = Vec::new();
-        let array = value.get("results")?.as_array()?;
-        for v in array.iter() {
-            if let Some((title, url)) = parse_itunes_item(v) {
-                vec.push(PodcastFeed {
-                    id: None,
-                    url,
-                    title: Some(title),
-                });
-            }
-        }
-        return Some(vec);
-    }
-    None
-}
-
-fn parse_itunes_item(v: &Value) -> Option<(String, String)> {
-    let title = v.get("collectionName")?.as_str()?.to_owned();
-    let url = v.get("feedUrl")?.as_str()?.to_owned();
-    Some((title, url))
-}
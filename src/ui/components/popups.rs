@@ -258,6 +258,12 @@ impl HelpPopup {
                         .add_col(Self::key(&[keys.global_player_toggle_gapless]))
                         .add_col(Self::comment("Toggle gapless playback"))
                         .add_row()
+                        .add_col(Self::key(&[keys.global_sleep_timer]))
+                        .add_col(Self::comment("Set sleep timer or wake-up alarm"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_sleep_timer_cancel]))
+                        .add_col(Self::comment("Cancel sleep timer and alarm"))
+                        .add_row()
                         .add_col(Self::key(&[
                             keys.global_lyric_adjust_forward,
                             keys.global_lyric_adjust_backward,
@@ -318,6 +324,16 @@ impl HelpPopup {
                         .add_col(Self::key(&[keys.global_xywh_hide]))
                         .add_col(Self::comment("Hide/Show album cover"))
                         .add_row()
+                        .add_col(TextSpan::new("Lyrics").bold().fg(Color::LightYellow))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_up, keys.global_down]))
+                        .add_col(Self::comment(
+                            "Scroll lyrics, following the song after a while",
+                        ))
+                        .add_row()
+                        .add_col(TextSpan::new("<Enter>").bold().fg(Color::Cyan))
+                        .add_col(TextSpan::from("Seek to selected line"))
+                        .add_row()
                         .add_col(TextSpan::new("Library").bold().fg(Color::LightYellow))
                         .add_row()
                         .add_col(Self::key(&[keys.global_right, keys.library_load_dir]))
@@ -327,10 +343,14 @@ impl HelpPopup {
                         .add_col(Self::comment("Delete track or folder"))
                         .add_row()
                         .add_col(Self::key(&[keys.library_search_youtube]))
-                        .add_col(Self::comment("Search or download track from youtube"))
+                        .add_col(Self::comment(
+                            "Search or download from youtube, or list a playlist or channel",
+                        ))
                         .add_row()
                         .add_col(Self::key(&[keys.library_tag_editor_open]))
-                        .add_col(Self::comment("Open tag editor for tag and lyric download"))
+                        .add_col(Self::comment(
+                            "Open tag editor for tag and lyric download, batch editor on directories",
+                        ))
                         .add_row()
                         .add_col(Self::key(&[keys.library_yank, keys.library_paste]))
                         .add_col(Self::comment("Yank and Paste files"))
@@ -384,6 +404,9 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Select random tracks/albums to playlist"))
                         .add_row()
+                        .add_col(Self::key(&[keys.playlist_download]))
+                        .add_col(Self::comment("Download streamed youtube track"))
+                        .add_row()
                         .add_col(TextSpan::new("Database").bold().fg(Color::LightYellow))
                         .add_row()
                         .add_col(Self::key(&[keys.global_right, keys.database_add_all]))
@@ -392,6 +415,20 @@ impl HelpPopup {
                         .add_col(Self::key(&[keys.library_search]))
                         .add_col(Self::comment("Search in database"))
                         .add_row()
+                        .add_col(Self::key(&[keys.library_tag_editor_open]))
+                        .add_col(Self::comment("Batch edit tags of the selected result"))
+                        .add_row()
+                        .add_col(TextSpan::new("Youtube search").bold().fg(Color::LightYellow))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            keys.youtube_search_mark,
+                            keys.youtube_search_mark_page,
+                        ]))
+                        .add_col(Self::comment("Mark one/all results on the page"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.youtube_search_play]))
+                        .add_col(Self::comment("Play result without downloading"))
+                        .add_row()
                         .add_col(TextSpan::new("Podcast").bold().fg(Color::LightYellow))
                         .add_row()
                         .add_col(Self::key(&[keys.podcast_search_add_feed]))
@@ -409,6 +446,12 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Feeds : refresh one/all feeds"))
                         .add_row()
+                        .add_col(Self::key(&[keys.podcast_toggle_skip_silence]))
+                        .add_col(Self::comment("Feeds : toggle skipping silence"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.podcast_sync_now]))
+                        .add_col(Self::comment("Feeds : sync with gpodder server"))
+                        .add_row()
                         .add_col(Self::key(&[
                             keys.podcast_mark_played,
                             keys.podcast_mark_all_played,
@@ -420,6 +463,11 @@ impl HelpPopup {
                         .add_row()
                         .add_col(Self::key(&[keys.podcast_episode_delete_file]))
                         .add_col(Self::comment("Episode: delete episode local file"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.podcast_episode_show_notes]))
+                        .add_col(Self::comment(
+                            "Episode: show notes, open links, jump to times",
+                        ))
                         .build(),
                 ),
             keys: keys.clone(),
@@ -935,6 +983,82 @@ impl Component<Msg, NoUserEvent> for PodcastAddPopup {
     }
 }
 
+#[derive(MockComponent)]
+pub struct SleepTimerPopup {
+    component: Input,
+}
+
+impl SleepTimerPopup {
+    pub fn new(style_color_symbol: &StyleColorSymbol) -> Self {
+        Self {
+            component: Input::default()
+                .foreground(
+                    style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Yellow),
+                )
+                .background(
+                    style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .borders(
+                    Borders::default()
+                        .color(style_color_symbol.library_border().unwrap_or(Color::Green))
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .title(
+                    " Sleep: 30 | 30m pause | track | 3t  Alarm: 7:30 [playlist] ",
+                    Alignment::Left,
+                ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for SleepTimerPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::SleepTimerPopupCloseCancel);
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => match self.component.state() {
+                State::One(StateValue::String(input_string)) => {
+                    return Some(Msg::SleepTimerPopupCloseOk(input_string));
+                }
+                _ => return Some(Msg::None),
+            },
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
 #[derive(MockComponent)]
 pub struct FeedDeleteConfirmRadioPopup {
     component: Radio,
@@ -1177,10 +1301,10 @@ impl Component<Msg, NoUserEvent> for PodcastSearchTablePopup {
     fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
         let _cmd_result = match ev {
             Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
-                return Some(Msg::Podcast(PCMsg::SearchItunesCloseCancel))
+                return Some(Msg::Podcast(PCMsg::SearchCloseCancel))
             }
             Event::Keyboard(keyevent) if keyevent == self.keys.global_quit.key_event() => {
-                return Some(Msg::Podcast(PCMsg::SearchItunesCloseCancel))
+                return Some(Msg::Podcast(PCMsg::SearchCloseCancel))
             }
             Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
                 self.perform(Cmd::Move(Direction::Up))
@@ -1221,7 +1345,7 @@ impl Component<Msg, NoUserEvent> for PodcastSearchTablePopup {
                 code: Key::Enter, ..
             }) => {
                 if let State::One(StateValue::Usize(index)) = self.state() {
-                    return Some(Msg::Podcast(PCMsg::SearchItunesCloseOk(index)));
+                    return Some(Msg::Podcast(PCMsg::SearchCloseOk(index)));
                 }
                 CmdResult::None
             }
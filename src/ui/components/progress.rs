@@ -1,8 +1,9 @@
 use crate::config::Settings;
+use crate::player::PlayerTrait;
 use crate::track::{MediaType, Track};
 use crate::ui::{Id, Model, Msg};
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tui_realm_stdlib::ProgressBar;
 use tuirealm::event::NoUserEvent;
 use tuirealm::props::{Alignment, BorderType, Borders, Color, PropPayload, PropValue};
@@ -84,23 +85,30 @@ impl Model {
                         " Status: {} | Volume: {} | Speed: {:^.1} | Gapless: {} ",
                         self.player.playlist.status(),
                         self.config.volume,
-                        self.config.speed as f32 / 10.0,
+                        self.player.speed() as f32 / 10.0,
                         gapless,
                     );
                 }
-                Some(MediaType::Podcast) => {
+                Some(MediaType::Podcast | MediaType::Youtube) => {
                     progress_title = format!(
                         " Status: {} {:^.20} | Volume: {} | Speed: {:^.1} | Gapless: {} ",
                         self.player.playlist.status(),
                         track.title().unwrap_or("Unknown title"),
                         self.config.volume,
-                        self.config.speed as f32 / 10.0,
+                        self.player.speed() as f32 / 10.0,
                         gapless,
                     );
                 }
                 None => {}
             }
         }
+        if let Some(countdown) = self.player.countdown() {
+            progress_title = if progress_title.is_empty() {
+                format!(" {countdown} ")
+            } else {
+                format!("{progress_title}| {countdown} ")
+            };
+        }
 
         self.app
             .attr(
@@ -118,27 +126,15 @@ impl Model {
             return;
         }
 
+        if self.time_pos != time_pos {
+            self.time_pos_at = Instant::now();
+        }
         self.time_pos = time_pos;
 
         let progress = (time_pos * 100).checked_div(duration).unwrap() as f64;
 
         let new_prog = Self::progress_safeguard(progress);
 
-        // About to finish signal is a simulation of gstreamer, and used for gapless
-        #[cfg(any(not(feature = "gst"), feature = "mpv"))]
-        if !self.player.playlist.is_empty()
-            && !self.player.playlist.has_next_track()
-            && new_prog >= 0.5
-            && duration - time_pos < 2
-            && self.config.gapless
-        {
-            // eprintln!("about to finish sent");
-            self.player
-                .message_tx
-                .send(crate::player::PlayerMsg::AboutToFinish)
-                .ok();
-        }
-
         self.progress_set(new_prog, duration);
     }
 
@@ -0,0 +1,374 @@
+use crate::config::{Keys, Settings};
+use crate::podcast::show_notes::{ShowNotes, Target};
+use crate::track::MediaType;
+use crate::ui::{Id, Model, Msg, PCMsg};
+use anyhow::{anyhow, Result};
+use tui_realm_stdlib::utils::get_block;
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
+use tuirealm::props::{
+    Alignment, AttrValue, Attribute, BorderType, Borders, Color, Props, Style, TextModifiers,
+};
+use tuirealm::tui::layout::Rect;
+use tuirealm::tui::text::{Span, Spans};
+use tuirealm::tui::widgets::Paragraph;
+use tuirealm::{Component, Event, Frame, MockComponent, State};
+
+/// Scrollable view of the show notes of one episode. Links and timestamps can be selected with
+/// tab and activated with enter.
+pub struct ShowNotesPopup {
+    props: Props,
+    notes: ShowNotes,
+    episode_url: String,
+    keys: Keys,
+    scroll: usize,
+    selected: Option<usize>,
+    /// Set when the selection changed, so the next draw scrolls it into view.
+    follow_selected: bool,
+    page: usize,
+    rows: usize,
+}
+
+impl ShowNotesPopup {
+    pub fn new(config: &Settings, title: &str, episode_url: &str, notes: ShowNotes) -> Self {
+        let mut props = Props::default();
+        props.set(
+            Attribute::Borders,
+            AttrValue::Borders(
+                Borders::default().modifiers(BorderType::Rounded).color(
+                    config
+                        .style_color_symbol
+                        .library_border()
+                        .unwrap_or(Color::Blue),
+                ),
+            ),
+        );
+        props.set(
+            Attribute::Foreground,
+            AttrValue::Color(
+                config
+                    .style_color_symbol
+                    .library_foreground()
+                    .unwrap_or(Color::Yellow),
+            ),
+        );
+        props.set(
+            Attribute::Background,
+            AttrValue::Color(
+                config
+                    .style_color_symbol
+                    .library_background()
+                    .unwrap_or(Color::Reset),
+            ),
+        );
+        props.set(
+            Attribute::HighlightedColor,
+            AttrValue::Color(
+                config
+                    .style_color_symbol
+                    .library_highlight()
+                    .unwrap_or(Color::LightBlue),
+            ),
+        );
+        props.set(
+            Attribute::Title,
+            AttrValue::Title((
+                format!(" {title} | <TAB> select link or time, <ENTER> open or jump "),
+                Alignment::Left,
+            )),
+        );
+        Self {
+            props,
+            notes,
+            episode_url: episode_url.to_string(),
+            keys: config.keys.clone(),
+            scroll: 0,
+            selected: None,
+            follow_selected: false,
+            page: 1,
+            rows: 0,
+        }
+    }
+
+    fn select(&mut self, forward: bool) {
+        let len = self.notes.targets.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = Some(match self.selected {
+            None if forward => 0,
+            None => len - 1,
+            Some(i) if forward => (i + 1) % len,
+            Some(i) => (i + len - 1) % len,
+        });
+        self.follow_selected = true;
+    }
+
+    fn activate(&self) -> Option<Msg> {
+        match self.notes.targets.get(self.selected?)? {
+            Target::Link(url) => Some(Msg::Podcast(PCMsg::ShowNotesOpenLink(url.clone()))),
+            Target::Timestamp(secs) => Some(Msg::Podcast(PCMsg::ShowNotesSeek(
+                self.episode_url.clone(),
+                *secs,
+            ))),
+        }
+    }
+}
+
+impl MockComponent for ShowNotesPopup {
+    fn view(&mut self, render: &mut Frame<'_>, area: Rect) {
+        let width = usize::from(area.width.saturating_sub(2));
+        let height = usize::from(area.height.saturating_sub(2)).max(1);
+        let rows = self.notes.wrap(width);
+        self.page = height;
+        self.rows = rows.len();
+
+        if self.follow_selected {
+            self.follow_selected = false;
+            if let Some(row) = rows.iter().position(|r| {
+                r.iter()
+                    .any(|s| s.target.is_some() && s.target == self.selected)
+            }) {
+                if row < self.scroll {
+                    self.scroll = row;
+                } else if row >= self.scroll + height {
+                    self.scroll = row + 1 - height;
+                }
+            }
+        }
+        self.scroll = self.scroll.min(rows.len().saturating_sub(height));
+
+        let foreground = self
+            .props
+            .get_or(Attribute::Foreground, AttrValue::Color(Color::Reset))
+            .unwrap_color();
+        let background = self
+            .props
+            .get_or(Attribute::Background, AttrValue::Color(Color::Reset))
+            .unwrap_color();
+        let highlight = self
+            .props
+            .get_or(Attribute::HighlightedColor, AttrValue::Color(Color::Reset))
+            .unwrap_color();
+        let title = self
+            .props
+            .get_or(
+                Attribute::Title,
+                AttrValue::Title((String::new(), Alignment::Left)),
+            )
+            .unwrap_title();
+        let borders = self
+            .props
+            .get_or(Attribute::Borders, AttrValue::Borders(Borders::default()))
+            .unwrap_borders();
+        let focus = self
+            .props
+            .get_or(Attribute::Focus, AttrValue::Flag(false))
+            .unwrap_flag();
+
+        let lines: Vec<Spans<'_>> = rows
+            .into_iter()
+            .skip(self.scroll)
+            .take(height)
+            .map(|row| {
+                let spans: Vec<Span<'_>> = row
+                    .into_iter()
+                    .map(|span| {
+                        let mut modifiers = TextModifiers::empty();
+                        if span.bold {
+                            modifiers |= TextModifiers::BOLD;
+                        }
+                        if span.italic {
+                            modifiers |= TextModifiers::ITALIC;
+                        }
+                        let mut style = Style::default();
+                        if let Some(target) = span.target {
+                            style = style.fg(highlight);
+                            modifiers |= TextModifiers::UNDERLINED;
+                            if Some(target) == self.selected {
+                                modifiers |= TextModifiers::REVERSED;
+                            }
+                        }
+                        Span::styled(span.text, style.add_modifier(modifiers))
+                    })
+                    .collect();
+                Spans::from(spans)
+            })
+            .collect();
+
+        render.render_widget(
+            Paragraph::new(lines)
+                .block(get_block(borders, Some(title), focus, None))
+                .style(Style::default().fg(foreground).bg(background)),
+            area,
+        );
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.props.get(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        self.props.set(attr, value);
+    }
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, cmd: Cmd) -> CmdResult {
+        let last = self.rows.saturating_sub(self.page);
+        match cmd {
+            Cmd::Move(Direction::Down) => self.scroll = (self.scroll + 1).min(last),
+            Cmd::Move(Direction::Up) => self.scroll = self.scroll.saturating_sub(1),
+            Cmd::Scroll(Direction::Down) => self.scroll = (self.scroll + self.page).min(last),
+            Cmd::Scroll(Direction::Up) => self.scroll = self.scroll.saturating_sub(self.page),
+            Cmd::GoTo(Position::Begin) => self.scroll = 0,
+            Cmd::GoTo(Position::End) => self.scroll = last,
+            _ => return CmdResult::None,
+        }
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ShowNotesPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            Event::Keyboard(key) if key == self.keys.global_esc.key_event() => {
+                return Some(Msg::Podcast(PCMsg::ShowNotesPopupClose))
+            }
+            Event::Keyboard(key) if key == self.keys.global_quit.key_event() => {
+                return Some(Msg::Podcast(PCMsg::ShowNotesPopupClose))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter,
+                modifiers: KeyModifiers::NONE,
+            }) => return self.activate().or(Some(Msg::None)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Tab,
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                self.select(true);
+                CmdResult::None
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::BackTab,
+                modifiers: KeyModifiers::SHIFT,
+            }) => {
+                self.select(false);
+                CmdResult::None
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            Event::Keyboard(key) if key == self.keys.global_down.key_event() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == self.keys.global_up.key_event() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent {
+                code: Key::End,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::GoTo(Position::End)),
+            Event::Keyboard(key) if key == self.keys.global_goto_top.key_event() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(key) if key == self.keys.global_goto_bottom.key_event() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+impl Model {
+    pub fn mount_show_notes_popup(&mut self, episode_index: usize) -> Result<()> {
+        let episode = self
+            .podcasts
+            .get(self.podcasts_index)
+            .and_then(|pod| pod.episodes.get(episode_index))
+            .ok_or_else(|| anyhow!("get episode selected failed."))?;
+        let popup = ShowNotesPopup::new(
+            &self.config,
+            &episode.title,
+            &episode.url,
+            ShowNotes::parse(&episode.description),
+        );
+        assert!(self
+            .app
+            .remount(Id::ShowNotesPopup, Box::new(popup), vec![])
+            .is_ok());
+        assert!(self.app.active(&Id::ShowNotesPopup).is_ok());
+        self.update_photo()
+    }
+
+    pub fn umount_show_notes_popup(&mut self) {
+        if self.app.mounted(&Id::ShowNotesPopup) {
+            assert!(self.app.umount(&Id::ShowNotesPopup).is_ok());
+        }
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(format!("update photo error: {e}"));
+        }
+    }
+
+    /// Jumps to `secs` if the episode with the given url is the one being played.
+    pub fn show_notes_seek(&mut self, episode_url: &str, secs: u64) {
+        let episode_path = self
+            .podcasts
+            .iter()
+            .flat_map(|pod| pod.episodes.iter())
+            .find(|ep| ep.url == episode_url)
+            .and_then(|ep| ep.path.clone());
+        let playing = self.player.playlist.current_track().is_some_and(|track| {
+            matches!(track.media_type, Some(MediaType::Podcast))
+                && track.file().is_some_and(|file| {
+                    file == episode_url
+                        || episode_path
+                            .as_ref()
+                            .is_some_and(|path| path.to_string_lossy() == file)
+                })
+        });
+        if !playing {
+            self.mount_error_popup("Play this episode first to jump to a timestamp.");
+            return;
+        }
+        let target = i64::try_from(secs).unwrap_or_default();
+        self.player_seek(target - self.time_pos);
+        self.show_message_timeout_label_help(
+            format!(
+                "Jumped to {:02}:{:02}:{:02}",
+                secs / 3600,
+                secs / 60 % 60,
+                secs % 60
+            ),
+            None,
+            None,
+            None,
+        );
+    }
+
+    pub fn show_notes_open_link(&mut self, url: &str) {
+        if let Err(e) = crate::utils::open_url(url) {
+            self.mount_error_popup(format!("Error opening link: {e}"));
+        }
+    }
+}
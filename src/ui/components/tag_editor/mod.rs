@@ -23,10 +23,12 @@
  */
 
 /// -- modules
+mod te_batch;
 mod te_counter_delete_lyric;
 mod te_input;
 mod te_select_lyric;
 mod te_table_lyric_options;
+mod te_table_musicbrainz;
 mod te_textarea_lyric;
 mod update;
 mod view;
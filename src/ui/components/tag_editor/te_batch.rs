@@ -0,0 +1,975 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::te_input::handle_input_ev;
+use crate::config::Settings;
+use crate::songtag::pattern::Pattern;
+use crate::track::Track;
+use crate::ui::components::LabelGeneric;
+use crate::ui::{Id, IdTagEditor, Model, Msg, TEMsg, TFMsg};
+use crate::utils::{draw_area_in_absolute, draw_area_top_right_absolute, filetype_supported};
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tui_realm_stdlib::{Input, Radio, Table};
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
+use tuirealm::props::{
+    Alignment, AttrValue, Attribute, BorderType, Borders, Color, InputType, Style, TableBuilder,
+    TextSpan,
+};
+use tuirealm::tui::layout::{Constraint, Layout};
+use tuirealm::tui::widgets::Clear;
+use tuirealm::{Component, Event, MockComponent, State, StateValue};
+
+/// Input of a field set on every track of the batch.
+#[derive(MockComponent)]
+pub struct TEBatchInput {
+    component: Input,
+    config: Settings,
+    on_key_down: Msg,
+    on_key_up: Msg,
+}
+
+impl TEBatchInput {
+    pub fn new(
+        config: &Settings,
+        title: &str,
+        placeholder: &str,
+        value: &str,
+        on_key_down: TFMsg,
+        on_key_up: TFMsg,
+    ) -> Self {
+        Self {
+            component: Input::default()
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Cyan),
+                )
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Black),
+                )
+                .borders(
+                    Borders::default()
+                        .color(
+                            config
+                                .style_color_symbol
+                                .library_border()
+                                .unwrap_or(Color::LightYellow),
+                        )
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .placeholder(placeholder, Style::default().fg(Color::Rgb(128, 128, 128)))
+                .title(title, Alignment::Left)
+                .value(value),
+            config: config.clone(),
+            on_key_down: Msg::TagEditor(TEMsg::TEFocus(on_key_down)),
+            on_key_up: Msg::TagEditor(TEMsg::TEFocus(on_key_up)),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TEBatchInput {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.config_save.key_event() => {
+                Some(Msg::TagEditor(TEMsg::TEBatchApply))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => Some(Msg::TagEditor(TEMsg::TEBatchPreview)),
+            ev => {
+                let config = self.config.clone();
+                let (on_key_down, on_key_up) = (self.on_key_down.clone(), self.on_key_up.clone());
+                handle_input_ev(self, ev, &config, on_key_down, on_key_up)
+            }
+        }
+    }
+}
+
+#[derive(MockComponent)]
+pub struct TEBatchTrackNumber {
+    component: Radio,
+    config: Settings,
+}
+
+impl TEBatchTrackNumber {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Radio::default()
+                .borders(
+                    Borders::default()
+                        .color(
+                            config
+                                .style_color_symbol
+                                .library_border()
+                                .unwrap_or(Color::LightYellow),
+                        )
+                        .modifiers(BorderType::Rounded),
+                )
+                .choices(&["Keep", "Number in order"])
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightYellow),
+                )
+                .rewind(true)
+                .title(" Track numbers ", Alignment::Left)
+                .value(0),
+            config: config.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TEBatchTrackNumber {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.config_save.key_event() => {
+                Some(Msg::TagEditor(TEMsg::TEBatchApply))
+            }
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.global_esc.key_event() => {
+                Some(Msg::TagEditor(TEMsg::TagEditorClose(None)))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down | Key::Tab,
+                ..
+            }) => Some(Msg::TagEditor(TEMsg::TEFocus(
+                TFMsg::BatchTrackNumberBlurDown,
+            ))),
+            Event::Keyboard(
+                KeyEvent { code: Key::Up, .. }
+                | KeyEvent {
+                    code: Key::BackTab,
+                    modifiers: KeyModifiers::SHIFT,
+                },
+            ) => Some(Msg::TagEditor(TEMsg::TEFocus(
+                TFMsg::BatchTrackNumberBlurUp,
+            ))),
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => {
+                self.perform(Cmd::Move(Direction::Left));
+                Some(Msg::TagEditor(TEMsg::TEBatchPreview))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => {
+                self.perform(Cmd::Move(Direction::Right));
+                Some(Msg::TagEditor(TEMsg::TEBatchPreview))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Every track of the batch with its changes and new file name, checked before writing.
+#[derive(MockComponent)]
+pub struct TEBatchPreview {
+    component: Table,
+    config: Settings,
+}
+
+impl TEBatchPreview {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Table::default()
+                .borders(
+                    Borders::default().modifiers(BorderType::Rounded).color(
+                        config
+                            .style_color_symbol
+                            .library_border()
+                            .unwrap_or(Color::Blue),
+                    ),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Yellow),
+                )
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .title(" Preview: nothing is written until saved ", Alignment::Left)
+                .scroll(true)
+                .highlighted_color(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightBlue),
+                )
+                .highlighted_str(&config.style_color_symbol.library_highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(&["File", "Changes", "New name"])
+                .column_spacing(2)
+                .widths(&[30, 40, 30])
+                .table(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from("Empty"))
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from(""))
+                        .build(),
+                ),
+            config: config.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TEBatchPreview {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.config_save.key_event() => {
+                return Some(Msg::TagEditor(TEMsg::TEBatchApply));
+            }
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.global_esc.key_event() => {
+                return Some(Msg::TagEditor(TEMsg::TagEditorClose(None)));
+            }
+            Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
+                return Some(Msg::TagEditor(TEMsg::TEFocus(TFMsg::BatchPreviewBlurDown)));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::BackTab,
+                modifiers: KeyModifiers::SHIFT,
+            }) => {
+                return Some(Msg::TagEditor(TEMsg::TEFocus(TFMsg::BatchPreviewBlurUp)));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.global_down.key_event() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(keyevent) if keyevent == self.config.keys.global_up.key_event() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(keyevent)
+                if keyevent == self.config.keys.global_goto_top.key_event() =>
+            {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.config.keys.global_goto_bottom.key_event() =>
+            {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+/// A track of the batch as it would be written.
+pub struct BatchEdit {
+    pub track: Track,
+    /// Field changes, or why the tags could not be read from the path.
+    pub changes: Result<Vec<String>, String>,
+    /// New path without extension, None keeps the file where it is.
+    pub target: Result<Option<PathBuf>, String>,
+}
+
+impl BatchEdit {
+    /// Whether applying the edit would change anything.
+    fn is_change(&self) -> bool {
+        self.changes.as_ref().is_ok_and(|c| !c.is_empty()) || matches!(self.target, Ok(Some(_)))
+    }
+}
+
+// Field changes as `album: Old -> New`.
+fn diff(changes: &mut Vec<String>, field: &str, old: Option<String>, new: Option<String>) {
+    if old != new {
+        changes.push(format!(
+            "{field}: {} -> {}",
+            old.unwrap_or_else(|| "-".to_string()),
+            new.unwrap_or_else(|| "-".to_string())
+        ));
+    }
+}
+
+fn changes(original: &Track, track: &Track) -> Vec<String> {
+    let mut changes = Vec::new();
+    let text = |s: Option<&str>| s.map(str::to_string);
+    let number = |n: Option<u32>| n.map(|n| n.to_string());
+    diff(
+        &mut changes,
+        "artist",
+        text(original.artist()),
+        text(track.artist()),
+    );
+    diff(
+        &mut changes,
+        "title",
+        text(original.title()),
+        text(track.title()),
+    );
+    diff(
+        &mut changes,
+        "album",
+        text(original.album()),
+        text(track.album()),
+    );
+    diff(
+        &mut changes,
+        "album artist",
+        text(original.album_artist()),
+        text(track.album_artist()),
+    );
+    diff(
+        &mut changes,
+        "genre",
+        text(original.genre()),
+        text(track.genre()),
+    );
+    diff(
+        &mut changes,
+        "year",
+        number(original.year()),
+        number(track.year()),
+    );
+    diff(
+        &mut changes,
+        "track",
+        number(original.track_number()),
+        number(track.track_number()),
+    );
+    changes
+}
+
+// Where `track` goes with `pattern`: next to the file, or below `root` for a pattern with
+// directories. None when it is there already.
+fn target(track: &Track, pattern: &Pattern, root: &Path) -> Result<Option<PathBuf>> {
+    let name = pattern.format(track)?;
+    let file = Path::new(track.file().unwrap_or_default());
+    let target = if pattern.has_directories() {
+        root.join(name)
+    } else {
+        file.with_file_name(name)
+    };
+    Ok(Some(target).filter(|target| *target != file.with_extension("")))
+}
+
+// Removes `dir` and then its parents as long as they are empty, up to but not including
+// `root`. Directories outside of `root` are left alone.
+fn remove_empty_dirs(dir: &Path, root: &Path) {
+    for dir in dir.ancestors() {
+        if dir == root || !dir.starts_with(root) || std::fs::remove_dir(dir).is_err() {
+            return;
+        }
+    }
+}
+
+// Full path of the file moved to `target`.
+fn with_ext(track: &Track, target: &Path) -> PathBuf {
+    let mut file = target.as_os_str().to_owned();
+    file.push(".");
+    file.push(track.ext().unwrap_or_default());
+    PathBuf::from(file)
+}
+
+/// Read tags from the paths by `patterns.parse`, apply the shared fields, number the tracks if
+/// asked and move the files by `patterns.rename`. Targets used twice, or taken by an existing
+/// file, are reported as conflicts so nothing is overwritten.
+pub fn batch_edits(
+    tracks: &[Track],
+    fields: &BatchFields,
+    patterns: &BatchPatterns,
+) -> Vec<BatchEdit> {
+    let mut edits: Vec<BatchEdit> = Vec::new();
+    for (index, original) in tracks.iter().enumerate() {
+        let mut track = original.clone();
+        let parsed = match &patterns.parse {
+            Some(pattern) => pattern
+                .parse(Path::new(original.file().unwrap_or_default()), &mut track)
+                .map_err(|e| e.to_string()),
+            None => Ok(()),
+        };
+        if let Some(album) = &fields.album {
+            track.set_album(album);
+        }
+        if let Some(album_artist) = &fields.album_artist {
+            track.set_album_artist(album_artist);
+        }
+        if let Some(genre) = &fields.genre {
+            track.set_genre(genre);
+        }
+        if let Some(year) = fields.year {
+            track.set_year(year);
+        }
+        if fields.number_tracks {
+            track.set_track_number(u32::try_from(index + 1).unwrap_or(u32::MAX));
+        }
+
+        let target = match &patterns.rename {
+            Some(pattern) => target(&track, pattern, &patterns.root).map_err(|e| e.to_string()),
+            None => Ok(None),
+        };
+        edits.push(BatchEdit {
+            changes: parsed.map(|()| changes(original, &track)),
+            track,
+            target,
+        });
+    }
+
+    // targets must not collide with each other, nor with existing files
+    let mut targets: HashMap<PathBuf, usize> = HashMap::new();
+    for edit in &edits {
+        if let Ok(Some(target)) = &edit.target {
+            *targets.entry(with_ext(&edit.track, target)).or_default() += 1;
+        }
+    }
+    for edit in &mut edits {
+        let Ok(Some(target)) = &edit.target else {
+            continue;
+        };
+        let file = with_ext(&edit.track, target);
+        if targets.get(&file).is_some_and(|n| *n > 1) {
+            edit.target = Err(format!("{} is used twice", file.display()));
+        } else if file.exists() {
+            edit.target = Err(format!("{} already exists", file.display()));
+        }
+    }
+    edits
+}
+
+/// Fields set on every track, None keeps the value of each track.
+pub struct BatchFields {
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub genre: Option<String>,
+    pub year: Option<u32>,
+    pub number_tracks: bool,
+}
+
+/// Patterns reading tags from the paths, and placing the files by their tags. A rename pattern
+/// with directories, like `{artist}/{album}/{title}`, is relative to `root`.
+pub struct BatchPatterns {
+    pub parse: Option<Pattern>,
+    pub rename: Option<Pattern>,
+    pub root: PathBuf,
+}
+
+impl Model {
+    /// Batch edit the supported files of a directory, like a whole album.
+    pub fn mount_tageditor_batch_dir(&mut self, dir: &Path) {
+        let mut files: Vec<String> = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path().to_string_lossy().to_string())
+                    .filter(|f| filetype_supported(f))
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort();
+        self.mount_tageditor_batch(&files);
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub fn mount_tageditor_batch(&mut self, files: &[String]) {
+        let mut tracks: Vec<Track> = files
+            .iter()
+            .filter_map(|f| Track::read_from_path(f, false).ok())
+            .collect();
+        if tracks.is_empty() {
+            self.mount_error_popup("no tracks with tags found!");
+            return;
+        }
+        tracks.sort_by(|a, b| {
+            (a.track_number().unwrap_or(u32::MAX), a.file())
+                .cmp(&(b.track_number().unwrap_or(u32::MAX), b.file()))
+        });
+
+        // fields all tracks agree on are filled in
+        let shared = |value: fn(&Track) -> Option<String>| {
+            let first = value(&tracks[0]);
+            if tracks.iter().all(|t| value(t) == first) {
+                first.unwrap_or_default()
+            } else {
+                String::new()
+            }
+        };
+        let album = shared(|t| t.album().map(str::to_string));
+        let album_artist = shared(|t| t.album_artist().map(str::to_string));
+        let genre = shared(|t| t.genre().map(str::to_string));
+        let year = shared(|t| t.year().map(|y| y.to_string()));
+        let count = tracks.len();
+        self.tageditor_batch = tracks;
+
+        self.remount_tag_editor_label_help();
+        let inputs = [
+            (
+                IdTagEditor::BatchAlbum,
+                " Album ",
+                album,
+                TFMsg::BatchAlbumBlurDown,
+                TFMsg::BatchAlbumBlurUp,
+            ),
+            (
+                IdTagEditor::BatchAlbumArtist,
+                " Album artist ",
+                album_artist,
+                TFMsg::BatchAlbumArtistBlurDown,
+                TFMsg::BatchAlbumArtistBlurUp,
+            ),
+            (
+                IdTagEditor::BatchGenre,
+                " Genre ",
+                genre,
+                TFMsg::BatchGenreBlurDown,
+                TFMsg::BatchGenreBlurUp,
+            ),
+            (
+                IdTagEditor::BatchYear,
+                " Year ",
+                year,
+                TFMsg::BatchYearBlurDown,
+                TFMsg::BatchYearBlurUp,
+            ),
+        ];
+        for (id, title, value, down, up) in inputs {
+            assert!(self
+                .app
+                .remount(
+                    Id::TagEditor(id),
+                    Box::new(TEBatchInput::new(
+                        &self.config,
+                        title,
+                        "keep",
+                        &value,
+                        down,
+                        up
+                    )),
+                    vec![]
+                )
+                .is_ok());
+        }
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::LabelHint),
+                Box::new(LabelGeneric::new(
+                    &self.config,
+                    &format!(
+                        "Editing {count} tracks, empty fields are kept. Press <ENTER> to preview:"
+                    )
+                )),
+                vec![]
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::BatchTrackNumber),
+                Box::new(TEBatchTrackNumber::new(&self.config)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::BatchParse),
+                Box::new(TEBatchInput::new(
+                    &self.config,
+                    " Tags from path ",
+                    "{artist}/{album}/{track} - {title}",
+                    "",
+                    TFMsg::BatchParseBlurDown,
+                    TFMsg::BatchParseBlurUp,
+                )),
+                vec![]
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::BatchRename),
+                Box::new(TEBatchInput::new(
+                    &self.config,
+                    " Rename files by pattern, / moves into the library ",
+                    "{track:02} - {title}, empty keeps the file names",
+                    "",
+                    TFMsg::BatchRenameBlurDown,
+                    TFMsg::BatchRenameBlurUp,
+                )),
+                vec![]
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::BatchPreview),
+                Box::new(TEBatchPreview::new(&self.config)),
+                vec![]
+            )
+            .is_ok());
+        self.app
+            .active(&Id::TagEditor(IdTagEditor::BatchAlbum))
+            .ok();
+        self.te_batch_preview();
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(format!("clear photo error: {e}"));
+        }
+    }
+
+    pub fn umount_tageditor_batch(&mut self) {
+        for id in [
+            IdTagEditor::BatchAlbum,
+            IdTagEditor::BatchAlbumArtist,
+            IdTagEditor::BatchGenre,
+            IdTagEditor::BatchYear,
+            IdTagEditor::BatchTrackNumber,
+            IdTagEditor::BatchParse,
+            IdTagEditor::BatchRename,
+            IdTagEditor::BatchPreview,
+        ] {
+            self.app.umount(&Id::TagEditor(id)).ok();
+        }
+        self.tageditor_batch.clear();
+    }
+
+    fn te_batch_input(&self, id: IdTagEditor) -> Option<String> {
+        match self.app.state(&Id::TagEditor(id)) {
+            Ok(State::One(StateValue::String(value))) if !value.trim().is_empty() => {
+                Some(value.trim().to_string())
+            }
+            _ => None,
+        }
+    }
+
+    fn te_batch_edits(&self) -> Result<Vec<BatchEdit>> {
+        let year = match self.te_batch_input(IdTagEditor::BatchYear) {
+            Some(year) => Some(
+                year.parse()
+                    .map_err(|_| anyhow!("year {year} is not a number"))?,
+            ),
+            None => None,
+        };
+        let fields = BatchFields {
+            album: self.te_batch_input(IdTagEditor::BatchAlbum),
+            album_artist: self.te_batch_input(IdTagEditor::BatchAlbumArtist),
+            genre: self.te_batch_input(IdTagEditor::BatchGenre),
+            year,
+            number_tracks: matches!(
+                self.app
+                    .state(&Id::TagEditor(IdTagEditor::BatchTrackNumber)),
+                Ok(State::One(StateValue::Usize(1)))
+            ),
+        };
+        let patterns = BatchPatterns {
+            parse: self
+                .te_batch_input(IdTagEditor::BatchParse)
+                .map(|p| Pattern::new(&p))
+                .transpose()?,
+            rename: self
+                .te_batch_input(IdTagEditor::BatchRename)
+                .map(|p| Pattern::new(&p))
+                .transpose()?,
+            root: self.path.clone(),
+        };
+        Ok(batch_edits(&self.tageditor_batch, &fields, &patterns))
+    }
+
+    pub fn te_batch_preview(&mut self) {
+        let mut table = TableBuilder::default();
+        match self.te_batch_edits() {
+            Ok(edits) => {
+                for (idx, edit) in edits.iter().enumerate() {
+                    if idx > 0 {
+                        table.add_row();
+                    }
+                    let file = edit.track.name().unwrap_or_default();
+                    table.add_col(TextSpan::new(file));
+                    match &edit.changes {
+                        Ok(changes) if changes.is_empty() => {
+                            table.add_col(TextSpan::new("no change").fg(Color::DarkGray))
+                        }
+                        Ok(changes) => {
+                            table.add_col(TextSpan::new(changes.join(", ")).fg(Color::Green))
+                        }
+                        Err(e) => table.add_col(TextSpan::new(e).fg(Color::Red)),
+                    };
+                    match &edit.target {
+                        Ok(Some(target)) => {
+                            // moves into the library are shown from its root
+                            let target = target.strip_prefix(&self.path).unwrap_or(target);
+                            let target = with_ext(&edit.track, target);
+                            table.add_col(TextSpan::new(target.to_string_lossy()).fg(Color::Green))
+                        }
+                        Ok(None) => table.add_col(TextSpan::new("keep").fg(Color::DarkGray)),
+                        Err(e) => table.add_col(TextSpan::new(e).fg(Color::Red)),
+                    };
+                }
+            }
+            Err(e) => {
+                table
+                    .add_col(TextSpan::new(e.to_string()).fg(Color::Red))
+                    .add_col(TextSpan::from(""))
+                    .add_col(TextSpan::from(""));
+            }
+        }
+        self.app
+            .attr(
+                &Id::TagEditor(IdTagEditor::BatchPreview),
+                Attribute::Content,
+                AttrValue::Table(table.build()),
+            )
+            .ok();
+    }
+
+    /// Write the previewed changes, nothing is written while any track has an error. Moved
+    /// files are updated in the playlist, and the database picks them up when the library is
+    /// synced.
+    pub fn te_batch_apply(&mut self) -> Result<()> {
+        let edits = self.te_batch_edits()?;
+        for edit in &edits {
+            if let Err(e) = &edit.changes {
+                bail!("{e}");
+            }
+            if let Err(e) = &edit.target {
+                bail!("{e}");
+            }
+        }
+        let mut written = 0;
+        let mut failed = vec![];
+        for mut edit in edits {
+            if !edit.is_change() {
+                continue;
+            }
+            let old_file = edit.track.file().unwrap_or_default().to_string();
+            let target = edit.target.unwrap_or_default();
+            if let Err(e) = edit.track.save_tag_as(target.as_deref()) {
+                failed.push(format!("{old_file}: {e}"));
+                continue;
+            }
+            if target.is_some() {
+                self.player.playlist.update_moved(&old_file, &edit.track);
+                // leave no empty directories behind when moving into the library layout
+                if let Some(dir) = Path::new(&old_file).parent() {
+                    remove_empty_dirs(dir, &self.path);
+                }
+            }
+            written += 1;
+        }
+
+        // files written before a failure stay written, so the views have to catch up either way
+        self.umount_tageditor();
+        self.library_reload_with_node_focus(None);
+        self.playlist_update_library_delete();
+        if !failed.is_empty() {
+            bail!(
+                "{written} tracks written, {} failed:\n{}",
+                failed.len(),
+                failed.join("\n")
+            );
+        }
+        self.show_message_timeout_label_help(format!("{written} tracks written"), None, None, None);
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub fn view_tag_editor_batch(&mut self) {
+        assert!(self
+            .terminal
+            .raw_mut()
+            .draw(|f| {
+                f.render_widget(Clear, f.size());
+                let chunks_main = Layout::default()
+                    .direction(tuirealm::tui::layout::Direction::Vertical)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Length(1),
+                            Constraint::Length(3),
+                            Constraint::Length(3),
+                            Constraint::Min(2),
+                            Constraint::Length(1),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(f.size());
+
+                let chunks_row1 = Layout::default()
+                    .direction(tuirealm::tui::layout::Direction::Horizontal)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Ratio(1, 4),
+                            Constraint::Ratio(1, 4),
+                            Constraint::Ratio(1, 4),
+                            Constraint::Ratio(1, 4),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(chunks_main[1]);
+                let chunks_row2 = Layout::default()
+                    .direction(tuirealm::tui::layout::Direction::Horizontal)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Ratio(2, 8),
+                            Constraint::Ratio(3, 8),
+                            Constraint::Ratio(3, 8),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(chunks_main[2]);
+
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::LabelHint), f, chunks_main[0]);
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::BatchAlbum), f, chunks_row1[0]);
+                self.app.view(
+                    &Id::TagEditor(IdTagEditor::BatchAlbumArtist),
+                    f,
+                    chunks_row1[1],
+                );
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::BatchGenre), f, chunks_row1[2]);
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::BatchYear), f, chunks_row1[3]);
+                self.app.view(
+                    &Id::TagEditor(IdTagEditor::BatchTrackNumber),
+                    f,
+                    chunks_row2[0],
+                );
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::BatchParse), f, chunks_row2[1]);
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::BatchRename), f, chunks_row2[2]);
+                self.app
+                    .view(&Id::TagEditor(IdTagEditor::BatchPreview), f, chunks_main[3]);
+                self.app.view(&Id::Label, f, chunks_main[4]);
+
+                if self.app.mounted(&Id::MessagePopup) {
+                    let popup = draw_area_top_right_absolute(f.size(), 25, 4);
+                    f.render_widget(Clear, popup);
+                    self.app.view(&Id::MessagePopup, f, popup);
+                }
+                if self.app.mounted(&Id::ErrorPopup) {
+                    let popup = draw_area_in_absolute(f.size(), 50, 4);
+                    f.render_widget(Clear, popup);
+                    self.app.view(&Id::ErrorPopup, f, popup);
+                }
+            })
+            .is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_empty_dirs() {
+        let root = std::env::temp_dir().join(format!("termusic-prune-{}", std::process::id()));
+        let nested = root.join("artist").join("album").join("cd1");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join("artist").join("cover.jpg"), b"").unwrap();
+        let outside = std::env::temp_dir().join(format!("termusic-outside-{}", std::process::id()));
+        std::fs::create_dir_all(&outside).unwrap();
+
+        remove_empty_dirs(&nested, &root);
+        remove_empty_dirs(&outside, &root);
+        let album_removed = !root.join("artist").join("album").exists();
+        let artist_kept = root.join("artist").exists();
+        let outside_kept = outside.exists();
+        remove_empty_dirs(&root, &root);
+        let root_kept = root.exists();
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::remove_dir_all(&outside).ok();
+
+        assert!(album_removed);
+        assert!(artist_kept);
+        assert!(outside_kept);
+        assert!(root_kept);
+    }
+
+    #[test]
+    fn test_batch_edits_conflicts() {
+        let dir = std::env::temp_dir().join(format!("termusic-batch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["a.mp3", "b.mp3", "c.mp3", "d.mp3", "Taken.mp3"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let tracks: Vec<Track> = [("a", "Same"), ("b", "Same"), ("c", "Taken"), ("d", "Free")]
+            .into_iter()
+            .map(|(name, title)| {
+                let mut track =
+                    Track::read_from_path(dir.join(format!("{name}.mp3")), false).unwrap();
+                track.set_title(title);
+                track
+            })
+            .collect();
+        let fields = BatchFields {
+            album: None,
+            album_artist: None,
+            genre: None,
+            year: None,
+            number_tracks: false,
+        };
+        let patterns = BatchPatterns {
+            parse: None,
+            rename: Some(Pattern::new("{title}").unwrap()),
+            root: dir.clone(),
+        };
+
+        let edits = batch_edits(&tracks, &fields, &patterns);
+        std::fs::remove_dir_all(&dir).ok();
+        let targets: Vec<Result<Option<PathBuf>, String>> =
+            edits.into_iter().map(|edit| edit.target).collect();
+        let same = dir.join("Same.mp3").display().to_string();
+        assert_eq!(
+            targets,
+            [
+                Err(format!("{same} is used twice")),
+                Err(format!("{same} is used twice")),
+                Err(format!(
+                    "{} already exists",
+                    dir.join("Taken.mp3").display()
+                )),
+                Ok(Some(dir.join("Free"))),
+            ]
+        );
+    }
+}
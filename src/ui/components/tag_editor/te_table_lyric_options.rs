@@ -22,11 +22,13 @@ use crate::config::Settings;
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+use crate::songtag::lrclib::Query;
 use crate::songtag::{search, SongTag};
 use crate::ui::{Id, IdTagEditor, Model, Msg, SearchLyricState, TEMsg, TFMsg};
 
 use anyhow::{anyhow, Context, Result};
 use std::path::Path;
+use std::time::Duration;
 use tui_realm_stdlib::Table;
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
 use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
@@ -151,6 +153,9 @@ impl Component<Msg, NoUserEvent> for TETableLyricOptions {
             Event::Keyboard(k) if k == self.config.keys.global_goto_bottom.key_event() => {
                 self.perform(Cmd::GoTo(Position::End))
             }
+            Event::Keyboard(k) if k == self.config.keys.tag_editor_lookup.key_event() => {
+                return Some(Msg::TagEditor(TEMsg::TELookup))
+            }
             Event::Keyboard(k) if k == self.config.keys.library_search_youtube.key_event() => {
                 if let State::One(StateValue::Usize(index)) = self.state() {
                     return Some(Msg::TagEditor(TEMsg::TEDownload(index)));
@@ -222,16 +227,26 @@ impl Model {
 
     pub fn te_songtag_search(&mut self) {
         let mut search_str = String::new();
+        let mut query = Query {
+            artist: String::new(),
+            title: String::new(),
+            duration: Duration::ZERO,
+        };
         if let Ok(State::One(StateValue::String(artist))) =
             self.app.state(&Id::TagEditor(IdTagEditor::InputArtist))
         {
             search_str.push_str(&artist);
+            query.artist = artist;
         }
         search_str.push(' ');
         if let Ok(State::One(StateValue::String(title))) =
             self.app.state(&Id::TagEditor(IdTagEditor::InputTitle))
         {
             search_str.push_str(&title);
+            query.title = title;
+        }
+        if let Some(song) = &self.tageditor_song {
+            query.duration = song.duration();
         }
 
         if search_str.len() < 4 {
@@ -240,11 +255,16 @@ impl Model {
                     let p: &Path = Path::new(file);
                     if let Some(stem) = p.file_stem() {
                         search_str = stem.to_string_lossy().to_string();
+                        // free text search, as there is no title to match
+                        query.artist.clone_from(&search_str);
+                        query.title = String::new();
                     }
                 }
             }
         }
-        search(&search_str, self.sender_songtag.clone());
+        let lrclib =
+            Some((self.config.lyric_lrclib_url.clone(), query)).filter(|(url, _)| !url.is_empty());
+        search(&search_str, lrclib, self.sender_songtag.clone());
     }
     pub fn te_update_lyric_options(&mut self) {
         if self
@@ -265,7 +285,8 @@ impl Model {
             .with_context(|| format!("no song_tag with index {index} found"))?;
         if let Some(song) = &self.tageditor_song {
             let file = song.file().context("no file path found")?;
-            song_tag.download(file, &self.tx_to_main)?;
+            let profile = self.config.selected_download_profile();
+            song_tag.download(file, &profile, &self.tx_to_main)?;
         }
         Ok(())
     }
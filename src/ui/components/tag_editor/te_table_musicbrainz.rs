@@ -0,0 +1,303 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use crate::config::Settings;
+use crate::songtag::musicbrainz::{Api, Query, Release};
+use crate::ui::{Id, IdTagEditor, Model, Msg, TEMsg};
+use anyhow::{anyhow, Context, Result};
+use std::path::PathBuf;
+use std::thread;
+use tui_realm_stdlib::Table;
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
+use tuirealm::props::{
+    Alignment, AttrValue, Attribute, BorderType, Borders, Color, TableBuilder, TextSpan,
+};
+use tuirealm::{Component, Event, MockComponent, State, StateValue};
+
+/// Releases found on musicbrainz, shown in place of the lyric search results.
+#[derive(MockComponent)]
+pub struct TETableMusicBrainz {
+    component: Table,
+    config: Settings,
+}
+
+impl TETableMusicBrainz {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Table::default()
+                .borders(
+                    Borders::default().modifiers(BorderType::Rounded).color(
+                        config
+                            .style_color_symbol
+                            .library_border()
+                            .unwrap_or(Color::Blue),
+                    ),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Yellow),
+                )
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .title(" MusicBrainz Releases ", Alignment::Left)
+                .scroll(true)
+                .highlighted_color(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightBlue),
+                )
+                .highlighted_str("\u{1f680}")
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(&["Artist", "Title", "Album", "Year", "Track", "Score"])
+                .column_spacing(1)
+                .widths(&[20, 25, 25, 10, 10, 10])
+                .table(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from("Looking up..."))
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from(""))
+                        .build(),
+                ),
+            config: config.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for TETableMusicBrainz {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            Event::Keyboard(
+                KeyEvent { code: Key::Tab, .. }
+                | KeyEvent {
+                    code: Key::BackTab,
+                    modifiers: KeyModifiers::SHIFT,
+                },
+            ) => return Some(Msg::TagEditor(TEMsg::TELookupClose)),
+            Event::Keyboard(k) if k == self.config.keys.global_esc.key_event() => {
+                return Some(Msg::TagEditor(TEMsg::TELookupClose))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(k) if k == self.config.keys.global_down.key_event() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(k) if k == self.config.keys.global_up.key_event() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(k) if k == self.config.keys.global_goto_top.key_event() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(k) if k == self.config.keys.global_goto_bottom.key_event() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::TagEditor(TEMsg::TELookupApply(index)));
+                }
+                CmdResult::None
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+impl Model {
+    fn musicbrainz_api(&self) -> Api {
+        Api::new(
+            &self.config.musicbrainz_url,
+            &self.config.musicbrainz_cover_art_url,
+            &self.config.acoustid_url,
+            &self.config.acoustid_api_key,
+        )
+    }
+
+    /// Look up the track on musicbrainz, by fingerprint when acoustid is set up and otherwise
+    /// by the tags typed in the editor and the duration.
+    pub fn te_lookup(&mut self) {
+        let Some(song) = &self.tageditor_song else {
+            return;
+        };
+        let input = |id: IdTagEditor| match self.app.state(&Id::TagEditor(id)) {
+            Ok(State::One(StateValue::String(value))) => value,
+            _ => String::new(),
+        };
+        let query = Query {
+            artist: input(IdTagEditor::InputArtist),
+            title: input(IdTagEditor::InputTitle),
+            album: input(IdTagEditor::InputAlbum),
+            duration: song.duration(),
+        };
+        let file = song.file().map(PathBuf::from);
+        let api = self.musicbrainz_api();
+
+        self.musicbrainz_options.clear();
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::TableMusicBrainz),
+                Box::new(TETableMusicBrainz::new(&self.config)),
+                vec![]
+            )
+            .is_ok());
+        self.app
+            .active(&Id::TagEditor(IdTagEditor::TableMusicBrainz))
+            .ok();
+
+        let tx = self.tx_to_main.clone();
+        thread::spawn(move || {
+            let by_fingerprint = file
+                .and_then(|file| api.search_fingerprint(&file).ok())
+                .filter(|releases| !releases.is_empty());
+            let releases = match by_fingerprint {
+                Some(releases) => Ok(releases),
+                None => api.search(&query).map_err(|e| e.to_string()),
+            };
+            tx.send(Msg::TagEditor(TEMsg::TELookupResult(releases)))
+                .ok();
+        });
+    }
+
+    pub fn te_lookup_sync(&mut self, releases: Result<Vec<Release>, String>) {
+        if !self
+            .app
+            .mounted(&Id::TagEditor(IdTagEditor::TableMusicBrainz))
+        {
+            return;
+        }
+        let mut table = TableBuilder::default();
+        match releases {
+            Ok(releases) if releases.is_empty() => {
+                table.add_col(TextSpan::from("No releases found"));
+            }
+            Ok(releases) => {
+                for (idx, release) in releases.iter().enumerate() {
+                    if idx > 0 {
+                        table.add_row();
+                    }
+                    let number = |n: Option<u32>| n.map_or_else(String::new, |n| n.to_string());
+                    table
+                        .add_col(
+                            TextSpan::new(release.artist.as_deref().unwrap_or("Nobody"))
+                                .fg(tuirealm::tui::style::Color::LightYellow),
+                        )
+                        .add_col(
+                            TextSpan::new(release.title.as_deref().unwrap_or("Unknown Title"))
+                                .bold(),
+                        )
+                        .add_col(TextSpan::new(
+                            release.album.as_deref().unwrap_or("Unknown Album"),
+                        ))
+                        .add_col(TextSpan::new(number(release.year)))
+                        .add_col(TextSpan::new(number(release.track_number)))
+                        .add_col(TextSpan::new(release.score.to_string()));
+                }
+                self.musicbrainz_options = releases;
+            }
+            Err(e) => {
+                table.add_col(TextSpan::new(format!("Lookup failed: {e}")).fg(Color::Red));
+            }
+        }
+        self.app
+            .attr(
+                &Id::TagEditor(IdTagEditor::TableMusicBrainz),
+                Attribute::Content,
+                AttrValue::Table(table.build()),
+            )
+            .ok();
+    }
+
+    pub fn te_lookup_close(&mut self) {
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::TableMusicBrainz))
+            .ok();
+        self.musicbrainz_options.clear();
+        self.app
+            .active(&Id::TagEditor(IdTagEditor::TableLyricOptions))
+            .ok();
+    }
+
+    /// Write the tags and ids of the release, and its cover from the Cover Art Archive.
+    pub fn te_lookup_apply(&mut self, index: usize) -> Result<()> {
+        let release = self
+            .musicbrainz_options
+            .get(index)
+            .cloned()
+            .with_context(|| format!("no release with index {index} found"))?;
+        let mut song = self
+            .tageditor_song
+            .clone()
+            .ok_or_else(|| anyhow!("no track in tag editor"))?;
+        if let Some(artist) = &release.artist {
+            song.set_artist(artist);
+        }
+        if let Some(title) = &release.title {
+            song.set_title(title);
+        }
+        if let Some(album) = &release.album {
+            song.set_album(album);
+        }
+        if let Some(album_artist) = &release.album_artist {
+            song.set_album_artist(album_artist);
+        }
+        if let Some(year) = release.year {
+            song.set_year(year);
+        }
+        if let Some(track_number) = release.track_number {
+            song.set_track_number(track_number);
+        }
+        song.set_musicbrainz(release.ids.clone());
+        if let Ok(picture) = self.musicbrainz_api().cover_art(&release.ids) {
+            song.set_photo(picture);
+        }
+
+        song.save_tag_as(None)?;
+        self.te_lookup_close();
+        self.init_by_song(&song);
+        self.playlist_update_library_delete();
+        Ok(())
+    }
+}
@@ -36,6 +36,12 @@ impl Model {
                 self.umount_tageditor();
             }
 
+            TEMsg::TEBatchPreview => self.te_batch_preview(),
+            TEMsg::TEBatchApply => {
+                if let Err(e) = self.te_batch_apply() {
+                    self.mount_error_popup(format!("batch tag edit error: {e}"));
+                }
+            }
             TEMsg::TECounterDeleteOk => {
                 self.te_delete_lyric();
             }
@@ -63,6 +69,14 @@ impl Model {
                     self.mount_error_popup(format!("rename song by tag error: {e}"));
                 }
             }
+            TEMsg::TELookup => self.te_lookup(),
+            TEMsg::TELookupResult(releases) => self.te_lookup_sync(releases.clone()),
+            TEMsg::TELookupClose => self.te_lookup_close(),
+            TEMsg::TELookupApply(index) => {
+                if let Err(e) = self.te_lookup_apply(*index) {
+                    self.mount_error_popup(format!("MusicBrainz tag error: {e}"));
+                }
+            }
             TEMsg::TEFocus(m) => self.update_tag_editor_focus(m),
         }
     }
@@ -109,6 +123,23 @@ impl Model {
                     .active(&Id::TagEditor(IdTagEditor::TextareaLyric))
                     .ok();
             }
+            m => self.update_tag_editor_batch_focus(m),
         }
     }
+
+    fn update_tag_editor_batch_focus(&mut self, msg: &TFMsg) {
+        let id = match msg {
+            TFMsg::BatchPreviewBlurDown | TFMsg::BatchAlbumArtistBlurUp => IdTagEditor::BatchAlbum,
+            TFMsg::BatchAlbumBlurDown | TFMsg::BatchGenreBlurUp => IdTagEditor::BatchAlbumArtist,
+            TFMsg::BatchAlbumArtistBlurDown | TFMsg::BatchYearBlurUp => IdTagEditor::BatchGenre,
+            TFMsg::BatchGenreBlurDown | TFMsg::BatchTrackNumberBlurUp => IdTagEditor::BatchYear,
+            TFMsg::BatchYearBlurDown | TFMsg::BatchParseBlurUp => IdTagEditor::BatchTrackNumber,
+            TFMsg::BatchTrackNumberBlurDown | TFMsg::BatchRenameBlurUp => IdTagEditor::BatchParse,
+            TFMsg::BatchParseBlurDown | TFMsg::BatchPreviewBlurUp => IdTagEditor::BatchRename,
+            TFMsg::BatchRenameBlurDown | TFMsg::BatchAlbumBlurUp => IdTagEditor::BatchPreview,
+            _ => return,
+        };
+        self.app.active(&Id::TagEditor(id)).ok();
+        self.te_batch_preview();
+    }
 }
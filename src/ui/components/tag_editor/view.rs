@@ -138,11 +138,22 @@ impl Model {
                         .view(&Id::TagEditor(IdTagEditor::InputAlbum), f, chunks_row2[0]);
                     self.app
                         .view(&Id::TagEditor(IdTagEditor::InputGenre), f, chunks_row2[1]);
-                    self.app.view(
-                        &Id::TagEditor(IdTagEditor::TableLyricOptions),
-                        f,
-                        chunks_row4[0],
-                    );
+                    if self
+                        .app
+                        .mounted(&Id::TagEditor(IdTagEditor::TableMusicBrainz))
+                    {
+                        self.app.view(
+                            &Id::TagEditor(IdTagEditor::TableMusicBrainz),
+                            f,
+                            chunks_row4[0],
+                        );
+                    } else {
+                        self.app.view(
+                            &Id::TagEditor(IdTagEditor::TableLyricOptions),
+                            f,
+                            chunks_row4[0],
+                        );
+                    }
                     self.app.view(
                         &Id::TagEditor(IdTagEditor::SelectLyric),
                         f,
@@ -177,7 +188,7 @@ impl Model {
     pub fn mount_tageditor(&mut self, node_id: &str) {
         let p: &Path = Path::new(node_id);
         if p.is_dir() {
-            self.mount_error_popup("directory doesn't have tag!");
+            self.mount_tageditor_batch_dir(p);
             return;
         }
 
@@ -299,6 +310,10 @@ impl Model {
         self.app
             .umount(&Id::TagEditor(IdTagEditor::TextareaLyric))
             .ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::TableMusicBrainz))
+            .ok();
+        self.umount_tageditor_batch();
         if let Err(e) = self.update_photo() {
             self.mount_error_popup(format!("update photo error: {e}"));
         }
@@ -525,6 +540,18 @@ impl Model {
                             .style_color_symbol
                             .library_foreground()
                             .unwrap_or(Color::White)),
+                        TextSpan::new(format!("<{}>", self.config.keys.tag_editor_lookup))
+                            .bold()
+                            .fg(self
+                                .config
+                                .style_color_symbol
+                                .library_highlight()
+                                .unwrap_or(Color::Cyan)),
+                        TextSpan::new(" MusicBrainz ").fg(self
+                            .config
+                            .style_color_symbol
+                            .library_foreground()
+                            .unwrap_or(Color::White)),
                     ]
                 )),
                 Vec::default(),
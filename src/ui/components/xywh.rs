@@ -229,7 +229,9 @@ impl Model {
             return true;
         }
 
-        if self.app.mounted(&Id::TagEditor(IdTagEditor::InputTitle)) {
+        if self.app.mounted(&Id::TagEditor(IdTagEditor::InputTitle))
+            || self.app.mounted(&Id::TagEditor(IdTagEditor::BatchPreview))
+        {
             return true;
         }
 
@@ -283,7 +285,7 @@ impl Model {
                     self.show_image(&img)?;
                 }
             }
-            Some(MediaType::Podcast) => {
+            Some(MediaType::Podcast | MediaType::Youtube) => {
                 let mut url = String::new();
                 if let Some(episode_photo_url) = track.album_photo() {
                     url = episode_photo_url.to_string();
@@ -160,11 +160,12 @@ impl YSTablePopup {
                 .rewind(false)
                 .step(4)
                 .row_height(1)
-                .headers(&["Duration", "Name"])
-                .column_spacing(3)
-                .widths(&[20, 80])
+                .headers(&["", "Duration", "Name"])
+                .column_spacing(2)
+                .widths(&[3, 17, 80])
                 .table(
                     TableBuilder::default()
+                        .add_col(TextSpan::from(" "))
                         .add_col(TextSpan::from("Empty result."))
                         .add_col(TextSpan::from("Loading..."))
                         .build(),
@@ -226,6 +227,24 @@ impl Component<Msg, NoUserEvent> for YSTablePopup {
                 }
                 CmdResult::None
             }
+            Event::Keyboard(keyevent) if keyevent == self.keys.youtube_search_mark.key_event() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    self.perform(Cmd::Move(Direction::Down));
+                    return Some(Msg::YoutubeSearch(YSMsg::TablePopupToggle(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.youtube_search_mark_page.key_event() =>
+            {
+                return Some(Msg::YoutubeSearch(YSMsg::TablePopupTogglePage))
+            }
+            Event::Keyboard(keyevent) if keyevent == self.keys.youtube_search_play.key_event() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::YoutubeSearch(YSMsg::TablePopupPlay(index)));
+                }
+                CmdResult::None
+            }
             _ => CmdResult::None,
         };
         Some(Msg::None)
@@ -25,8 +25,10 @@ pub mod components;
 pub mod model;
 
 use crate::config::{BindingForEvent, ColorTermusic, Settings};
+use crate::invidious::{InstanceCheck, YoutubeStream};
+use crate::podcast::sync::SyncOutcome;
 use crate::podcast::{EpData, PodcastFeed, PodcastNoId};
-use crate::songtag::SongTag;
+use crate::songtag::{musicbrainz::Release, SongTag};
 use components::ImageWrapper;
 use model::YoutubeOptions;
 use model::{Model, TermusicLayout};
@@ -77,6 +79,10 @@ pub enum Msg {
     SavePlaylistPopupCloseOk(String),
     SavePlaylistConfirmCloseCancel,
     SavePlaylistConfirmCloseOk(String),
+    SleepTimerPopupShow,
+    SleepTimerPopupCloseCancel,
+    SleepTimerPopupCloseOk(String),
+    SleepTimerCancel,
     TagEditor(TEMsg),
     UpdatePhoto,
     YoutubeSearch(YSMsg),
@@ -102,10 +108,18 @@ pub enum DLMsg {
     DownloadCompleted(String, Option<String>),
     DownloadErrDownload(String, String, String),
     DownloadErrEmbedData(String, String),
+    /// A download finished, but its file is missing from the output of yt-dlp.
+    DownloadErrFilePath(String),
+    /// Position (starting at 1) and length of the youtube download queue.
+    DownloadQueueItem(usize, usize),
+    /// Number of finished downloads and the titles of the failed ones.
+    DownloadQueueFinished(usize, Vec<String>),
     MessageShow((String, String)),
     MessageHide((String, String)),
     YoutubeSearchSuccess(YoutubeOptions),
     YoutubeSearchFail(String),
+    YoutubeStreamReady(YoutubeStream),
+    YoutubeStreamFail(String),
     FetchPhotoSuccess(ImageWrapper),
     FetchPhotoErr(String),
 }
@@ -114,6 +128,7 @@ pub enum DLMsg {
 pub enum LyricMsg {
     LyricTextAreaBlurUp,
     LyricTextAreaBlurDown,
+    SeekToLine(usize),
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -178,6 +193,8 @@ pub enum ConfigEditorMsg {
     LyricBackgroundBlurUp,
     LyricBorderBlurDown,
     LyricBorderBlurUp,
+    LyricHighlightBlurDown,
+    LyricHighlightBlurUp,
     ThemeSelectBlurDown,
     ThemeSelectBlurUp,
     ThemeSelectLoad(usize),
@@ -186,6 +203,13 @@ pub enum ConfigEditorMsg {
     SaveLastPosotionBlurUp,
     SeekStepBlurDown,
     SeekStepBlurUp,
+    InvidiousInstancesBlurDown,
+    InvidiousInstancesBlurUp,
+    InvidiousPreferredBlurDown,
+    InvidiousPreferredBlurUp,
+    InvidiousTestBlurUp,
+    InvidiousTestRun,
+    InvidiousTestResult(Vec<InstanceCheck>),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -300,6 +324,10 @@ pub enum KFMsg {
     GlobalXywhZoomOutBlurUp,
     GlobalXywhHideBlurDown,
     GlobalXywhHideBlurUp,
+    GlobalSleepTimerBlurDown,
+    GlobalSleepTimerBlurUp,
+    GlobalSleepTimerCancelBlurDown,
+    GlobalSleepTimerCancelBlurUp,
     PodcastMarkPlayedBlurDown,
     PodcastMarkPlayedBlurUp,
     PodcastMarkAllPlayedBlurDown,
@@ -318,6 +346,22 @@ pub enum KFMsg {
     PodcastRefreshFeedBlurUp,
     PodcastRefreshAllFeedsBlurDown,
     PodcastRefreshAllFeedsBlurUp,
+    PodcastToggleSkipSilenceBlurDown,
+    PodcastToggleSkipSilenceBlurUp,
+    PodcastSyncNowBlurDown,
+    PodcastSyncNowBlurUp,
+    PodcastEpisodeShowNotesBlurDown,
+    PodcastEpisodeShowNotesBlurUp,
+    PlaylistDownloadBlurDown,
+    PlaylistDownloadBlurUp,
+    TagEditorLookupBlurDown,
+    TagEditorLookupBlurUp,
+    YoutubeSearchMarkBlurDown,
+    YoutubeSearchMarkBlurUp,
+    YoutubeSearchMarkPageBlurDown,
+    YoutubeSearchMarkPageBlurUp,
+    YoutubeSearchPlayBlurDown,
+    YoutubeSearchPlayBlurUp,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -344,6 +388,7 @@ pub enum DBMsg {
     SearchTrack(usize),
     SearchTracksBlurDown,
     SearchTracksBlurUp,
+    TagEditorBatch(usize),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -379,10 +424,18 @@ pub enum PCMsg {
     FeedsDeleteShow,
     FeedsDeleteCloseOk,
     FeedsDeleteCloseCancel,
-    SearchItunesCloseCancel,
-    SearchItunesCloseOk(usize),
+    FeedToggleSkipSilence(usize),
+    RemoteSyncStart,
+    RemoteSyncSuccess(SyncOutcome),
+    RemoteSyncError(String),
+    SearchCloseCancel,
+    SearchCloseOk(usize),
     SearchSuccess(Vec<PodcastFeed>),
     SearchError(String),
+    ShowNotesPopupShow(usize),
+    ShowNotesPopupClose,
+    ShowNotesOpenLink(String),
+    ShowNotesSeek(String, u64),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -402,6 +455,7 @@ pub enum PLMsg {
     SwapUp(usize),
     CmusLQueue,
     CmusTQueue,
+    Download(usize),
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GSMsg {
@@ -430,15 +484,24 @@ pub enum YSMsg {
     TablePopupPrevious,
     TablePopupCloseCancel,
     TablePopupCloseOk(usize),
+    TablePopupToggle(usize),
+    TablePopupTogglePage,
+    TablePopupPlay(usize),
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TEMsg {
     TagEditorRun(String),
     TagEditorClose(Option<String>),
+    TEBatchApply,
+    TEBatchPreview,
     TECounterDeleteOk,
     TEDownload(usize),
     TEEmbed(usize),
     TEFocus(TFMsg),
+    TELookup,
+    TELookupApply(usize),
+    TELookupClose,
+    TELookupResult(Result<Vec<Release>, String>),
     TERename,
     TESearch,
     TESelectLyricOk(usize),
@@ -462,6 +525,22 @@ pub enum TFMsg {
     TableLyricOptionsBlurUp,
     TextareaLyricBlurDown,
     TextareaLyricBlurUp,
+    BatchAlbumBlurDown,
+    BatchAlbumBlurUp,
+    BatchAlbumArtistBlurDown,
+    BatchAlbumArtistBlurUp,
+    BatchGenreBlurDown,
+    BatchGenreBlurUp,
+    BatchYearBlurDown,
+    BatchYearBlurUp,
+    BatchTrackNumberBlurDown,
+    BatchTrackNumberBlurUp,
+    BatchParseBlurDown,
+    BatchParseBlurUp,
+    BatchRenameBlurDown,
+    BatchRenameBlurUp,
+    BatchPreviewBlurDown,
+    BatchPreviewBlurUp,
 }
 // Let's define the component ids for our application
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
@@ -494,6 +573,8 @@ pub enum Id {
     SavePlaylistPopup,
     SavePlaylistLabel,
     SavePlaylistConfirm,
+    ShowNotesPopup,
+    SleepTimerPopup,
     TagEditor(IdTagEditor),
     YoutubeSearchInputPopup,
     YoutubeSearchTablePopup,
@@ -510,6 +591,15 @@ pub enum IdTagEditor {
     SelectLyric,
     TableLyricOptions,
     TextareaLyric,
+    TableMusicBrainz,
+    BatchAlbum,
+    BatchAlbumArtist,
+    BatchGenre,
+    BatchYear,
+    BatchTrackNumber,
+    BatchParse,
+    BatchRename,
+    BatchPreview,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
@@ -548,8 +638,12 @@ pub enum IdConfigEditor {
     LyricForeground,
     LyricBackground,
     LyricBorder,
+    LyricHighlight,
     SaveLastPosition,
     SeekStep,
+    InvidiousInstances,
+    InvidiousPreferred,
+    InvidiousTest,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
@@ -609,6 +703,8 @@ pub enum IdKey {
     GlobalXywhZoomIn,
     GlobalXywhZoomOut,
     GlobalXywhHide,
+    GlobalSleepTimer,
+    GlobalSleepTimerCancel,
     PodcastMarkPlayed,
     PodcastMarkAllPlayed,
     PodcastEpDownload,
@@ -618,6 +714,14 @@ pub enum IdKey {
     PodcastSearchAddFeed,
     PodcastRefreshFeed,
     PodcastRefreshAllFeeds,
+    PodcastToggleSkipSilence,
+    PodcastSyncNow,
+    PodcastEpisodeShowNotes,
+    PlaylistDownload,
+    TagEditorLookup,
+    YoutubeSearchMark,
+    YoutubeSearchMarkPage,
+    YoutubeSearchPlay,
 }
 pub enum SearchLyricState {
     Finish(Vec<SongTag>),
@@ -632,6 +736,7 @@ impl UI {
     pub fn new(config: &Settings) -> Self {
         let mut model = Model::new(config);
         model.init_config();
+        model.session_restore();
         Self { model }
     }
     /// ### run
@@ -639,25 +744,18 @@ impl UI {
     /// Main loop for Ui thread
     pub fn run(&mut self) {
         self.model.init_terminal();
+        if self.model.config.podcast_sync_on_start && !self.model.config.podcast_sync_url.is_empty()
+        {
+            self.model.podcast_remote_sync();
+        }
         // Main loop
-        let mut progress_interval = 0;
         while !self.model.quit {
-            #[cfg(feature = "mpris")]
-            self.model.update_mpris();
-
             self.model.te_update_lyric_options();
             self.model.update_player_msg();
             self.model.update_outside_msg();
             if self.model.layout != TermusicLayout::Podcast {
                 self.model.lyric_update();
             }
-            if progress_interval == 0 {
-                self.model.run();
-            }
-            progress_interval += 1;
-            if progress_interval >= 80 {
-                progress_interval = 0;
-            }
 
             match self.model.app.tick(PollStrategy::Once) {
                 Err(err) => {
@@ -681,8 +779,12 @@ impl UI {
             self.model.view();
             // sleep(Duration::from_millis(20));
         }
-        self.model.player_save_last_position();
-        assert!(self.model.player.playlist.save().is_ok());
+        if let Err(e) = self.model.podcast_remote_sync_push() {
+            eprintln!("{e}");
+        }
+        if let Err(e) = self.model.session_save() {
+            eprintln!("{e}");
+        }
         if let Err(e) = self.model.config.save() {
             eprintln!("{e}");
         };
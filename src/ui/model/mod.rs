@@ -24,8 +24,7 @@
 
 #[cfg(feature = "discord")]
 use crate::discord::Rpc;
-#[cfg(feature = "mpris")]
-mod mpris;
+mod session;
 mod update;
 mod view;
 mod youtube_options;
@@ -39,13 +38,17 @@ use crate::{
 };
 
 use crate::config::{Keys, StyleColorSymbol};
-use crate::player::{GeneralPlayer, Loop, PlayerTrait};
+use crate::player::PlayerTrait;
 use crate::podcast::{db::Database as DBPod, Podcast, PodcastFeed, Threadpool};
-use crate::songtag::SongTag;
+use crate::server::timer::{self, Setting};
+use crate::server::RemotePlayer;
+use crate::songtag::{musicbrainz::Release, SongTag};
 use crate::sqlite::TrackForDB;
-use crate::track::MediaType;
 use crate::ui::SearchLyricState;
 use crate::utils::{get_app_config_path, DownloadTracker};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::time::{Duration, Instant};
@@ -54,8 +57,9 @@ use tuirealm::event::NoUserEvent;
 use tuirealm::terminal::TerminalBridge;
 pub use youtube_options::YoutubeOptions;
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 pub enum TermusicLayout {
+    #[default]
     TreeView,
     DataBase,
     Podcast,
@@ -67,6 +71,7 @@ pub enum ConfigEditorLayout {
     Color,
     Key1,
     Key2,
+    Invidious,
 }
 
 pub struct Model {
@@ -81,24 +86,30 @@ pub struct Model {
     pub path: PathBuf,
     pub tree: Tree,
     pub config: Settings,
-    pub player: GeneralPlayer,
+    pub player: RemotePlayer,
     pub yanked_node_id: Option<String>,
     // pub current_song: Option<Track>,
     pub tageditor_song: Option<Track>,
+    pub tageditor_batch: Vec<Track>,
     pub time_pos: i64,
+    /// When `time_pos` last changed, to follow word timing between progress updates.
+    pub time_pos_at: Instant,
     pub lyric_line: String,
+    /// Synced lyric lines shown in the lyric pane, with the line and word being sung.
+    pub lyric_lines: Vec<String>,
+    pub lyric_current: Option<usize>,
+    pub lyric_word: Option<usize>,
     youtube_options: YoutubeOptions,
     #[cfg(feature = "cover")]
     pub ueberzug_instance: UeInstance,
     pub songtag_options: Vec<SongTag>,
+    pub musicbrainz_options: Vec<Release>,
     pub sender_songtag: Sender<SearchLyricState>,
     pub receiver_songtag: Receiver<SearchLyricState>,
     pub viuer_supported: ViuerSupported,
     pub ce_themes: Vec<String>,
     pub ce_style_color_symbol: StyleColorSymbol,
     pub ke_key_config: Keys,
-    #[cfg(feature = "mpris")]
-    pub mpris: mpris::Mpris,
     #[cfg(feature = "discord")]
     pub discord: Rpc,
     pub db: DataBase,
@@ -116,6 +127,8 @@ pub struct Model {
     pub tx_to_main: Sender<Msg>,
     pub rx_to_main: Receiver<Msg>,
     pub podcast_search_vec: Option<Vec<PodcastFeed>>,
+    /// Feeds subscribed on another device, so adding them isn't sent back to the sync server.
+    pub podcast_synced_feeds: HashSet<String>,
 }
 
 pub enum ViuerSupported {
@@ -141,7 +154,8 @@ impl Model {
         let db_criteria = SearchCriteria::Artist;
         let app = Self::init_app(&tree, config);
         let terminal = TerminalBridge::new().expect("Could not initialize terminal");
-        let player = GeneralPlayer::new(config);
+        let player =
+            RemotePlayer::connect(config).expect("failed to attach to the playback daemon");
         // let viuer_supported =
         //     viuer::KittySupport::None != viuer::get_kitty_support() || viuer::is_iterm_supported();
 
@@ -149,7 +163,7 @@ impl Model {
         let ueberzug_instance = UeInstance::default();
 
         #[cfg(feature = "discord")]
-        let discord = Rpc::default();
+        let discord = Rpc::new(config);
 
         let db_path = get_app_config_path().expect("failed to get podcast db path.");
 
@@ -174,20 +188,24 @@ impl Model {
             yanked_node_id: None,
             // current_song: None,
             tageditor_song: None,
+            tageditor_batch: Vec::new(),
             time_pos: 0,
+            time_pos_at: Instant::now(),
             lyric_line: String::new(),
+            lyric_lines: Vec::new(),
+            lyric_current: None,
+            lyric_word: None,
             youtube_options: YoutubeOptions::new(),
             #[cfg(feature = "cover")]
             ueberzug_instance,
             songtag_options: vec![],
+            musicbrainz_options: vec![],
             sender_songtag: tx3,
             receiver_songtag: rx3,
             viuer_supported,
             ce_themes: vec![],
             ce_style_color_symbol: StyleColorSymbol::default(),
             ke_key_config: Keys::default(),
-            #[cfg(feature = "mpris")]
-            mpris: mpris::Mpris::default(),
             #[cfg(feature = "discord")]
             discord,
             db,
@@ -205,6 +223,7 @@ impl Model {
             rx_to_main,
             download_tracker: DownloadTracker::default(),
             podcast_search_vec: None,
+            podcast_synced_feeds: HashSet::new(),
         }
     }
 
@@ -250,40 +269,15 @@ impl Model {
         self.redraw = true;
     }
 
-    pub fn run(&mut self) {
-        if self.player.playlist.is_stopped() {
-            self.player.start_play();
-            self.player_restore_last_position();
-        }
-    }
-
-    pub fn player_stop(&mut self) {
-        self.time_pos = 0;
-        self.player.stop();
-        self.player
-            .message_tx
-            .send(crate::player::PlayerMsg::Progress(0, 60))
-            .ok();
-        if let Err(e) = self.update_photo() {
-            self.mount_error_popup(format!("update photo error: {e}"));
-        };
-        self.progress_update_title();
-        self.lyric_update_title();
-        self.lyric_update();
-        self.force_redraw();
-    }
-
     pub fn player_update_current_track_after(&mut self) {
-        #[cfg(any(feature = "mpris", feature = "discord"))]
+        #[cfg(feature = "discord")]
         if let Some(song) = self.player.playlist.current_track() {
-            #[cfg(feature = "mpris")]
-            self.mpris.add_and_play(song);
-            #[cfg(feature = "discord")]
             if !self.config.disable_discord_rpc_from_cli {
                 self.discord.update(song);
             }
         }
         self.time_pos = 0;
+        self.lyric_load_files();
         self.playlist_sync();
         if let Err(e) = self.update_photo() {
             self.mount_error_popup(format!("update photo error: {e}"));
@@ -294,152 +288,88 @@ impl Model {
     }
 
     pub fn player_previous(&mut self) {
-        if let Loop::Single | Loop::Queue = self.config.loop_mode {
-            return;
-        }
-
-        if self.player.playlist.is_empty() {
-            self.player_stop();
-            return;
-        }
-
-        self.player.playlist.handle_previous();
-        self.player.skip();
+        self.player.previous();
     }
 
     pub fn player_toggle_pause(&mut self) {
         if self.player.playlist.is_empty() && self.player.playlist.current_track().is_none() {
             return;
         }
-        if self.player.is_paused() {
+        if self.player.playlist.is_stopped() {
+            self.player.start_play();
+        } else if self.player.is_paused() {
             self.player.resume();
-            #[cfg(feature = "mpris")]
-            self.mpris.resume();
-            #[cfg(feature = "discord")]
-            self.discord.resume(self.time_pos);
         } else {
             self.player.pause();
-            #[cfg(feature = "mpris")]
-            self.mpris.pause();
-            #[cfg(feature = "discord")]
-            self.discord.pause();
         }
         self.progress_update_title();
     }
 
     pub fn player_seek(&mut self, offset: i64) {
-        // FIXME: dirty fix for seeking when paused with symphonia,basically set it to play
-        // in rusty sink code, and seek, and then set it back to pause.
-        #[cfg(not(any(feature = "mpv", feature = "gst")))]
-        let paused = self.player.is_paused();
-        #[cfg(not(any(feature = "mpv", feature = "gst")))]
-        if paused {
-            self.player.set_volume(0);
-        }
-
         self.player.seek(offset).ok();
-
-        #[cfg(not(any(feature = "mpv", feature = "gst")))]
-        if paused {
-            self.force_redraw();
-            std::thread::sleep(std::time::Duration::from_millis(50));
-            self.player.pause();
-            self.player.set_volume(self.config.volume);
-        }
     }
 
-    #[allow(clippy::cast_sign_loss)]
-    pub fn player_save_last_position(&mut self) {
-        match self.config.remember_last_played_position {
-            crate::config::LastPosition::Yes => {
-                if let Some(track) = self.player.playlist.current_track() {
-                    match track.media_type {
-                        Some(MediaType::Music) => self
-                            .db
-                            .set_last_position(track, Duration::from_secs(self.time_pos as u64)),
-                        Some(MediaType::Podcast) => self
-                            .db_podcast
-                            .set_last_position(track, Duration::from_secs(self.time_pos as u64)),
-                        None => {}
-                    }
-                }
-            }
-            crate::config::LastPosition::No => {}
-            crate::config::LastPosition::Auto => {
-                if let Some(track) = self.player.playlist.current_track() {
-                    // 10 minutes
-                    if track.duration().as_secs() >= 600 {
-                        match track.media_type {
-                            Some(MediaType::Music) => self.db.set_last_position(
-                                track,
-                                Duration::from_secs(self.time_pos as u64),
-                            ),
-                            Some(MediaType::Podcast) => self.db_podcast.set_last_position(
-                                track,
-                                Duration::from_secs(self.time_pos as u64),
-                            ),
-                            None => {}
-                        }
-                    }
-                }
+    /// Sets the sleep timer or the alarm from the text of the sleep timer popup.
+    pub fn sleep_timer_set(&mut self, spec: &str) -> Result<()> {
+        match timer::parse(spec)? {
+            Setting::Sleep(sleep) => self.player.set_sleep(Some(sleep)),
+            Setting::Alarm(alarm) => self.player.set_alarm(Some(alarm)),
+            Setting::Off => {
+                self.sleep_timer_cancel();
+                return Ok(());
             }
         }
+        self.progress_update_title();
+        Ok(())
     }
 
-    // #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
-    pub fn player_restore_last_position(&mut self) {
-        let mut restored = false;
-        match self.config.remember_last_played_position {
-            crate::config::LastPosition::Yes => {
-                if let Some(track) = self.player.playlist.current_track() {
-                    match track.media_type {
-                        Some(MediaType::Music) => {
-                            if let Ok(last_pos) = self.db.get_last_position(track) {
-                                self.player.seek_to(last_pos);
-                                restored = true;
-                            }
-                        }
+    pub fn sleep_timer_cancel(&mut self) {
+        self.player.set_sleep(None);
+        self.player.set_alarm(None);
+        self.progress_update_title();
+    }
 
-                        Some(MediaType::Podcast) => {
-                            if let Ok(last_pos) = self.db_podcast.get_last_position(track) {
-                                self.player.seek_to(last_pos);
-                                restored = true;
-                            }
-                        }
-                        None => {}
-                    }
-                }
-            }
-            crate::config::LastPosition::No => {}
-            crate::config::LastPosition::Auto => {
-                if let Some(track) = self.player.playlist.current_track() {
-                    // 10 minutes
-                    if track.duration().as_secs() >= 600 {
-                        match track.media_type {
-                            Some(MediaType::Music) => {
-                                if let Ok(last_pos) = self.db.get_last_position(track) {
-                                    self.player.seek_to(last_pos);
-                                    restored = true;
-                                }
-                            }
+    pub fn seek_step(&self) -> i64 {
+        let duration = self
+            .player
+            .playlist
+            .current_track()
+            .map(Track::duration)
+            .unwrap_or_default();
+        self.config.seek_step.seconds(duration)
+    }
 
-                            Some(MediaType::Podcast) => {
-                                if let Ok(last_pos) = self.db_podcast.get_last_position(track) {
-                                    self.player.seek_to(last_pos);
-                                    restored = true;
-                                }
-                            }
-                            None => {}
-                        }
-                    }
-                }
+    /// Applies the speed and silence skipping of the podcast being played, or the global
+    /// speed for everything else.
+    pub fn player_restore_speed(&mut self) {
+        let (speed, skip_silence) = match self.podcast_get_current_feed_index() {
+            Some(idx) => {
+                let pod = &self.podcasts[idx];
+                (pod.speed.unwrap_or(self.config.speed), pod.skip_silence)
             }
+            None => (self.config.speed, false),
+        };
+        if speed != self.player.speed() {
+            self.player.set_speed(speed);
+        }
+        if skip_silence != self.player.skip_silence() {
+            self.player.set_skip_silence(skip_silence);
         }
+        self.progress_update_title();
+    }
 
-        if restored {
-            if let Some(track) = self.player.playlist.current_track() {
-                self.db.set_last_position(track, Duration::from_secs(0));
+    /// Remembers the current speed, per feed for podcasts and globally otherwise.
+    pub fn player_save_speed(&mut self) {
+        let speed = self.player.speed();
+        if let Some(idx) = self.podcast_get_current_feed_index() {
+            let pod = &mut self.podcasts[idx];
+            pod.speed = Some(speed);
+            if let Err(e) = self.db_podcast.set_podcast_speed(pod.id, Some(speed)) {
+                self.mount_error_popup(format!("Error save podcast speed: {e}"));
             }
+            self.podcast_sync_feeds_and_episodes();
+            return;
         }
+        self.config.speed = speed;
     }
 }
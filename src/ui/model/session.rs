@@ -0,0 +1,173 @@
+/**
+ * MIT License
+ *
+ * termusic - Copyright (c) 2021 Larry Hao
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+use super::{Model, TermusicLayout};
+use crate::config::{load_session, save_session};
+use crate::sqlite::SearchCriteria;
+use crate::ui::{Id, Msg};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tui_realm_treeview::TREE_INITIAL_NODE;
+use tuirealm::props::{PropPayload, PropValue};
+use tuirealm::{AttrValue, Attribute, State, StateValue, Update};
+
+/// Layout and selections of the tui, saved to `session_view.toml` on exit.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ViewSession {
+    pub layout: TermusicLayout,
+    /// Selected node of the library tree.
+    pub library: Option<String>,
+    pub playlist: usize,
+    /// Criteria searched in the database layout, and the result and track selected there.
+    pub database_criteria: Option<SearchCriteria>,
+    pub database_result: usize,
+    pub database_track: usize,
+    pub podcast: usize,
+    pub episode: usize,
+}
+
+impl ViewSession {
+    pub fn load() -> Result<Option<Self>> {
+        load_session("session_view.toml")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_session("session_view.toml", self)
+    }
+}
+
+impl Model {
+    /// Saves the layout and what is selected in each pane, for the next start.
+    pub fn session_save(&self) -> Result<()> {
+        let library = match self.app.state(&Id::Library) {
+            Ok(State::One(StateValue::String(node))) => Some(node),
+            _ => None,
+        };
+        // the search results only come back when there were some
+        let database_criteria = if self.db_search_results.is_empty() {
+            None
+        } else {
+            Some(self.db_criteria.clone())
+        };
+        ViewSession {
+            layout: self.layout,
+            library,
+            playlist: self.session_selected(&Id::Playlist),
+            database_criteria,
+            database_result: self.session_selected(&Id::DBListSearchResult),
+            database_track: self.session_selected(&Id::DBListSearchTracks),
+            podcast: self.podcasts_index,
+            episode: self.session_selected(&Id::Episode),
+        }
+        .save()
+    }
+
+    pub fn session_restore(&mut self) {
+        let session = match ViewSession::load() {
+            Ok(Some(session)) => session,
+            Ok(None) => return,
+            Err(e) => {
+                self.mount_error_popup(format!("error loading view session: {e}"));
+                return;
+            }
+        };
+
+        if let Some(node) = session
+            .library
+            .filter(|node| self.tree.root().query(node).is_some())
+        {
+            self.app
+                .attr(
+                    &Id::Library,
+                    Attribute::Custom(TREE_INITIAL_NODE),
+                    AttrValue::String(node),
+                )
+                .ok();
+        }
+        if session.playlist < self.player.playlist.len() {
+            self.playlist_locate(session.playlist);
+        }
+
+        if let Some(criteria) = session.database_criteria {
+            self.db_criteria = criteria;
+            self.database_update_search_results();
+            if session.database_result < self.db_search_results.len() {
+                self.session_locate(&Id::DBListSearchResult, session.database_result);
+                self.database_update_search_tracks(session.database_result);
+                if session.database_track < self.db_search_tracks.len() {
+                    self.session_locate(&Id::DBListSearchTracks, session.database_track);
+                }
+            }
+        }
+
+        if session.podcast < self.podcasts.len() {
+            self.podcasts_index = session.podcast;
+            self.session_locate(&Id::Podcast, session.podcast);
+            if let Err(e) = self.podcast_sync_episodes() {
+                self.mount_error_popup(format!("Error sync episodes: {e}"));
+            }
+            if session.episode < self.podcasts[session.podcast].episodes.len() {
+                self.session_locate(&Id::Episode, session.episode);
+            }
+        }
+
+        let layout = match session.layout {
+            TermusicLayout::TreeView => Msg::LayoutTreeView,
+            TermusicLayout::DataBase => Msg::LayoutDataBase,
+            TermusicLayout::Podcast => Msg::LayoutPodCast,
+        };
+        self.update(Some(layout));
+    }
+
+    fn session_selected(&self, id: &Id) -> usize {
+        match self.app.state(id) {
+            Ok(State::One(StateValue::Usize(index))) => index,
+            _ => 0,
+        }
+    }
+
+    fn session_locate(&mut self, id: &Id, index: usize) {
+        self.app
+            .attr(
+                id,
+                Attribute::Value,
+                AttrValue::Payload(PropPayload::One(PropValue::Usize(index))),
+            )
+            .ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_session_defaults() {
+        // older or hand edited files miss fields
+        let view: ViewSession = toml::from_str("playlist = 3").unwrap();
+        assert_eq!(view.playlist, 3);
+        assert_eq!(view.layout, TermusicLayout::TreeView);
+        assert_eq!(view.database_criteria, None);
+    }
+}
@@ -21,13 +21,16 @@
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
-use crate::player::{PlayerMsg, PlayerTrait};
+use crate::invidious::YoutubeList;
+use crate::player::PlayerTrait;
+use crate::server::Event;
 use crate::sqlite::SearchCriteria;
 use crate::track::MediaType;
 use crate::ui::{
     model::TermusicLayout, DBMsg, DLMsg, GSMsg, Id, IdTagEditor, LIMsg, LyricMsg, Model, Msg,
     PCMsg, PLMsg, XYWHMsg, YSMsg,
 };
+use crate::utils::DownloadTracker;
 use std::thread::{self, sleep};
 use std::time::Duration;
 use tuirealm::props::{AttrValue, Attribute};
@@ -131,6 +134,25 @@ impl Update<Msg> for Model {
                 }
 
                 Msg::None => None,
+                Msg::SleepTimerPopupShow => {
+                    self.mount_sleep_timer_popup();
+                    None
+                }
+                Msg::SleepTimerPopupCloseCancel => {
+                    self.umount_sleep_timer_popup();
+                    None
+                }
+                Msg::SleepTimerPopupCloseOk(spec) => {
+                    self.umount_sleep_timer_popup();
+                    if let Err(e) = self.sleep_timer_set(&spec) {
+                        self.mount_error_popup(format!("sleep timer error: {e}"));
+                    }
+                    None
+                }
+                Msg::SleepTimerCancel => {
+                    self.sleep_timer_cancel();
+                    None
+                }
                 Msg::SavePlaylistPopupShow => {
                     if let Err(e) = self.mount_save_playlist() {
                         self.mount_error_popup(format!("save playlist error: {e}"));
@@ -200,6 +222,10 @@ impl Model {
                 TermusicLayout::DataBase => self.app.active(&Id::DBListCriteria).ok(),
                 TermusicLayout::Podcast => self.app.active(&Id::Podcast).ok(),
             },
+            LyricMsg::SeekToLine(index) => {
+                self.lyric_seek_to_line(*index);
+                None
+            }
         };
         None
     }
@@ -226,7 +252,7 @@ impl Model {
                 if url.starts_with("http") {
                     self.podcast_add(url);
                 } else {
-                    self.podcast_search_itunes(url);
+                    self.podcast_search(url);
                     self.mount_podcast_search_table();
                 }
             }
@@ -292,6 +318,20 @@ impl Model {
                     self.mount_error_popup(format!("Error in Sync One: {e}"));
                 }
             }
+            PCMsg::FeedToggleSkipSilence(index) => {
+                if let Err(e) = self.podcast_toggle_skip_silence(*index) {
+                    self.mount_error_popup(format!("Error toggle skip silence: {e}"));
+                }
+            }
+            PCMsg::RemoteSyncStart => self.podcast_remote_sync(),
+            PCMsg::RemoteSyncSuccess(outcome) => {
+                if let Err(e) = self.podcast_remote_sync_complete(outcome) {
+                    self.mount_error_popup(format!("Error in podcast sync: {e}"));
+                }
+            }
+            PCMsg::RemoteSyncError(e) => {
+                self.mount_error_popup(format!("Error in podcast sync: {e}"));
+            }
             PCMsg::PodcastRefreshAll => {
                 if let Err(e) = self.podcast_refresh_feeds(None) {
                     self.mount_error_popup(format!("Error in Sync All: {e}"));
@@ -386,8 +426,8 @@ impl Model {
                 }
             }
             PCMsg::FeedsDeleteCloseCancel => self.umount_feed_delete_confirm_input(),
-            PCMsg::SearchItunesCloseCancel => self.umount_podcast_search_table(),
-            PCMsg::SearchItunesCloseOk(index) => {
+            PCMsg::SearchCloseCancel => self.umount_podcast_search_table(),
+            PCMsg::SearchCloseOk(index) => {
                 if let Some(vec) = &self.podcast_search_vec {
                     if let Some(pod) = vec.get(*index) {
                         let url = pod.url.clone();
@@ -400,6 +440,14 @@ impl Model {
                 self.update_podcast_search_table();
             }
             PCMsg::SearchError(e) => self.mount_error_popup(e),
+            PCMsg::ShowNotesPopupShow(index) => {
+                if let Err(e) = self.mount_show_notes_popup(*index) {
+                    self.mount_error_popup(format!("Error show notes: {e}"));
+                }
+            }
+            PCMsg::ShowNotesPopupClose => self.umount_show_notes_popup(),
+            PCMsg::ShowNotesOpenLink(url) => self.show_notes_open_link(url),
+            PCMsg::ShowNotesSeek(url, secs) => self.show_notes_seek(url, *secs),
         }
         None
     }
@@ -410,49 +458,19 @@ impl Model {
             }
 
             Msg::PlayerSeekForward => {
-                let offset = match self.config.seek_step {
-                    crate::config::SeekStep::Short => 5_i64,
-                    crate::config::SeekStep::Long => 30,
-                    crate::config::SeekStep::Auto => {
-                        if let Some(track) = self.player.playlist.current_track() {
-                            if track.duration().as_secs() >= 600 {
-                                30
-                            } else {
-                                5
-                            }
-                        } else {
-                            5
-                        }
-                    }
-                };
-                self.player_seek(offset);
+                self.player_seek(self.seek_step());
             }
             Msg::PlayerSeekBackward => {
-                let offset = match self.config.seek_step {
-                    crate::config::SeekStep::Short => -5_i64,
-                    crate::config::SeekStep::Long => -30,
-                    crate::config::SeekStep::Auto => {
-                        if let Some(track) = self.player.playlist.current_track() {
-                            if track.duration().as_secs() >= 600 {
-                                -30
-                            } else {
-                                -5
-                            }
-                        } else {
-                            -5
-                        }
-                    }
-                };
-                self.player_seek(offset);
+                self.player_seek(-self.seek_step());
             }
             Msg::PlayerSpeedUp => {
                 self.player.speed_up();
-                self.config.speed = self.player.speed();
+                self.player_save_speed();
                 self.progress_update_title();
             }
             Msg::PlayerSpeedDown => {
                 self.player.speed_down();
-                self.config.speed = self.player.speed();
+                self.player_save_speed();
                 self.progress_update_title();
             }
             Msg::PlayerVolumeUp => {
@@ -588,6 +606,15 @@ impl Model {
             DBMsg::SearchTrack(index) => {
                 self.database_update_search_tracks(*index);
             }
+            DBMsg::TagEditorBatch(index) => {
+                self.database_update_search_tracks(*index);
+                let files: Vec<String> = self
+                    .db_search_tracks
+                    .iter()
+                    .map(|track| track.file.clone())
+                    .collect();
+                self.mount_tageditor_batch(&files);
+            }
             DBMsg::AddPlaylist(index) => {
                 if !self.db_search_tracks.is_empty() {
                     if let Some(track) = self.db_search_tracks.get(*index) {
@@ -649,7 +676,10 @@ impl Model {
                 if self.app.mounted(&Id::YoutubeSearchInputPopup) {
                     assert!(self.app.umount(&Id::YoutubeSearchInputPopup).is_ok());
                 }
-                if url.starts_with("http") {
+                if let Some(list) = YoutubeList::parse(url) {
+                    self.mount_youtube_search_table();
+                    self.youtube_options_list(list);
+                } else if url.starts_with("http") {
                     match self.youtube_dl(url) {
                         Ok(_) => {}
                         Err(e) => {
@@ -670,6 +700,13 @@ impl Model {
             YSMsg::TablePopupPrevious => {
                 self.youtube_options_prev_page();
             }
+            YSMsg::TablePopupToggle(index) => self.youtube_options_toggle(*index),
+            YSMsg::TablePopupTogglePage => self.youtube_options_toggle_page(),
+            YSMsg::TablePopupPlay(index) => {
+                if let Err(e) = self.youtube_options_play(*index) {
+                    self.mount_error_popup(format!("Error playing: {e}"));
+                }
+            }
             YSMsg::TablePopupCloseOk(index) => {
                 if let Err(e) = self.youtube_options_download(*index) {
                     self.library_reload_with_node_focus(None);
@@ -795,6 +832,11 @@ impl Model {
             PLMsg::Delete(index) => {
                 self.playlist_delete_item(*index);
             }
+            PLMsg::Download(index) => {
+                if let Err(e) = self.playlist_download(*index) {
+                    self.mount_error_popup(format!("Error downloading: {e}"));
+                }
+            }
             PLMsg::DeleteAll => {
                 self.playlist_clear();
             }
@@ -819,7 +861,6 @@ impl Model {
                 TermusicLayout::Podcast => assert!(self.app.active(&Id::Lyric).is_ok()),
             },
             PLMsg::NextSong => {
-                self.player_save_last_position();
                 self.player.skip();
                 // self.playlist_update_title();
             }
@@ -886,83 +927,82 @@ impl Model {
 
     // update player messages
     pub fn update_player_msg(&mut self) {
-        if let Ok(msg) = self.player.message_rx.try_recv() {
-            match msg {
-                PlayerMsg::Eos => {
-                    // eprintln!("Eos received");
-                    if self.player.playlist.is_empty() {
-                        self.player_stop();
-                        return;
-                    }
-                    self.player.playlist.handle_current_track();
-                    self.player.start_play();
-                    self.player_restore_last_position();
-                }
-                PlayerMsg::AboutToFinish => {
-                    if self.config.gapless {
-                        // eprintln!("about to finish received");
-                        self.player.enqueue_next();
-                    }
-                }
-                PlayerMsg::CurrentTrackUpdated => {
-                    self.update_layout_for_current_track();
-                    self.player_update_current_track_after();
-                    self.lyric_update_for_podcast_by_current_track();
-                    if (self.config.speed - 10).abs() >= 1 {
-                        self.player.set_speed(self.config.speed);
-                    }
-
-                    if let Err(e) = self.podcast_mark_current_track_played() {
-                        self.mount_error_popup(format!("Error when mark episode as played: {e}"));
-                    }
+        let event = match self.player.try_recv() {
+            Ok(Some(event)) => event,
+            Ok(None) => return,
+            Err(e) => {
+                self.mount_error_popup(format!("{e}"));
+                return;
+            }
+        };
+        match event {
+            Event::TrackChanged => {
+                self.update_layout_for_current_track();
+                self.player_update_current_track_after();
+                self.lyric_update_for_podcast_by_current_track();
+                self.podcast_mark_current_track_played();
+            }
+            Event::Progress { position, duration } => {
+                self.progress_update(position, duration);
+                // keeps the countdown of the sleep timer going
+                if self.player.countdown().is_some() {
+                    self.progress_update_title();
+                }
+                #[cfg(feature = "discord")]
+                if !self.config.disable_discord_rpc_from_cli {
+                    self.discord.progress(
+                        self.player.playlist.status(),
+                        position,
+                        duration,
+                        self.player.speed(),
+                    );
                 }
-                PlayerMsg::Progress(time_pos, duration) => {
-                    self.progress_update(time_pos, duration);
-                }
-                #[cfg(not(any(feature = "mpv", feature = "gst")))]
-                PlayerMsg::Duration(duration) => {
-                    self.player.player.total_duration = Some(Duration::from_secs(duration));
-                }
-                #[cfg(not(any(feature = "mpv", feature = "gst")))]
-                PlayerMsg::DurationNext(duration) => {
-                    self.player
-                        .playlist
-                        .set_next_track_duration(Duration::from_secs(duration));
-                }
-                #[cfg(not(any(feature = "mpv", feature = "gst")))]
-                PlayerMsg::CacheStart(url) => {
-                    self.download_tracker.increase_one(&url);
-                    self.download_tracker.time_stamp_for_cache = std::time::Instant::now();
-                    self.show_message_timeout_label_help(
-                        " Cache episode... ",
-                        None,
-                        None,
-                        Some(100),
+            }
+            Event::State(state) => {
+                self.config.volume = state.volume;
+                self.config.gapless = state.gapless;
+                self.progress_update_title();
+                #[cfg(feature = "discord")]
+                if !self.config.disable_discord_rpc_from_cli {
+                    self.discord.progress(
+                        state.status,
+                        state.position,
+                        state.duration,
+                        state.speed,
                     );
                 }
-                #[cfg(not(any(feature = "mpv", feature = "gst")))]
-                PlayerMsg::CacheEnd(url) => {
-                    self.download_tracker.decrease_one(&url);
-                    if self
-                        .download_tracker
-                        .time_stamp_for_cache
-                        .elapsed()
-                        .as_secs()
-                        < 10
-                    {
-                        let label = " Cache finished. Start Playing. ".to_string();
-                        self.show_message_timeout_label_help(&label, None, None, Some(5));
-                    } else {
-                        let label = " Cache finished but took more than 10 seconds. Start Downloading & Playing. ".to_string();
-                        self.show_message_timeout_label_help(&label, None, None, Some(5));
-                        if let Some(index) = self.podcast_get_episode_index_by_url(&url) {
-                            if let Err(e) = self.episode_download(Some(index)) {
-                                self.mount_error_popup(format!("Error in download episode: {e}"));
-                            }
+            }
+            Event::Queue(_) => {
+                self.config.loop_mode = self.player.playlist.loop_mode();
+                self.playlist_sync();
+            }
+            Event::CacheStart(url) => {
+                self.download_tracker.increase_one(&url);
+                self.download_tracker.time_stamp_for_cache = std::time::Instant::now();
+                self.show_message_timeout_label_help(" Cache episode... ", None, None, Some(100));
+            }
+            Event::CacheEnd(url) => {
+                self.download_tracker.decrease_one(&url);
+                if self
+                    .download_tracker
+                    .time_stamp_for_cache
+                    .elapsed()
+                    .as_secs()
+                    < 10
+                {
+                    let label = " Cache finished. Start Playing. ".to_string();
+                    self.show_message_timeout_label_help(&label, None, None, Some(5));
+                } else {
+                    let label = " Cache finished but took more than 10 seconds. Start Downloading & Playing. ".to_string();
+                    self.show_message_timeout_label_help(&label, None, None, Some(5));
+                    if let Some(index) = self.podcast_get_episode_index_by_url(&url) {
+                        if let Err(e) = self.episode_download(Some(index)) {
+                            self.mount_error_popup(format!("Error in download episode: {e}"));
                         }
                     }
                 }
             }
+            Event::Error(e) => self.mount_error_popup(e),
         }
     }
 
@@ -981,7 +1021,7 @@ impl Model {
                         self.update_layout(&Msg::LayoutTreeView);
                     }
                 },
-                None => {}
+                Some(MediaType::Youtube) | None => {}
             }
         }
     }
@@ -994,6 +1034,7 @@ impl Model {
     }
 
     // change status bar text to indicate the downloading state
+    #[allow(clippy::too_many_lines)]
     fn update_download_msg(&mut self, msg: &DLMsg) -> Option<Msg> {
         self.redraw = true;
         match msg {
@@ -1027,7 +1068,10 @@ impl Model {
             }
             DLMsg::DownloadErrDownload(url, title, error_message) => {
                 self.download_tracker.decrease_one(url);
-                self.mount_error_popup(format!("download failed: {error_message}"));
+                // failures in a queue are listed once it finished
+                if !self.download_tracker.in_queue() {
+                    self.mount_error_popup(format!("download failed: {error_message}"));
+                }
                 self.show_message_timeout_label_help(
                     self.download_tracker.message_download_error_response(title),
                     None,
@@ -1045,6 +1089,29 @@ impl Model {
                     None,
                 );
             }
+            DLMsg::DownloadErrFilePath(title) => {
+                self.mount_error_popup(format!(
+                    "{title} was downloaded, but its file was not found in the output of yt-dlp, so no lyrics were embedded."
+                ));
+            }
+            DLMsg::DownloadQueueItem(position, total) => {
+                self.download_tracker.set_queue_position(*position, *total);
+            }
+            DLMsg::DownloadQueueFinished(downloaded, failed) => {
+                self.download_tracker.finish_queue();
+                if !failed.is_empty() {
+                    self.mount_error_popup(format!("download failed: {}", failed.join(", ")));
+                }
+                self.show_message_timeout_label_help(
+                    DownloadTracker::message_queue_finished(*downloaded, failed),
+                    None,
+                    None,
+                    None,
+                );
+                if !self.download_tracker.visible() {
+                    self.library_reload_with_node_focus(None);
+                }
+            }
             DLMsg::MessageShow((title, text)) => {
                 self.mount_message(title, text);
             }
@@ -1059,6 +1126,12 @@ impl Model {
             DLMsg::YoutubeSearchFail(e) => {
                 self.mount_error_popup(format!("Youtube search fail: {e}"));
             }
+            DLMsg::YoutubeStreamReady(stream) => {
+                self.playlist_play_youtube(stream);
+            }
+            DLMsg::YoutubeStreamFail(e) => {
+                self.mount_error_popup(format!("Youtube stream fail: {e}"));
+            }
             DLMsg::FetchPhotoSuccess(image_wrapper) => {
                 self.show_image(&image_wrapper.data).ok();
             }
@@ -26,7 +26,7 @@ use crate::ui::components::{
     DBListCriteria, DBListSearchResult, DBListSearchTracks, DownloadSpinner, EpisodeList,
     ErrorPopup, FeedsList, GSInputPopup, GSTablePopup, GlobalListener, HelpPopup, LabelSpan, Lyric,
     MessagePopup, MusicLibrary, Playlist, PodcastAddPopup, Progress, QuitPopup,
-    SavePlaylistConfirm, SavePlaylistPopup, Source, YSInputPopup, YSTablePopup,
+    SavePlaylistConfirm, SavePlaylistPopup, SleepTimerPopup, Source, YSInputPopup, YSTablePopup,
 };
 use crate::utils::{
     draw_area_in_absolute, draw_area_in_relative, draw_area_top_right_absolute, get_parent_folder,
@@ -165,12 +165,16 @@ impl Model {
             {
                 self.view_tag_editor();
                 return;
+            } else if self.app.mounted(&Id::TagEditor(IdTagEditor::BatchPreview)) {
+                self.view_tag_editor_batch();
+                return;
             } else if self.app.mounted(&Id::ConfigEditor(IdConfigEditor::Header)) {
                 match self.config_layout {
                     ConfigEditorLayout::General => self.view_config_editor_general(),
                     ConfigEditorLayout::Color => self.view_config_editor_color(),
                     ConfigEditorLayout::Key1 => self.view_config_editor_key1(),
                     ConfigEditorLayout::Key2 => self.view_config_editor_key2(),
+                    ConfigEditorLayout::Invidious => self.view_config_editor_invidious(),
                 }
                 return;
             }
@@ -412,6 +416,10 @@ impl Model {
             let popup = draw_area_in_relative(f.size(), 65, 68);
             f.render_widget(Clear, popup);
             app.view(&Id::PodcastSearchTablePopup, f, popup);
+        } else if app.mounted(&Id::ShowNotesPopup) {
+            let popup = draw_area_in_relative(f.size(), 80, 80);
+            f.render_widget(Clear, popup);
+            app.view(&Id::ShowNotesPopup, f, popup);
         } else if app.mounted(&Id::SavePlaylistPopup) {
             let popup = draw_area_in_absolute(f.size(), 76, 6);
             f.render_widget(Clear, popup);
@@ -429,6 +437,10 @@ impl Model {
             let popup = draw_area_in_absolute(f.size(), 65, 3);
             f.render_widget(Clear, popup);
             app.view(&Id::PodcastAddPopup, f, popup);
+        } else if app.mounted(&Id::SleepTimerPopup) {
+            let popup = draw_area_in_absolute(f.size(), 65, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::SleepTimerPopup, f, popup);
         }
         if app.mounted(&Id::MessagePopup) {
             let popup = draw_area_top_right_absolute(f.size(), 25, 4);
@@ -815,6 +827,25 @@ impl Model {
         }
     }
 
+    pub fn mount_sleep_timer_popup(&mut self) {
+        assert!(self
+            .app
+            .remount(
+                Id::SleepTimerPopup,
+                Box::new(SleepTimerPopup::new(&self.config.style_color_symbol)),
+                vec![]
+            )
+            .is_ok());
+
+        assert!(self.app.active(&Id::SleepTimerPopup).is_ok());
+    }
+
+    pub fn umount_sleep_timer_popup(&mut self) {
+        if self.app.mounted(&Id::SleepTimerPopup) {
+            assert!(self.app.umount(&Id::SleepTimerPopup).is_ok());
+        }
+    }
+
     pub fn show_message_timeout_label_help<S: AsRef<str>>(
         &mut self,
         active_msg: S,
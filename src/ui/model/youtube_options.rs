@@ -22,16 +22,18 @@
  * SOFTWARE.
  */
 use super::Model;
-use crate::invidious::{Instance, YoutubeVideo};
+use crate::invidious::{video_url, Instance, InstanceSettings, YoutubeList, YoutubeVideo};
 use crate::track::Track;
 use crate::ui::{DLMsg, Id, Msg};
 use crate::utils::get_parent_folder;
 use anyhow::{anyhow, bail, Result};
-use id3::TagLike;
-use id3::Version::Id3v24;
 use lazy_static::lazy_static;
+use lofty::id3::v2::{Frame, FrameFlags, FrameValue, ID3v2Tag, LanguageFrame};
+use lofty::mpeg::MPEGFile;
+use lofty::{Accessor, AudioFile, FileType, ItemKey, TagExt, TaggedFileExt, TextEncoding};
 use regex::Regex;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
 // use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread::{self, sleep};
 use std::time::Duration;
@@ -40,10 +42,13 @@ use tuirealm::{State, StateValue};
 use ytd_rs::{Arg, YoutubeDL};
 
 lazy_static! {
-    static ref RE_FILENAME: Regex =
-        Regex::new(r"\[ffmpeg\] Destination: (?P<name>.*)\.mp3").unwrap();
     static ref RE_FILENAME_YTDLP: Regex =
-        Regex::new(r"\[ExtractAudio\] Destination: (?P<name>.*)\.mp3").unwrap();
+        Regex::new(r"\[ExtractAudio\] Destination: (?P<name>.*\.\w+)").unwrap();
+    // printed instead when the downloaded audio already has the requested codec, e.g. m4a
+    static ref RE_FILENAME_YTDLP_KEPT: Regex = Regex::new(
+        r"\[ExtractAudio\] Not converting audio (?P<name>.*\.\w+); (the )?file is already in target format"
+    )
+    .unwrap();
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -51,6 +56,13 @@ pub struct YoutubeOptions {
     items: Vec<YoutubeVideo>,
     page: u32,
     invidious_instance: Instance,
+    /// Set when showing the videos of a playlist or channel instead of search results.
+    list: Option<YoutubeList>,
+    list_title: String,
+    /// Continuation tokens of the channel pages after the first one.
+    continuations: Vec<String>,
+    /// Videos marked for download, kept across pages.
+    selected: Vec<YoutubeVideo>,
 }
 
 impl YoutubeOptions {
@@ -59,6 +71,10 @@ impl YoutubeOptions {
             items: Vec::new(),
             page: 1,
             invidious_instance: crate::invidious::Instance::default(),
+            list: None,
+            list_title: String::new(),
+            continuations: Vec::new(),
+            selected: Vec::new(),
         }
     }
     pub fn get_by_index(&self, index: usize) -> Result<&YoutubeVideo> {
@@ -68,30 +84,89 @@ impl YoutubeOptions {
         Err(anyhow!("index not found"))
     }
 
+    fn fetch_page(&mut self) -> Result<()> {
+        let Some(list) = &self.list else {
+            self.items = self.invidious_instance.get_search_query(self.page)?;
+            return Ok(());
+        };
+        let index = self.page as usize;
+        let continuation = index
+            .checked_sub(2)
+            .and_then(|i| self.continuations.get(i))
+            .map(String::as_str);
+        let result = self
+            .invidious_instance
+            .get_list_page(list, self.page, continuation)?;
+        if let Some(token) = result.continuation {
+            if self.continuations.len() == index - 1 {
+                self.continuations.push(token);
+            }
+        }
+        self.items = result.videos;
+        Ok(())
+    }
+
     pub fn prev_page(&mut self) -> Result<()> {
         if self.page > 1 {
             self.page -= 1;
-            self.items = self.invidious_instance.get_search_query(self.page)?;
+            self.fetch_page()?;
         }
         Ok(())
     }
 
     pub fn next_page(&mut self) -> Result<()> {
         self.page += 1;
-        self.items = self.invidious_instance.get_search_query(self.page)?;
-        Ok(())
+        self.fetch_page()
     }
 
     pub const fn page(&self) -> u32 {
         self.page
     }
+
+    pub fn is_selected(&self, video: &YoutubeVideo) -> bool {
+        self.selected.iter().any(|v| v.video_id == video.video_id)
+    }
+
+    pub fn toggle_selected(&mut self, index: usize) {
+        let Some(video) = self.items.get(index) else {
+            return;
+        };
+        if self.is_selected(video) {
+            self.selected.retain(|v| v.video_id != video.video_id);
+        } else {
+            self.selected.push(video.clone());
+        }
+    }
+
+    /// Selects every video of the current page, or clears them if all are already selected.
+    pub fn toggle_selected_page(&mut self) {
+        if self.items.iter().all(|v| self.is_selected(v)) {
+            let page: Vec<&str> = self.items.iter().map(|v| v.video_id.as_str()).collect();
+            self.selected
+                .retain(|v| !page.contains(&v.video_id.as_str()));
+        } else {
+            let new: Vec<YoutubeVideo> = self
+                .items
+                .iter()
+                .filter(|v| !self.is_selected(v))
+                .cloned()
+                .collect();
+            self.selected.extend(new);
+        }
+    }
 }
 
 impl Model {
     pub fn youtube_options_download(&mut self, index: usize) -> Result<()> {
+        // download the marked videos one after another
+        if !self.youtube_options.selected.is_empty() {
+            let videos = std::mem::take(&mut self.youtube_options.selected);
+            self.sync_youtube_options();
+            return self.youtube_dl_queue(videos);
+        }
         // download from search result here
         if let Ok(item) = self.youtube_options.get_by_index(index) {
-            let url = format!("https://www.youtube.com/watch?v={}", item.video_id);
+            let url = video_url(&item.video_id);
             if let Err(e) = self.youtube_dl(url.as_ref()) {
                 bail!("Error download: {e}");
             }
@@ -101,14 +176,15 @@ impl Model {
 
     pub fn youtube_options_search(&mut self, keyword: &str) {
         let search_word = keyword.to_string();
+        let settings = InstanceSettings::new(&self.config);
         let tx = self.tx_to_main.clone();
         thread::spawn(
-            move || match crate::invidious::Instance::new(&search_word) {
+            move || match crate::invidious::Instance::new(&search_word, &settings) {
                 Ok((instance, result)) => {
                     let youtube_options = YoutubeOptions {
                         items: result,
-                        page: 1,
                         invidious_instance: instance,
+                        ..YoutubeOptions::new()
                     };
                     tx.send(Msg::Download(DLMsg::YoutubeSearchSuccess(youtube_options)))
                         .ok();
@@ -121,6 +197,59 @@ impl Model {
         );
     }
 
+    pub fn youtube_options_list(&mut self, list: YoutubeList) {
+        let settings = InstanceSettings::new(&self.config);
+        let tx = self.tx_to_main.clone();
+        thread::spawn(move || match Instance::new_for_list(&list, &settings) {
+            Ok((instance, list, page)) => {
+                let youtube_options = YoutubeOptions {
+                    items: page.videos,
+                    invidious_instance: instance,
+                    list: Some(list),
+                    list_title: page.title,
+                    continuations: page.continuation.into_iter().collect(),
+                    ..YoutubeOptions::new()
+                };
+                tx.send(Msg::Download(DLMsg::YoutubeSearchSuccess(youtube_options)))
+                    .ok();
+            }
+            Err(e) => {
+                tx.send(Msg::Download(DLMsg::YoutubeSearchFail(e.to_string())))
+                    .ok();
+            }
+        });
+    }
+
+    /// Resolves the audio stream of a result in the background, so it can be played without
+    /// downloading it first.
+    pub fn youtube_options_play(&mut self, index: usize) -> Result<()> {
+        let video_id = self.youtube_options.get_by_index(index)?.video_id.clone();
+        let instance = self.youtube_options.invidious_instance.clone();
+        let tx = self.tx_to_main.clone();
+        self.show_message_timeout_label_help("Loading youtube stream...", None, None, None);
+        thread::spawn(move || match instance.get_audio_stream(&video_id) {
+            Ok(stream) => {
+                tx.send(Msg::Download(DLMsg::YoutubeStreamReady(stream)))
+                    .ok();
+            }
+            Err(e) => {
+                tx.send(Msg::Download(DLMsg::YoutubeStreamFail(e.to_string())))
+                    .ok();
+            }
+        });
+        Ok(())
+    }
+
+    pub fn youtube_options_toggle(&mut self, index: usize) {
+        self.youtube_options.toggle_selected(index);
+        self.sync_youtube_options();
+    }
+
+    pub fn youtube_options_toggle_page(&mut self) {
+        self.youtube_options.toggle_selected_page();
+        self.sync_youtube_options();
+    }
+
     pub fn youtube_options_prev_page(&mut self) {
         match self.youtube_options.prev_page() {
             Ok(_) => self.sync_youtube_options(),
@@ -136,6 +265,7 @@ impl Model {
     pub fn sync_youtube_options(&mut self) {
         if self.youtube_options.items.is_empty() {
             let table = TableBuilder::default()
+                .add_col(TextSpan::from(" "))
                 .add_col(TextSpan::from("Empty result."))
                 .add_col(TextSpan::from(
                     "Wait 10 seconds but no results, means all servers are down.",
@@ -162,8 +292,14 @@ impl Model {
             let duration_string = format!("[{duration:^10.10}]");
 
             let title = record.title.as_str();
+            let mark = if self.youtube_options.is_selected(record) {
+                "\u{2714}"
+            } else {
+                " "
+            };
 
             table
+                .add_col(TextSpan::new(mark).bold())
                 .add_col(TextSpan::new(duration_string))
                 .add_col(TextSpan::new(title).bold());
         }
@@ -177,11 +313,21 @@ impl Model {
             .ok();
 
         if let Some(domain) = &self.youtube_options.invidious_instance.domain {
+            let keys = &self.config.keys;
+            let mut hint = format!(
+                "Tab/Shift+Tab switch pages, {}/{} mark, {} plays",
+                keys.youtube_search_mark, keys.youtube_search_mark_page, keys.youtube_search_play
+            );
+            let selected = self.youtube_options.selected.len();
+            if selected > 0 {
+                hint = format!("{hint}, Enter downloads {selected} marked");
+            }
+            let mut page = format!("Page {}", self.youtube_options.page());
+            if self.youtube_options.list.is_some() {
+                page = format!("{:.30} {page}", self.youtube_options.list_title);
+            }
             let title = format!(
-                    "\u{2500}\u{2500}\u{2500} Page {} \u{2500}\u{2500}\u{2500}\u{2524} {} \u{251c}\u{2500}\u{2500} {} \u{2500}\u{2500}\u{2500}\u{2500}\u{2500}",
-                    self.youtube_options.page(),
-                    "Tab/Shift+Tab switch pages",
-                    domain,
+                    "\u{2500}\u{2500}\u{2500} {page} \u{2500}\u{2500}\u{2500}\u{2524} {hint} \u{251c}\u{2500}\u{2500} {domain} \u{2500}\u{2500}\u{2500}\u{2500}\u{2500}"
                 );
             self.app
                 .attr(
@@ -193,99 +339,149 @@ impl Model {
         }
     }
 
-    #[allow(clippy::too_many_lines)]
-    pub fn youtube_dl(&mut self, url: &str) -> Result<()> {
+    // the download folder and yt-dlp arguments from the selected download profile
+    fn youtube_dl_target(&self) -> (PathBuf, Vec<Arg>) {
+        let profile = self.config.selected_download_profile();
         let mut path: PathBuf = std::env::temp_dir();
         if let Ok(State::One(StateValue::String(node_id))) = self.app.state(&Id::Library) {
             path = PathBuf::from(get_parent_folder(&node_id));
         }
-        let args = vec![
-            Arg::new("--extract-audio"),
-            // Arg::new_with_arg("--audio-format", "vorbis"),
-            Arg::new_with_arg("--audio-format", "mp3"),
+        let path = profile.destination_or(path);
+        let mut args = profile.audio_args();
+        args.extend([
             Arg::new("--add-metadata"),
             Arg::new("--embed-thumbnail"),
             Arg::new_with_arg("--metadata-from-title", "%(artist) - %(title)s"),
             Arg::new("--write-sub"),
             Arg::new("--all-subs"),
             Arg::new_with_arg("--convert-subs", "lrc"),
-            Arg::new_with_arg("--output", "%(title).90s.%(ext)s"),
-        ];
+            Arg::new_with_arg("--output", &profile.filename_template),
+            // a watch url with `list=` still means the one video
+            Arg::new("--no-playlist"),
+        ]);
+        (path, args)
+    }
 
+    pub fn youtube_dl(&mut self, url: &str) -> Result<()> {
+        let (path, args) = self.youtube_dl_target();
         let ytd = YoutubeDL::new(&path, args, url)?;
         let tx = self.tx_to_main.clone();
         let url = url.to_string();
 
-        thread::spawn(move || -> Result<()> {
-            tx.send(Msg::Download(DLMsg::DownloadRunning(
-                url.clone(),
-                "youtube music".to_string(),
-            )))
-            .ok();
-            // start download
-            let download = ytd.download();
-
-            // check what the result is and print out the path to the download or the error
-            match download {
-                Ok(result) => {
-                    tx.send(Msg::Download(DLMsg::DownloadSuccess(url.clone())))
-                        .ok();
-                    sleep(Duration::from_secs(5));
-                    // here we extract the full file name from download output
-                    if let Some(file_fullname) =
-                        extract_filepath(result.output(), &path.to_string_lossy())
-                    {
-                        tx.send(Msg::Download(DLMsg::DownloadCompleted(
-                            url.to_string(),
-                            Some(file_fullname.clone()),
-                        )))
-                        .ok();
+        thread::spawn(move || {
+            let file = youtube_dl_blocking(&ytd, &path, &url, "youtube music", &tx)
+                .ok()
+                .flatten();
+            sleep(Duration::from_secs(5));
+            tx.send(Msg::Download(DLMsg::DownloadCompleted(url, file)))
+                .ok();
+        });
+        Ok(())
+    }
 
-                        // here we remove downloaded live_chat.json file
-                        remove_downloaded_json(&path, &file_fullname);
+    /// Downloads the videos one at a time, so a long playlist doesn't start dozens of yt-dlp
+    /// processes at once.
+    pub fn youtube_dl_queue(&mut self, videos: Vec<YoutubeVideo>) -> Result<()> {
+        let (path, args) = self.youtube_dl_target();
+        let jobs = videos
+            .into_iter()
+            .map(|video| {
+                let url = video_url(&video.video_id);
+                let ytd = YoutubeDL::new(&path, args.clone(), &url)?;
+                Ok((ytd, url, video.title))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let tx = self.tx_to_main.clone();
 
-                        embed_downloaded_lrc(&path, &file_fullname);
-                    } else {
-                        tx.send(Msg::Download(DLMsg::DownloadCompleted(url.clone(), None)))
-                            .ok();
-                    }
-                }
-                Err(e) => {
-                    tx.send(Msg::Download(DLMsg::DownloadErrDownload(
-                        url.clone(),
-                        "youtube music".to_string(),
-                        e.to_string(),
-                    )))
+        thread::spawn(move || {
+            let total = jobs.len();
+            let mut failed = Vec::new();
+            for (index, (ytd, url, title)) in jobs.into_iter().enumerate() {
+                tx.send(Msg::Download(DLMsg::DownloadQueueItem(index + 1, total)))
+                    .ok();
+                let file =
+                    youtube_dl_blocking(&ytd, &path, &url, &title, &tx).unwrap_or_else(|_| {
+                        failed.push(title);
+                        None
+                    });
+                tx.send(Msg::Download(DLMsg::DownloadCompleted(url, file)))
                     .ok();
-                    sleep(Duration::from_secs(5));
-                    tx.send(Msg::Download(DLMsg::DownloadCompleted(url.clone(), None)))
-                        .ok();
-                }
             }
-            Ok(())
+            tx.send(Msg::Download(DLMsg::DownloadQueueFinished(
+                total - failed.len(),
+                failed,
+            )))
+            .ok();
         });
         Ok(())
     }
 }
+
+// Runs one download and the post-processing, returning the downloaded file when it shows in
+// the output of yt-dlp. Progress and errors are reported to the ui through `tx`.
+fn youtube_dl_blocking(
+    ytd: &YoutubeDL,
+    path: &Path,
+    url: &str,
+    title: &str,
+    tx: &Sender<Msg>,
+) -> Result<Option<String>> {
+    tx.send(Msg::Download(DLMsg::DownloadRunning(
+        url.to_string(),
+        title.to_string(),
+    )))
+    .ok();
+
+    // check what the result is and print out the path to the download or the error
+    match ytd.download() {
+        Ok(result) => {
+            tx.send(Msg::Download(DLMsg::DownloadSuccess(url.to_string())))
+                .ok();
+            // here we extract the full file name from download output
+            let Some(file_fullname) = extract_filepath(result.output(), &path.to_string_lossy())
+            else {
+                // the download itself worked, only the post-processing is skipped
+                tx.send(Msg::Download(DLMsg::DownloadErrFilePath(title.to_string())))
+                    .ok();
+                return Ok(None);
+            };
+
+            // here we remove downloaded live_chat.json file
+            remove_downloaded_json(path, &file_fullname);
+
+            if embed_downloaded_lrc(path, &file_fullname).is_err() {
+                tx.send(Msg::Download(DLMsg::DownloadErrEmbedData(
+                    url.to_string(),
+                    file_fullname.clone(),
+                )))
+                .ok();
+            }
+            Ok(Some(file_fullname))
+        }
+        Err(e) => {
+            tx.send(Msg::Download(DLMsg::DownloadErrDownload(
+                url.to_string(),
+                title.to_string(),
+                e.to_string(),
+            )))
+            .ok();
+            Err(e.into())
+        }
+    }
+}
+
 // This just parsing the output from youtubedl to get the audio path
 // This is used because we need to get the song name
-// example ~/path/to/song/song.mp3
+// example ~/path/to/song/song.opus
 fn extract_filepath(output: &str, dir: &str) -> Option<String> {
-    // #[cfg(not(feature = "yt-dlp"))]
-    // if let Some(cap) = RE_FILENAME.captures(output) {
-    //     if let Some(c) = cap.name("name") {
-    //         let filename = format!("{}/{}.mp3", dir, c.as_str());
-    //         return Ok(filename);
-    //     }
-    // }
-    // #[cfg(feature = "yt-dlp")]
-    if let Some(cap) = RE_FILENAME_YTDLP.captures(output) {
-        if let Some(c) = cap.name("name") {
-            let filename = format!("{dir}/{}.mp3", c.as_str());
-            return Some(filename);
-        }
+    let cap = RE_FILENAME_YTDLP
+        .captures(output)
+        .or_else(|| RE_FILENAME_YTDLP_KEPT.captures(output))?;
+    let name = cap.name("name")?.as_str();
+    if Path::new(name).is_absolute() {
+        return Some(name.to_string());
     }
-    None
+    Some(format!("{dir}/{name}"))
 }
 
 fn remove_downloaded_json(path: &Path, file_fullname: &str) {
@@ -315,20 +511,10 @@ fn remove_downloaded_json(path: &Path, file_fullname: &str) {
     }
 }
 
-fn embed_downloaded_lrc(path: &Path, file_fullname: &str) {
-    let mut id3_tag = if let Ok(tag) = id3::Tag::read_from_path(file_fullname) {
-        tag
-    } else {
-        let mut t = id3::Tag::new();
-        let p: &Path = Path::new(file_fullname);
-        if let Some(p_base) = p.file_stem() {
-            t.set_title(p_base.to_string_lossy());
-        }
-        t.write_to_path(p, Id3v24).ok();
-        t
-    };
-
-    // here we add all downloaded lrc file
+// here we collect the downloaded lrc files, keyed by the language yt-dlp puts in front of the
+// extension, e.g. song.en.lrc
+fn take_downloaded_lrc(path: &Path, file_fullname: &str) -> Vec<(String, String)> {
+    let mut lyrics = Vec::new();
     let files = walkdir::WalkDir::new(path).follow_links(true);
 
     for f in files
@@ -362,15 +548,74 @@ fn embed_downloaded_lrc(path: &Path, file_fullname: &str) {
             }
         }
         let lyric_string = std::fs::read_to_string(f.path());
-        id3_tag.add_frame(id3::frame::Lyrics {
-            lang: "eng".to_string(),
-            description: lang_ext,
-            text: lyric_string.unwrap_or_else(|_| String::from("[00:00:01] No lyric")),
-        });
+        lyrics.push((
+            lang_ext,
+            lyric_string.unwrap_or_else(|_| String::from("[00:00:01] No lyric")),
+        ));
         std::fs::remove_file(f.path()).ok();
     }
+    lyrics
+}
+
+// mp3 gets one USLT frame per language, like the tag editor writes them. Every other container
+// gets the lyrics as items of its primary tag.
+fn embed_downloaded_lrc(path: &Path, file_fullname: &str) -> Result<()> {
+    let lyrics = take_downloaded_lrc(path, file_fullname);
+    let title = Path::new(file_fullname)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string());
 
-    id3_tag.write_to_path(file_fullname, Id3v24).ok();
+    let file_type = lofty::Probe::open(file_fullname)?
+        .guess_file_type()?
+        .file_type()
+        .ok_or_else(|| anyhow!("unknown file type of {file_fullname}"))?;
+
+    if file_type == FileType::MPEG {
+        let mut reader = std::io::BufReader::new(std::fs::File::open(file_fullname)?);
+        let mpeg = MPEGFile::read_from(&mut reader, lofty::ParseOptions::new())?;
+        let mut tag = mpeg.id3v2().cloned().unwrap_or_else(ID3v2Tag::default);
+        if tag.title().is_none() {
+            if let Some(title) = title {
+                tag.set_title(title);
+            }
+        }
+        for (lang_ext, text) in lyrics {
+            let frame = Frame::new(
+                "USLT",
+                FrameValue::UnSyncText(LanguageFrame {
+                    encoding: TextEncoding::UTF8,
+                    language: *b"eng",
+                    description: lang_ext,
+                    content: text,
+                }),
+                FrameFlags::default(),
+            )?;
+            tag.insert(frame);
+        }
+        tag.save_to_path(file_fullname)?;
+        return Ok(());
+    }
+
+    let mut tagged_file = lofty::read_from_path(file_fullname)?;
+    if tagged_file.primary_tag().is_none() {
+        tagged_file.insert_tag(lofty::Tag::new(file_type.primary_tag_type()));
+    }
+    let tag = tagged_file
+        .primary_tag_mut()
+        .ok_or_else(|| anyhow!("cannot create tag for {file_fullname}"))?;
+    if tag.title().is_none() {
+        if let Some(title) = title {
+            tag.set_title(title);
+        }
+    }
+    for (_, text) in lyrics {
+        tag.push_item(lofty::TagItem::new(
+            ItemKey::Lyrics,
+            lofty::ItemValue::Text(text),
+        ));
+    }
+    tag.save_to_path(file_fullname)?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -399,5 +644,21 @@ mod tests {
             .unwrap(),
             "/tmp/观众说“小哥哥，到饭点了”《干饭人之歌》走，端起饭盆干饭去.mp3".to_string()
         );
+        assert_eq!(
+            extract_filepath(
+                "[ExtractAudio] Destination: Artist/Mr. Song.opus\nDeleting original file",
+                "/tmp"
+            )
+            .unwrap(),
+            "/tmp/Artist/Mr. Song.opus".to_string()
+        );
+        assert_eq!(
+            extract_filepath(
+                "[ExtractAudio] Not converting audio Song.m4a; file is already in target format m4a",
+                "/tmp"
+            )
+            .unwrap(),
+            "/tmp/Song.m4a".to_string()
+        );
     }
 }
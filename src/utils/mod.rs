@@ -23,6 +23,8 @@ lazy_static! {
 pub struct DownloadTracker {
     items: HashSet<String>,
     pub time_stamp_for_cache: Instant,
+    /// Position and length of the running download queue.
+    queue: Option<(usize, usize)>,
 }
 
 impl Default for DownloadTracker {
@@ -32,6 +34,7 @@ impl Default for DownloadTracker {
         Self {
             items,
             time_stamp_for_cache,
+            queue: None,
         }
     }
 }
@@ -50,7 +53,30 @@ impl DownloadTracker {
     }
 
     pub fn visible(&self) -> bool {
-        !self.items.is_empty()
+        !self.items.is_empty() || self.queue.is_some()
+    }
+
+    pub fn set_queue_position(&mut self, position: usize, total: usize) {
+        self.queue = Some((position, total));
+    }
+
+    pub fn finish_queue(&mut self) {
+        self.queue = None;
+    }
+
+    pub const fn in_queue(&self) -> bool {
+        self.queue.is_some()
+    }
+
+    pub fn message_queue_finished(downloaded: usize, failed: &[String]) -> String {
+        if failed.is_empty() {
+            format!(" Queue finished, {downloaded} items downloaded. ")
+        } else {
+            format!(
+                " Queue finished, {downloaded} items downloaded, {} failed. ",
+                failed.len()
+            )
+        }
     }
 
     #[allow(unused)]
@@ -101,6 +127,9 @@ impl DownloadTracker {
 
     pub fn message_download_start(&self, title: &str) -> String {
         let len = self.items.len();
+        if let Some((position, total)) = self.queue {
+            return format!(" [{position}/{total}] {title:^.30} downloading... ");
+        }
         if len > 1 {
             format!(" {len} items downloading... ")
         } else {
@@ -411,6 +440,27 @@ fn playlist_get_absolute_pathbuf(item: &str, p_base: &Path) -> Result<PathBuf> {
     Ok(pathbuf)
 }
 
+/// Opens a web link with the default browser of the desktop. Only http and https links are
+/// accepted, as they come from untrusted feeds.
+pub fn open_url(url: &str) -> Result<()> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        bail!("only http links can be opened: {url}");
+    }
+    #[cfg(target_os = "macos")]
+    let opener = "open";
+    #[cfg(not(target_os = "macos"))]
+    let opener = "xdg-open";
+    let mut child = std::process::Command::new(opener)
+        .arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    // reap the process once the browser got the link
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
 /// Some helper functions for dealing with Unicode strings.
 #[allow(clippy::module_name_repetitions)]
 pub trait StringUtils {